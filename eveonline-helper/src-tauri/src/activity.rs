@@ -0,0 +1,252 @@
+//! Activities fits are generated for in EveOnline Helper
+//
+// An activity is what the player wants to do with the fit (missions, mining, exploration,
+// PvP, ratting, hauling) together with the space it happens in. Each activity profile
+// carries:
+// - objective weights: how much DPS, tank, mining yield, tackle, agility and cargo count
+//   when fits are scored for the activity
+// - the damage it deals to the player and the targets the player shoots at
+// - minimum safety thresholds (EHP, cap stability, align time)
+// - optionally an environment (wormhole effect, abyssal weather, incursion), applied when
+//   fits are evaluated
+//
+// Built-in profiles ship in `resources/activities.json`. Users can add their own or
+// override a built-in one by name; those are stored under `data/`, like boost presets.
+
+use crate::environment::EnvironmentProfile;
+use crate::ewar::tackle_score;
+use crate::fitting::{DamageProfile, FitStats, TargetProfile};
+use crate::mining::OreType;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const BUILTIN_ACTIVITIES: &str = include_str!("../resources/activities.json");
+const USER_ACTIVITIES_PATH: &str = "data/activities.json";
+/// Value used for a weighted stat of 0 so fits without it still rank among themselves
+const STAT_FLOOR: f64 = 1e-3;
+
+/// Site types found while exploring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SiteType {
+    Data,
+    Relic,
+    Combat,
+}
+
+/// Role of a fit in a PvP fleet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PvpRole {
+    Dps,
+    Tackle,
+    Logistics,
+    Ewar,
+}
+
+/// What the player does with the fit
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActivityKind {
+    Mission {
+        /// Agent level (1-5)
+        level: u8,
+        /// SDE faction ID of the enemy, if known (e.g. 500010 for the Guristas)
+        #[serde(default)]
+        faction_id: Option<i64>,
+        /// SDE NPC corporation of the agent; decides the enemy if the faction is not set
+        #[serde(default)]
+        agent_corporation_id: Option<i64>,
+    },
+    Mining {
+        #[serde(default)]
+        ore: Option<OreType>,
+    },
+    Exploration {
+        /// None for any site type
+        #[serde(default)]
+        site: Option<SiteType>,
+    },
+    Pvp {
+        role: PvpRole,
+    },
+    Ratting,
+    Hauling,
+}
+
+/// How much each stat counts when scoring fits for an activity (0 = ignored)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ObjectiveWeights {
+    /// Applied DPS against the activity's target
+    pub dps: f64,
+    /// EHP against the activity's incoming damage
+    pub tank: f64,
+    /// Mining yield in m3/s
+    pub mining: f64,
+    /// Tackle score (points, range, webs, speed)
+    pub tackle: f64,
+    /// Inverse align time
+    pub agility: f64,
+    /// Cargo capacity in m3
+    pub cargo: f64,
+}
+
+/// Minimum a fit should reach to be considered safe for an activity
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SafetyThresholds {
+    pub min_ehp: f64,
+    pub require_cap_stable: bool,
+    /// Longest acceptable align time in seconds
+    pub max_align_time: Option<f64>,
+    /// Expected incoming DPS (0 if unknown)
+    pub incoming_dps: f64,
+}
+
+/// The activity a fit is generated for
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Activity {
+    /// Profile name (e.g., "Level 4 Missions", "PvP Tackle")
+    pub name: String,
+    pub kind: ActivityKind,
+    pub weights: ObjectiveWeights,
+    /// Damage types the player takes, as fractions of the total
+    pub incoming_damage: DamageProfile,
+    /// What the player shoots at
+    pub target: TargetProfile,
+    #[serde(default)]
+    pub safety: SafetyThresholds,
+    /// System effect active where the activity takes place, if any
+    #[serde(default)]
+    pub environment: Option<EnvironmentProfile>,
+}
+
+impl Activity {
+    /// Score of a fit's stats for this activity (higher is better)
+    ///
+    /// Sum of weight x ln(stat) over the weighted stats (the log of their weighted
+    /// product), so each stat counts by its relative improvement regardless of its unit.
+    pub fn score(&self, stats: &FitStats) -> f64 {
+        let w = &self.weights;
+        let applied_dps: f64 = stats
+            .weapons
+            .iter()
+            .map(|weapon| weapon.applied_dps(&self.target))
+            .sum();
+        let agility = if stats.align_time > 0.0 {
+            1.0 / stats.align_time
+        } else {
+            1.0
+        };
+        [
+            (w.dps, applied_dps),
+            (w.tank, stats.ehp_against(&self.incoming_damage)),
+            (w.mining, stats.mining_yield),
+            (w.tackle, tackle_score(stats)),
+            (w.agility, agility),
+            (w.cargo, stats.cargo_capacity),
+        ]
+        .iter()
+        .filter(|(weight, _)| *weight > 0.0)
+        .map(|(weight, stat)| weight * stat.max(STAT_FLOOR).ln())
+        .sum()
+    }
+}
+
+/// Activity profiles that ship with the app
+pub fn builtin_activities() -> Vec<Activity> {
+    serde_json::from_str(BUILTIN_ACTIVITIES).expect("built-in activity profiles are valid")
+}
+
+fn load_user_activities() -> Result<Vec<Activity>, String> {
+    match fs::read_to_string(USER_ACTIVITIES_PATH) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse activity profiles: {}", e)),
+        Err(_) => Ok(vec![]),
+    }
+}
+
+fn write_user_activities(activities: &[Activity]) -> Result<(), String> {
+    fs::create_dir_all("data").map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(activities)
+        .map_err(|e| format!("Failed to serialize activity profiles: {}", e))?;
+    fs::write(USER_ACTIVITIES_PATH, json)
+        .map_err(|e| format!("Failed to write activity profiles: {}", e))
+}
+
+/// Built-in and user activity profiles (user profiles replace built-ins of the same name)
+pub fn load_activities() -> Result<Vec<Activity>, String> {
+    let mut activities = builtin_activities();
+    for activity in load_user_activities()? {
+        match activities.iter_mut().find(|a| a.name == activity.name) {
+            Some(existing) => *existing = activity,
+            None => activities.push(activity),
+        }
+    }
+    Ok(activities)
+}
+
+/// Save a user activity profile (replaces an existing user profile with the same name)
+pub fn save_activity(activity: Activity) -> Result<(), String> {
+    let mut activities = load_user_activities()?;
+    activities.retain(|a| a.name != activity.name);
+    activities.push(activity);
+    write_user_activities(&activities)
+}
+
+/// Delete a user activity profile (a built-in profile of the same name comes back)
+pub fn delete_activity(name: &str) -> Result<(), String> {
+    let mut activities = load_user_activities()?;
+    activities.retain(|a| a.name != name);
+    write_user_activities(&activities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fitting::calculate_fit_stats;
+    use crate::test_support::{activity, mining_fit};
+
+    #[test]
+    fn test_activity_profiles() {
+        let activities = builtin_activities();
+        assert!(activities
+            .iter()
+            .any(|a| matches!(a.kind, ActivityKind::Mission { level: 4, .. })));
+        let tackle = activity("PvP Tackle");
+        assert!(matches!(
+            tackle.kind,
+            ActivityKind::Pvp {
+                role: PvpRole::Tackle
+            }
+        ));
+
+        // User profiles only need a name, kind, damage and target; weights default to 0
+        let custom: Activity = serde_json::from_str(
+            r#"{
+                "name": "Guristas Level 4s",
+                "kind": { "type": "mission", "level": 4, "faction_id": 500010 },
+                "weights": { "dps": 2.0, "tank": 1.0 },
+                "incoming_damage": { "em": 0.0, "thermal": 0.2, "kinetic": 0.8, "explosive": 0.0 },
+                "target": {
+                    "resists": { "em": 0.3, "thermal": 0.3, "kinetic": 0.3, "explosive": 0.3 },
+                    "distance": 30000.0
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(custom.weights.mining, 0.0);
+        assert_eq!(custom.safety.min_ehp, 0.0);
+
+        // Mining profiles prefer the fit that mines more
+        let mining = activity("Mining");
+        let mut unfitted = mining_fit(false);
+        unfitted.modules.clear();
+        let unfitted = calculate_fit_stats(&unfitted, &[]);
+        let strip_miner = calculate_fit_stats(&mining_fit(false), &[]);
+        assert!(mining.score(&strip_miner) > mining.score(&unfitted));
+        // ... and ignore DPS
+        assert_eq!(mining.weights.dps, 0.0);
+    }
+}
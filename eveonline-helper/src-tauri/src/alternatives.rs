@@ -0,0 +1,633 @@
+//! Alternative fits for EveOnline Helper
+//
+// Repairs a fit the character cannot fly:
+// 1. every module the character lacks the skills for is swapped for the best variation
+//    they can use (highest `metaLevelOld`: T2, then meta, then T1), or removed if there is
+//    none; variations share a variation parent (`Module::variation_parent_id`). Only tech I
+//    and II variations and those of the module's own meta group are candidates, so a
+//    repair never turns to pricier faction, officer or deadspace modules
+// 2. every weapon loaded with a charge the character lacks the skills for is reloaded
+//    with the best usable charge for the target, or unloaded if there is none
+// 3. while CPU or powergrid is overloaded, a fitting implant, rig or low slot fitting
+//    module (co-processor, reactor control unit, ...) that raises the overloaded output
+//    is added if there is room for it (implant slots the clone already fills are not);
+//    otherwise the module whose removal costs the least is dropped
+// Modules the player locked are never swapped or removed, and keep the charge they were
+// locked with; a fit that cannot be made flyable around them gets no alternative.
+// Every change is recorded on the alternative with its stat cost. A second alternative
+// is built with step 3 only dropping modules, for characters who would rather not buy
+// fitting upgrades. Alternatives that still fail validation (e.g. hull skills or slots
+// repair does not touch) are not suggested.
+
+use crate::charges::{can_use_charge, select_best_charge};
+use crate::esi::{validate_fit_with, Charge, FitChange, FitVariant, Module, SlotType};
+use crate::fitting::{
+    attr, calculate_attributes_with, calculate_fit_stats_with, CalcContext, FitStats, Modifier,
+    ModifierTarget, StatDelta, TargetProfile,
+};
+use crate::implants::Implant;
+use crate::optimizer::{locked_positions, LockedModule};
+
+/// Fitting resource a fit can run out of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resource {
+    Cpu,
+    Powergrid,
+}
+
+impl Resource {
+    fn output_attribute(self) -> &'static str {
+        match self {
+            Resource::Cpu => attr::CPU_OUTPUT,
+            Resource::Powergrid => attr::POWER_OUTPUT,
+        }
+    }
+
+    fn usage_attribute(self) -> &'static str {
+        match self {
+            Resource::Cpu => attr::CPU,
+            Resource::Powergrid => attr::POWER,
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            Resource::Cpu => Resource::Powergrid,
+            Resource::Powergrid => Resource::Cpu,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Resource::Cpu => "CPU",
+            Resource::Powergrid => "powergrid",
+        }
+    }
+
+    /// Usage beyond the fit's output (0 if within)
+    fn overage(self, stats: &FitStats) -> f64 {
+        match self {
+            Resource::Cpu => (stats.cpu_used - stats.cpu_output).max(0.0),
+            Resource::Powergrid => (stats.powergrid_used - stats.powergrid_output).max(0.0),
+        }
+    }
+}
+
+fn can_use(required_skills: &[(i64, String, i32)], ctx: &CalcContext) -> bool {
+    required_skills
+        .iter()
+        .all(|(skill_id, _, level)| ctx.skill_level(*skill_id) >= *level)
+}
+
+/// Variation family of a module: its parent type, or itself for T1 modules
+fn variation_family(module: &Module) -> i64 {
+    module.variation_parent_id.unwrap_or(module.module_id)
+}
+
+/// Meta groups of tech I and tech II modules
+const TECH_META_GROUPS: [i64; 2] = [1, 2];
+
+/// Whether a variation may replace a module: tech I or II, or of the module's meta group
+fn is_replacement_tier(module: &Module, variation: &Module) -> bool {
+    variation.meta_group_id.is_none_or(|group| {
+        TECH_META_GROUPS.contains(&group) || module.meta_group_id == Some(group)
+    })
+}
+
+fn meta_level(module: &Module) -> f64 {
+    module
+        .attributes
+        .get(attr::META_LEVEL)
+        .copied()
+        .unwrap_or(0.0)
+}
+
+/// Whether a set of modifiers raises a fitting output on the ship
+fn raises_output(effects: &[Modifier], resource: Resource) -> bool {
+    effects.iter().any(|m| {
+        m.target == ModifierTarget::Ship
+            && m.attribute == resource.output_attribute()
+            && m.value > 0.0
+    })
+}
+
+/// Relative loss of the stats a module contributes to when going from `before` to `after`
+fn value_lost(before: &FitStats, after: &FitStats) -> f64 {
+    let relative = |b: f64, a: f64| if b > 0.0 { (b - a) / b } else { 0.0 };
+    relative(before.dps, after.dps)
+        + relative(before.ehp, after.ehp)
+        + relative(before.max_velocity, after.max_velocity)
+        + relative(
+            before.shield_boost_rate + before.armor_repair_rate,
+            after.shield_boost_rate + after.armor_repair_rate,
+        )
+        + relative(before.mining_yield, after.mining_yield)
+}
+
+/// A fit being repaired, with its changes so far
+struct Repair<'a> {
+    fit: FitVariant,
+    ctx: &'a CalcContext,
+    locked: &'a [LockedModule],
+}
+
+impl Repair<'_> {
+    fn stats(&self) -> FitStats {
+        calculate_fit_stats_with(&self.fit, self.ctx)
+    }
+
+    /// Whether the module at `index` is one the player locked
+    fn is_locked(&self, index: usize) -> bool {
+        locked_positions(&self.fit, self.locked).contains(&Some(index))
+    }
+
+    /// Whether the module at `index` was locked with its charge
+    fn is_charge_locked(&self, index: usize) -> bool {
+        self.locked
+            .iter()
+            .zip(locked_positions(&self.fit, self.locked))
+            .any(|(locked, position)| position == Some(index) && locked.charge.is_some())
+    }
+
+    /// Apply a change to the fit and record it with its stat cost
+    fn change(&mut self, description: String, apply: impl FnOnce(&mut FitVariant)) {
+        let before = self.stats();
+        apply(&mut self.fit);
+        let after = self.stats();
+        self.fit.changes.push(FitChange {
+            description,
+            stat_delta: StatDelta::between(&before, &after),
+        });
+    }
+
+    /// Swap modules the character cannot use for usable variations
+    fn downgrade_modules(&mut self, eligible_modules: &[Module]) {
+        let mut index = 0;
+        while index < self.fit.modules.len() {
+            let module = self.fit.modules[index].clone();
+            if can_use(&module.required_skills, self.ctx) || self.is_locked(index) {
+                index += 1;
+                continue;
+            }
+            let family = variation_family(&module);
+            let replacement = eligible_modules
+                .iter()
+                .filter(|m| {
+                    variation_family(m) == family
+                        && is_replacement_tier(&module, m)
+                        && can_use(&m.required_skills, self.ctx)
+                })
+                .reduce(|best, m| {
+                    if meta_level(m) > meta_level(best) {
+                        m
+                    } else {
+                        best
+                    }
+                })
+                .cloned();
+            match replacement {
+                Some(mut replacement) => {
+                    replacement.charge = module.charge.clone();
+                    replacement.state = module.state;
+                    self.change(
+                        format!(
+                            "Replaced {} with {}",
+                            module.module_name, replacement.module_name
+                        ),
+                        |fit| fit.modules[index] = replacement,
+                    );
+                    index += 1;
+                }
+                None => self.change(
+                    format!("Removed {}: no variation is usable", module.module_name),
+                    |fit| {
+                        fit.modules.remove(index);
+                    },
+                ),
+            }
+        }
+    }
+
+    /// Reload weapons whose charge needs skills the character lacks
+    fn downgrade_charges(&mut self, charges: &[Charge], target: &TargetProfile) {
+        for index in 0..self.fit.modules.len() {
+            let Some(charge) = self.fit.modules[index].charge.clone() else {
+                continue;
+            };
+            if can_use_charge(&charge, self.ctx) || self.is_charge_locked(index) {
+                continue;
+            }
+            let replacement =
+                select_best_charge(&self.fit, index, charges, target, &self.ctx.skills);
+            let description = match &replacement {
+                Some(replacement) => format!(
+                    "Replaced {} with {}",
+                    charge.charge_name, replacement.charge_name
+                ),
+                None => format!("Unloaded {}: no usable charge", charge.charge_name),
+            };
+            self.change(description, |fit| fit.modules[index].charge = replacement);
+        }
+    }
+
+    /// Add the fitting implant, rig or low slot module that reduces the overage most
+    fn add_fitting_upgrade(
+        &mut self,
+        resource: Resource,
+        eligible_modules: &[Module],
+        fitting_implants: &[Implant],
+    ) -> bool {
+        let current = self.stats();
+        let overage = resource.overage(&current);
+        let other = resource.other();
+        let calc = calculate_attributes_with(&self.fit, self.ctx);
+        let free_slot = |slot: SlotType| {
+            self.fit
+                .modules
+                .iter()
+                .filter(|m| m.slot == Some(slot))
+                .count()
+                < calc.slot_count(slot)
+        };
+
+        // (remaining overage, fit with the upgrade, description)
+        let mut best: Option<(f64, FitVariant, String)> = None;
+        let mut consider = |trial: FitVariant, description: String| {
+            let stats = calculate_fit_stats_with(&trial, self.ctx);
+            let trial_overage = resource.overage(&stats);
+            let acceptable = trial_overage < overage
+                && other.overage(&stats) <= other.overage(&current)
+                && stats.calibration_used <= stats.calibration_output;
+            if acceptable
+                && best
+                    .as_ref()
+                    .map(|(o, _, _)| trial_overage < *o)
+                    .unwrap_or(true)
+            {
+                best = Some((trial_overage, trial, description));
+            }
+        };
+        for implant in fitting_implants {
+            let slot_taken = self
+                .fit
+                .implants
+                .iter()
+                .chain(&self.ctx.implants)
+                .any(|i| i.slot == implant.slot);
+            if slot_taken || !raises_output(&implant.effects, resource) {
+                continue;
+            }
+            let mut trial = self.fit.clone();
+            trial.implants.push(implant.clone());
+            consider(trial, format!("Plugged in {}", implant.implant_name));
+        }
+        for module in eligible_modules {
+            let Some(slot) = module.slot else {
+                continue;
+            };
+            if !matches!(slot, SlotType::Rig | SlotType::Low)
+                || !free_slot(slot)
+                || !raises_output(&module.effects, resource)
+                || !can_use(&module.required_skills, self.ctx)
+            {
+                continue;
+            }
+            let mut trial = self.fit.clone();
+            trial.modules.push(module.clone());
+            consider(trial, format!("Fitted {}", module.module_name));
+        }
+
+        match best {
+            Some((_, trial, description)) => {
+                let description = format!("{} for {}", description, resource.label());
+                self.change(description, |fit| *fit = trial);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop the module using the resource whose removal costs the least
+    fn drop_cheapest_module(&mut self, resource: Resource) -> bool {
+        let before = self.stats();
+        let calc = calculate_attributes_with(&self.fit, self.ctx);
+        let mut cheapest: Option<(f64, usize)> = None;
+        for index in 0..self.fit.modules.len() {
+            if calc.module_attr(index, resource.usage_attribute()) <= 0.0 || self.is_locked(index) {
+                continue;
+            }
+            let mut trial = self.fit.clone();
+            trial.modules.remove(index);
+            let lost = value_lost(&before, &calculate_fit_stats_with(&trial, self.ctx));
+            if cheapest.map(|(c, _)| lost < c).unwrap_or(true) {
+                cheapest = Some((lost, index));
+            }
+        }
+        let Some((_, index)) = cheapest else {
+            return false;
+        };
+        let name = self.fit.modules[index].module_name.clone();
+        self.change(
+            format!("Removed {} to free {}", name, resource.label()),
+            |fit| {
+                fit.modules.remove(index);
+            },
+        );
+        true
+    }
+
+    /// Bring CPU and powergrid within the fit's output
+    fn fix_resources(
+        &mut self,
+        eligible_modules: &[Module],
+        fitting_implants: &[Implant],
+        use_upgrades: bool,
+    ) {
+        for resource in [Resource::Cpu, Resource::Powergrid] {
+            while resource.overage(&self.stats()) > 0.0 {
+                let upgraded = use_upgrades
+                    && self.add_fitting_upgrade(resource, eligible_modules, fitting_implants);
+                if !upgraded && !self.drop_cheapest_module(resource) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Repair a fit the character cannot fly
+///
+/// # Arguments
+/// * `fit` - A fit that failed validation
+/// * `ctx` - The character's skills, clone implants and the rest of the context
+/// * `eligible_modules` - Modules to pick variations and fitting upgrades from
+/// * `charges` - Charges to reload weapons with
+/// * `target` - Target the replacement charges are picked for
+/// * `fitting_implants` - Implants that may be plugged in to gain CPU or powergrid
+/// * `locked` - Modules the player locked, matched in the fit by type ID and slot
+///
+/// # Returns
+/// - Up to two flyable alternatives (with fitting upgrades, and by only removing
+///   modules), each listing its changes and their stat cost; empty if the fit needed no
+///   changes or could not be made flyable
+pub fn repair_fit(
+    fit: &FitVariant,
+    ctx: &CalcContext,
+    eligible_modules: &[Module],
+    charges: &[Charge],
+    target: &TargetProfile,
+    fitting_implants: &[Implant],
+    locked: &[LockedModule],
+) -> Vec<FitVariant> {
+    let mut alternatives: Vec<FitVariant> = vec![];
+    for (use_upgrades, suffix) in [(true, "Alternative"), (false, "Stripped")] {
+        let mut repair = Repair {
+            fit: FitVariant {
+                changes: vec![],
+                stats: None,
+                ..fit.clone()
+            },
+            ctx,
+            locked,
+        };
+        repair.downgrade_modules(eligible_modules);
+        repair.downgrade_charges(charges, target);
+        repair.fix_resources(eligible_modules, fitting_implants, use_upgrades);
+        let mut alternative = repair.fit;
+        if alternative.changes.is_empty() || !validate_fit_with(&alternative, ctx).is_valid {
+            continue;
+        }
+        let same_as_previous = alternatives.iter().any(|a| {
+            a.changes
+                .iter()
+                .map(|c| &c.description)
+                .eq(alternative.changes.iter().map(|c| &c.description))
+        });
+        if same_as_previous {
+            continue;
+        }
+        alternative.fit_name = format!("{} ({})", fit.fit_name, suffix);
+        alternative.rationale = alternative
+            .changes
+            .iter()
+            .map(|c| c.description.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        alternatives.push(alternative);
+    }
+    alternatives
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::esi::{
+        suggest_alternative_fits, validate_fit, validate_fit_with, FitVariant, Module, Ship, Skill,
+        SlotType,
+    };
+    use crate::fitting::{CalcContext, DamageProfile, Modifier, ModifierTarget, TargetProfile};
+    use crate::implants::Implant;
+    use crate::optimizer::LockedModule;
+    use crate::test_support::{attrs, hybrid_charge, large_blaster, magnetic_field_stabilizer};
+
+    #[test]
+    fn test_suggest_alternative_fits() {
+        // T2 blaster (needs Large Hybrid Turret 5) with its meta and T1 variations
+        let mut t2 = large_blaster();
+        t2.variation_parent_id = Some(3170);
+        t2.meta_group_id = Some(2);
+        t2.attributes.insert("metaLevelOld".to_string(), 5.0);
+        t2.charge = Some(hybrid_charge("Antimatter Charge L", 10.0, 1.0));
+        let variation = |id: i64, name: &str, meta: f64, damage: f64, cpu: f64| {
+            let mut module = large_blaster();
+            module.module_id = id;
+            module.module_name = name.to_string();
+            module.variation_parent_id = (id != 3170).then_some(3170);
+            module.meta_group_id = Some(1);
+            module.required_skills = vec![(3307, "Large Hybrid Turret".to_string(), 1)];
+            module.attributes.insert("metaLevelOld".to_string(), meta);
+            module
+                .attributes
+                .insert("damageMultiplier".to_string(), damage);
+            module.attributes.insert("cpu".to_string(), cpu);
+            module
+        };
+        let t1 = variation(3170, "Neutron Blaster Cannon I", 0.0, 3.5, 45.0);
+        let meta = variation(7783, "Modal Neutron Particle Accelerator I", 1.0, 3.8, 48.0);
+        // A faction variation is never picked to replace a T2 module
+        let mut faction = variation(
+            14377,
+            "Shadow Serpentis Neutron Blaster Cannon",
+            8.0,
+            4.6,
+            45.0,
+        );
+        faction.meta_group_id = Some(4);
+        let mut damage_mod = magnetic_field_stabilizer();
+        damage_mod.attributes.insert("cpu".to_string(), 20.0);
+        let cpu_rig = Module {
+            module_id: 31378,
+            module_name: "Large Processor Overclocking Unit I".to_string(),
+            group_id: 781,
+            slot: Some(SlotType::Rig),
+            attributes: attrs(&[("upgradeCost", 100.0)]),
+            effects: vec![Modifier::percent(ModifierTarget::Ship, "cpuOutput", 10.0)],
+            ..Default::default()
+        };
+        let cpu_implant = Implant {
+            implant_id: 27070,
+            implant_name: "Zainou 'Gypsy' CPU Management EE-610".to_string(),
+            slot: 6,
+            effects: vec![Modifier::percent(ModifierTarget::Ship, "cpuOutput", 10.0)],
+            set_bonus: None,
+        };
+        let fit = FitVariant {
+            fit_name: "Blasterthron".to_string(),
+            ship: Ship {
+                ship_id: 641,
+                ship_name: "Megathron".to_string(),
+                attributes: attrs(&[
+                    ("hiSlots", 2.0),
+                    ("turretSlotsLeft", 2.0),
+                    ("lowSlots", 1.0),
+                    ("rigSlots", 1.0),
+                    ("upgradeCapacity", 400.0),
+                    ("cpuOutput", 100.0),
+                    ("powerOutput", 20_000.0),
+                ]),
+                ..Default::default()
+            },
+            modules: vec![t2.clone(), t2, damage_mod],
+            ..Default::default()
+        };
+        let skills = vec![Skill {
+            skill_id: 3307,
+            skill_name: Some("Large Hybrid Turret".to_string()),
+            active_level: 4,
+        }];
+        let modules = vec![t1, meta, faction, cpu_rig];
+        let ctx = CalcContext::from_skills(&skills);
+        let target = TargetProfile {
+            resists: DamageProfile::default(),
+            distance: 0.0,
+        };
+        let charges = vec![hybrid_charge("Antimatter Charge L", 10.0, 1.0)];
+        let alternatives = suggest_alternative_fits(
+            &fit,
+            &ctx,
+            &modules,
+            &charges,
+            &target,
+            std::slice::from_ref(&cpu_implant),
+            &[],
+        );
+        assert_eq!(alternatives.len(), 2);
+
+        // The meta blasters need 116 tf with the damage mod: implant and rig make room
+        let upgraded = &alternatives[0];
+        assert!(validate_fit(upgraded, &skills).is_valid);
+        let blasters: Vec<_> = upgraded
+            .modules
+            .iter()
+            .filter(|m| m.group_id == 74)
+            .collect();
+        assert_eq!(blasters.len(), 2);
+        assert!(
+            blasters
+                .iter()
+                .all(|m| m.module_name == "Modal Neutron Particle Accelerator I"
+                    && m.charge.is_some())
+        );
+        assert_eq!(upgraded.implants.len(), 1);
+        assert_eq!(
+            upgraded.changes[0].description,
+            "Replaced Neutron Blaster Cannon II with Modal Neutron Particle Accelerator I"
+        );
+        assert!(upgraded.changes[0].stat_delta.dps < 0.0);
+        assert!(upgraded.changes.iter().any(|c| c
+            .description
+            .starts_with("Fitted Large Processor Overclocking Unit I")));
+
+        // Without fitting upgrades the damage mod goes
+        let stripped = &alternatives[1];
+        assert!(validate_fit(stripped, &skills).is_valid);
+        assert!(stripped.implants.is_empty());
+        let last = stripped.changes.last().unwrap();
+        assert_eq!(
+            last.description,
+            "Removed Magnetic Field Stabilizer II to free CPU"
+        );
+        assert!(last.stat_delta.dps < 0.0);
+
+        // A locked damage mod stays: a blaster goes instead
+        let lock = |module: &Module, slot| LockedModule {
+            module: module.clone(),
+            slot,
+            charge: None,
+        };
+        let locked = [lock(&fit.modules[2], SlotType::Low)];
+        let alternatives =
+            suggest_alternative_fits(&fit, &ctx, &modules, &charges, &target, &[], &locked);
+        let stripped = alternatives.last().unwrap();
+        assert!(validate_fit(stripped, &skills).is_valid);
+        assert!(stripped
+            .modules
+            .iter()
+            .any(|m| m.module_id == fit.modules[2].module_id));
+        assert!(stripped
+            .changes
+            .iter()
+            .all(|c| !c.description.contains("Magnetic Field Stabilizer")));
+
+        // A locked T2 blaster the character cannot use is not swapped: nothing flyable
+        let locked = [lock(&fit.modules[0], SlotType::High)];
+        assert!(suggest_alternative_fits(
+            &fit,
+            &ctx,
+            &modules,
+            &charges,
+            &target,
+            std::slice::from_ref(&cpu_implant),
+            &locked
+        )
+        .is_empty());
+
+        // The clone's own slot 6 implant stays; T2 ammo without the skill is swapped out
+        let mut fit = fit;
+        let mut void = hybrid_charge("Void L", 12.0, 0.75);
+        void.required_skills = vec![(12209, "Large Blaster Specialization".to_string(), 1)];
+        fit.modules[0].charge = Some(void);
+        let cloned = CalcContext {
+            implants: vec![Implant {
+                implant_id: 13283,
+                implant_name: "Inherent Implants 'Noble' Repair Systems RS-606".to_string(),
+                effects: vec![],
+                ..cpu_implant.clone()
+            }],
+            ..ctx.clone()
+        };
+        let alternatives = suggest_alternative_fits(
+            &fit,
+            &cloned,
+            &modules,
+            &charges,
+            &target,
+            &[cpu_implant],
+            &[],
+        );
+        let upgraded = &alternatives[0];
+        assert!(upgraded.implants.is_empty());
+        assert!(upgraded
+            .changes
+            .iter()
+            .any(|c| c.description == "Replaced Void L with Antimatter Charge L"));
+        assert!(alternatives
+            .iter()
+            .all(|fit| validate_fit_with(fit, &cloned).is_valid));
+
+        // Repair does not train hull skills: no flyable alternative
+        fit.ship.required_skills = vec![(3336, "Gallente Battleship".to_string(), 1)];
+        assert!(
+            suggest_alternative_fits(&fit, &ctx, &modules, &charges, &target, &[], &[]).is_empty()
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! Character assets for EveOnline Helper
+//
+// "Use what I have" mode: the character's assets are fetched from ESI (scope
+// `esi-assets.read_assets.v1`) and cached under `data/`, one file per character, until
+// the expiry ESI sends with them (assets are cached for an hour on ESI's side too). Fits
+// are then built only from module types the character owns, either anywhere or in one
+// station or structure. Items inside ships and containers count for the station they
+// are in.
+//
+// Every item of a fit is marked with how many are on hand, so the player knows what
+// still has to be bought (more copies of a module than owned, the hull, ammo, ...).
+
+use crate::esi::{FitVariant, Module};
+use crate::prices::fit_items;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// ESI cache time of character assets, used when a response has no `expires` header
+const ASSET_CACHE_SECS: u64 = 3600;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Quantity owned, keyed by SDE type ID
+pub type OwnedItems = HashMap<i64, u32>;
+
+/// An item in a character's assets, as returned by ESI
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Asset {
+    pub item_id: i64,
+    pub type_id: i64,
+    /// Station, structure or solar system, or the item_id of the ship or container
+    /// the item is in
+    pub location_id: i64,
+    /// "station", "solar_system", "item" or "other"
+    pub location_type: String,
+    /// Where in its location the item is (e.g. "Hangar", "Cargo", "HiSlot0")
+    #[serde(default)]
+    pub location_flag: String,
+    pub quantity: u32,
+    #[serde(default)]
+    pub is_singleton: bool,
+}
+
+/// A character's assets as last fetched
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssetCache {
+    pub character_id: i64,
+    /// Unix time of the fetch
+    pub fetched_at: u64,
+    /// Unix time at which ESI has newer data (0 for caches written without it)
+    #[serde(default)]
+    pub expires_at: u64,
+    pub assets: Vec<Asset>,
+}
+
+impl AssetCache {
+    /// Whether ESI's cache expiry has passed, so the assets should be fetched again
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Assets of one fetch and the time ESI has newer data
+pub struct FetchedAssets {
+    pub assets: Vec<Asset>,
+    /// Unix time from the `expires` header of the first page
+    pub expires_at: u64,
+}
+
+/// Where owned items are taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssetScope {
+    /// One station or structure
+    Station { location_id: i64 },
+    /// The whole inventory
+    Everywhere,
+}
+
+/// The assets a fit may be built from
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct AssetSelection {
+    pub character_id: i64,
+    pub scope: AssetScope,
+}
+
+/// How many of one item a fit needs and how many the character has
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ItemAvailability {
+    pub type_id: i64,
+    pub name: String,
+    /// Quantity the fit needs
+    pub quantity: u32,
+    /// Quantity owned (at most `quantity`)
+    pub on_hand: u32,
+    /// Quantity to buy
+    pub to_buy: u32,
+}
+
+fn cache_path(character_id: i64) -> String {
+    format!("data/assets_{}.json", character_id)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Parse an HTTP date (IMF-fixdate, e.g. "Sun, 19 Oct 2026 12:00:00 GMT") to Unix time
+pub fn parse_http_date(date: &str) -> Option<u64> {
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    if parts.len() != 6 || parts[5] != "GMT" {
+        return None;
+    }
+    let day: u64 = parts[1].parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == parts[2])? as u64 + 1;
+    let year: u64 = parts[3].parse().ok()?;
+    let time: Vec<u64> = parts[4]
+        .split(':')
+        .map(|t| t.parse().ok())
+        .collect::<Option<_>>()?;
+    if time.len() != 3 || year < 1970 || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Days since 1970-01-01 (civil-from-days, March-based years)
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = (era * 146_097 + doe).checked_sub(719_468)?;
+    Some(days * 86_400 + time[0] * 3600 + time[1] * 60 + time[2])
+}
+
+/// Fetch all pages of a character's assets from ESI
+pub async fn fetch_character_assets(
+    access_token: &str,
+    character_id: i64,
+) -> Result<FetchedAssets, String> {
+    let client = Client::new();
+    let mut assets = vec![];
+    let mut expires_at = None;
+    let mut page = 1;
+    loop {
+        let url = format!(
+            "https://esi.evetech.net/latest/characters/{}/assets/?datasource=tranquility&page={}",
+            character_id, page
+        );
+        let resp = client
+            .get(&url)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch assets: {}", e))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("ESI returned {} for assets: {}", status, body));
+        }
+        if expires_at.is_none() {
+            expires_at = resp
+                .headers()
+                .get("expires")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_http_date);
+        }
+        let pages = resp
+            .headers()
+            .get("x-pages")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(1);
+        let page_assets = resp
+            .json::<Vec<Asset>>()
+            .await
+            .map_err(|e| format!("Failed to parse assets: {}", e))?;
+        assets.extend(page_assets);
+        if page >= pages {
+            return Ok(FetchedAssets {
+                assets,
+                expires_at: expires_at.unwrap_or_else(|| now_secs() + ASSET_CACHE_SECS),
+            });
+        }
+        page += 1;
+    }
+}
+
+/// Load a character's cached assets (None if they were never fetched)
+pub fn load_cached_assets(character_id: i64) -> Result<Option<AssetCache>, String> {
+    match fs::read_to_string(cache_path(character_id)) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse cached assets: {}", e)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Cache a character's assets
+pub fn cache_assets(character_id: i64, fetched: FetchedAssets) -> Result<AssetCache, String> {
+    let cache = AssetCache {
+        character_id,
+        fetched_at: now_secs(),
+        expires_at: fetched.expires_at,
+        assets: fetched.assets,
+    };
+    fs::create_dir_all("data").map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(&cache)
+        .map_err(|e| format!("Failed to serialize assets: {}", e))?;
+    fs::write(cache_path(character_id), json)
+        .map_err(|e| format!("Failed to write cached assets: {}", e))?;
+    Ok(cache)
+}
+
+/// Station, structure or solar system an item is in, through any ships and containers
+fn root_location(asset: &Asset, by_item: &HashMap<i64, &Asset>) -> i64 {
+    let mut current = asset;
+    // Bounded by the number of assets, in case ESI ever returns a cycle
+    for _ in 0..by_item.len() {
+        match by_item.get(&current.location_id) {
+            Some(parent) if current.location_type == "item" => current = parent,
+            _ => break,
+        }
+    }
+    current.location_id
+}
+
+/// Quantity of each type the character owns within a scope
+pub fn owned_items(assets: &[Asset], scope: AssetScope) -> OwnedItems {
+    let by_item: HashMap<i64, &Asset> = assets.iter().map(|a| (a.item_id, a)).collect();
+    let mut owned = OwnedItems::new();
+    for asset in assets {
+        let in_scope = match scope {
+            AssetScope::Station { location_id } => root_location(asset, &by_item) == location_id,
+            AssetScope::Everywhere => true,
+        };
+        if in_scope {
+            *owned.entry(asset.type_id).or_insert(0) += asset.quantity;
+        }
+    }
+    owned
+}
+
+/// Owned items of a selection, from the cache or fetched (and cached) if not cached yet
+/// or ESI's cache expiry has passed
+pub async fn load_owned_items(
+    selection: AssetSelection,
+    access_token: &str,
+) -> Result<OwnedItems, String> {
+    let cache = match load_cached_assets(selection.character_id)? {
+        Some(cache) if !cache.is_expired(now_secs()) => cache,
+        _ => {
+            let assets = fetch_character_assets(access_token, selection.character_id).await?;
+            cache_assets(selection.character_id, assets)?
+        }
+    };
+    Ok(owned_items(&cache.assets, selection.scope))
+}
+
+/// Modules of types the character owns
+pub fn owned_modules(modules: &[Module], owned: &OwnedItems) -> Vec<Module> {
+    modules
+        .iter()
+        .filter(|m| {
+            owned
+                .get(&m.module_id)
+                .is_some_and(|quantity| *quantity > 0)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Which items of a fit are on hand and which must be bought
+///
+/// # Returns
+/// - One entry per type in the fit (hull, modules, charges, drones and implants), in
+///   the order they first appear
+pub fn fit_availability(fit: &FitVariant, owned: &OwnedItems) -> Vec<ItemAvailability> {
+    let mut items: Vec<ItemAvailability> = vec![];
+    for (type_id, name, quantity) in fit_items(fit) {
+        match items.iter_mut().find(|i| i.type_id == type_id) {
+            Some(item) => item.quantity += quantity,
+            None => items.push(ItemAvailability {
+                type_id,
+                name: name.to_string(),
+                quantity,
+                on_hand: 0,
+                to_buy: 0,
+            }),
+        }
+    }
+    for item in &mut items {
+        item.on_hand = owned
+            .get(&item.type_id)
+            .copied()
+            .unwrap_or(0)
+            .min(item.quantity);
+        item.to_buy = item.quantity - item.on_hand;
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{Charge, FitVariant, Ship};
+    use crate::test_support::{
+        armor_plate, hybrid_charge, large_blaster, magnetic_field_stabilizer,
+    };
+
+    #[test]
+    fn test_owned_assets() {
+        let asset = |item_id, type_id, location_id, location_type: &str, quantity| Asset {
+            item_id,
+            type_id,
+            location_id,
+            location_type: location_type.to_string(),
+            location_flag: "Hangar".to_string(),
+            quantity,
+            is_singleton: false,
+        };
+        let jita = 60003760;
+        let amarr = 60008494;
+        let assets = vec![
+            // A container in Jita holding one T2 blaster, a loose one in the hangar
+            asset(1, 17366, jita, "station", 1),
+            asset(2, 3186, 1, "item", 1),
+            asset(3, 3186, jita, "station", 1),
+            asset(4, 10190, amarr, "station", 2),
+            asset(5, 230, jita, "station", 5_000),
+        ];
+        let in_jita = owned_items(&assets, AssetScope::Station { location_id: jita });
+        assert_eq!(in_jita.get(&3186), Some(&2));
+        assert!(!in_jita.contains_key(&10190));
+        let everywhere = owned_items(&assets, AssetScope::Everywhere);
+        assert_eq!(everywhere.get(&10190), Some(&2));
+
+        let modules = vec![large_blaster(), magnetic_field_stabilizer(), armor_plate()];
+        let pool = owned_modules(&modules, &in_jita);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool[0].module_id, 3186);
+
+        let mut blaster = large_blaster();
+        blaster.charge = Some(Charge {
+            charge_id: 230,
+            ..hybrid_charge("Antimatter Charge L", 10.0, 1.0)
+        });
+        let fit = FitVariant {
+            ship: Ship {
+                ship_id: 641,
+                ship_name: "Megathron".to_string(),
+                ..Default::default()
+            },
+            modules: vec![blaster.clone(), blaster.clone(), blaster],
+            ..Default::default()
+        };
+        let availability = fit_availability(&fit, &in_jita);
+        let item = |type_id| availability.iter().find(|i| i.type_id == type_id).unwrap();
+        assert_eq!(availability.len(), 3);
+        assert_eq!(item(641).to_buy, 1);
+        assert_eq!(
+            (item(3186).quantity, item(3186).on_hand, item(3186).to_buy),
+            (3, 2, 1)
+        );
+        assert_eq!(item(230).to_buy, 0);
+        assert_eq!(item(230).name, "Antimatter Charge L");
+
+        // The cache is refetched once ESI's expiry has passed
+        let expires_at = parse_http_date("Mon, 19 Oct 2026 12:00:00 GMT").unwrap();
+        assert_eq!(expires_at, 1_792_411_200);
+        assert_eq!(parse_http_date("19 Oct 2026"), None);
+        let cache = AssetCache {
+            character_id: 1,
+            fetched_at: expires_at - 3600,
+            expires_at,
+            assets,
+        };
+        assert!(!cache.is_expired(expires_at - 1));
+        assert!(cache.is_expired(expires_at));
+    }
+}
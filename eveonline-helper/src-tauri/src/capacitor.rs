@@ -0,0 +1,92 @@
+//! Capacitor simulation for EveOnline Helper
+//
+// Steps the capacitor second by second:
+// - recharge follows EVE's curve: dC/dt = 10 * Cmax / tau * (sqrt(C/Cmax) - C/Cmax),
+//   with tau = `rechargeRate` in seconds (peak recharge at 25% capacitor)
+// - every running module drains its `capacitorNeed` at the start of each cycle
+// - incoming capacitor transfer (e.g. a cap chain) is added continuously
+// The fit is cap stable if the capacitor never runs dry within the simulated hour.
+
+use crate::esi::FitVariant;
+use crate::fitting::{attr, calculate_attributes_with, cycle_time, is_running, CalcContext};
+use serde::{Deserialize, Serialize};
+
+/// Simulated time in seconds
+const MAX_SECONDS: u32 = 3600;
+
+/// Result of a capacitor simulation
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CapSimulation {
+    pub stable: bool,
+    /// Capacitor fraction (0.0-1.0) at the end of the simulation, if stable
+    pub stable_level: Option<f64>,
+    /// Seconds until the capacitor runs dry, if not stable
+    pub seconds_to_empty: Option<f64>,
+}
+
+/// Simulate a fit's capacitor with all running modules cycling from full cap
+///
+/// # Arguments
+/// * `fit` - The fit to simulate
+/// * `ctx` - Skills, implants and other context
+/// * `incoming_per_second` - Capacitor received from other ships in GJ/s
+///
+/// # Returns
+/// - CapSimulation with stability and either the stable level or time to empty
+pub fn simulate_capacitor(
+    fit: &FitVariant,
+    ctx: &CalcContext,
+    incoming_per_second: f64,
+) -> CapSimulation {
+    let calc = calculate_attributes_with(fit, ctx);
+    let capacity = calc.ship_attr(attr::CAPACITOR_CAPACITY);
+    let tau = calc.ship_attr(attr::CAPACITOR_RECHARGE) / 1000.0;
+    // (cycle time, cap per cycle, next activation)
+    let mut drains: Vec<(f64, f64, f64)> = fit
+        .modules
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| is_running(m))
+        .map(|(index, _)| {
+            let cycle = cycle_time(&calc.modules[index]);
+            (cycle, calc.module_attr(index, attr::CAPACITOR_NEED), 0.0)
+        })
+        .filter(|(cycle, need, _)| *cycle > 0.0 && *need > 0.0)
+        .collect();
+    if capacity <= 0.0 {
+        let stable = drains.is_empty();
+        return CapSimulation {
+            stable,
+            stable_level: stable.then_some(0.0),
+            seconds_to_empty: (!stable).then_some(0.0),
+        };
+    }
+
+    let mut cap = capacity;
+    for second in 0..MAX_SECONDS {
+        let t = second as f64;
+        for (cycle, need, next) in drains.iter_mut() {
+            while *next <= t {
+                cap -= *need;
+                *next += *cycle;
+            }
+        }
+        if cap < 0.0 {
+            return CapSimulation {
+                stable: false,
+                stable_level: None,
+                seconds_to_empty: Some(t),
+            };
+        }
+        let fraction = cap / capacity;
+        if tau > 0.0 {
+            cap += 10.0 * capacity / tau * (fraction.sqrt() - fraction);
+        }
+        cap = (cap + incoming_per_second).min(capacity);
+    }
+    CapSimulation {
+        stable: true,
+        stable_level: Some(cap / capacity),
+        seconds_to_empty: None,
+    }
+}
@@ -0,0 +1,215 @@
+//! Charge (ammo, crystals, scripts) compatibility and selection for EveOnline Helper
+//
+// A module accepts a charge when the charge's group is one of the module's
+// `chargeGroup1..5` attributes and, for sized weapons, the charge's `chargeSize`
+// matches the module's. Charges per reload is the module `capacity` divided by the
+// charge `volume`.
+//
+// Ammo is picked for weapons only, by applied DPS against the activity's target and
+// among the charges the character has the skills for. Other modules that take charges
+// (mining crystals, scripts) have no damage to compare them by and keep what they have.
+
+use crate::esi::{Charge, FitVariant, Module, Skill};
+use crate::fitting::{
+    attr, calculate_fit_stats, is_launcher, is_turret, CalcContext, TargetProfile,
+};
+
+/// Check that a charge can be loaded into a module
+///
+/// # Arguments
+/// * `module` - The fitted module
+/// * `charge` - The charge to load
+///
+/// # Returns
+/// - Ok(charges_per_reload) if compatible, Err(reason) otherwise
+pub fn check_charge_compatibility(module: &Module, charge: &Charge) -> Result<u32, String> {
+    let accepted_groups: Vec<i64> = attr::CHARGE_GROUPS
+        .iter()
+        .filter_map(|name| module.attributes.get(*name))
+        .map(|group| *group as i64)
+        .filter(|group| *group > 0)
+        .collect();
+    if accepted_groups.is_empty() {
+        return Err(format!("{} does not accept charges", module.module_name));
+    }
+    if !accepted_groups.contains(&charge.group_id) {
+        return Err(format!(
+            "{} cannot be loaded into {}",
+            charge.charge_name, module.module_name
+        ));
+    }
+    if let (Some(module_size), Some(charge_size)) = (
+        module.attributes.get(attr::CHARGE_SIZE),
+        charge.attributes.get(attr::CHARGE_SIZE),
+    ) {
+        if module_size != charge_size {
+            return Err(format!(
+                "{} is the wrong size for {} (size {} vs {})",
+                charge.charge_name, module.module_name, charge_size, module_size
+            ));
+        }
+    }
+    let capacity = module
+        .attributes
+        .get(attr::CAPACITY)
+        .copied()
+        .unwrap_or(0.0);
+    let volume = charge.attributes.get(attr::VOLUME).copied().unwrap_or(0.0);
+    if volume <= 0.0 {
+        // Scripts and similar charges take no room
+        return Ok(1);
+    }
+    // Small epsilon so 0.1 m3 charges in 1.0 m3 capacity give 10, not 9
+    let per_reload = (capacity / volume + 1e-9).floor() as u32;
+    if per_reload == 0 {
+        return Err(format!(
+            "{} does not fit in {} ({} m3 capacity, {} m3 per charge)",
+            charge.charge_name, module.module_name, capacity, volume
+        ));
+    }
+    Ok(per_reload)
+}
+
+/// Charges from `candidates` that can be loaded into `module`
+pub fn compatible_charges<'a>(module: &Module, candidates: &'a [Charge]) -> Vec<&'a Charge> {
+    candidates
+        .iter()
+        .filter(|charge| check_charge_compatibility(module, charge).is_ok())
+        .collect()
+}
+
+/// Whether the character has the skills to use a charge
+pub fn can_use_charge(charge: &Charge, ctx: &CalcContext) -> bool {
+    charge
+        .required_skills
+        .iter()
+        .all(|(skill_id, _, level)| ctx.skill_level(*skill_id) >= *level)
+}
+
+/// Pick the usable charge that gives a fitted weapon the most applied DPS against a target
+///
+/// # Arguments
+/// * `fit` - The fit containing the weapon
+/// * `module_index` - Index of the weapon in `fit.modules`
+/// * `candidates` - Charges available to choose from
+/// * `target` - Resists and range of the expected target
+/// * `user_skills` - The character's skills
+///
+/// # Returns
+/// - The best compatible charge the character can use, or None if there is none or the
+///   module is not a weapon
+pub fn select_best_charge(
+    fit: &FitVariant,
+    module_index: usize,
+    candidates: &[Charge],
+    target: &TargetProfile,
+    user_skills: &[Skill],
+) -> Option<Charge> {
+    let module = fit.modules.get(module_index)?;
+    if !is_turret(module) && !is_launcher(module) {
+        return None;
+    }
+    let ctx = CalcContext::from_skills(user_skills);
+    let mut best: Option<(f64, &Charge)> = None;
+    for charge in compatible_charges(module, candidates)
+        .into_iter()
+        .filter(|charge| can_use_charge(charge, &ctx))
+    {
+        let mut trial = fit.clone();
+        trial.modules[module_index].charge = Some(charge.clone());
+        let stats = calculate_fit_stats(&trial, user_skills);
+        let applied = stats
+            .weapons
+            .iter()
+            .find(|w| w.module_index == module_index)
+            .map(|w| w.applied_dps(target))
+            .unwrap_or(0.0);
+        // Ties keep the first candidate so results are stable
+        if best.map(|(dps, _)| applied > dps).unwrap_or(true) {
+            best = Some((applied, charge));
+        }
+    }
+    best.map(|(_, charge)| charge.clone())
+}
+
+/// Load the best charge for the target into every weapon
+///
+/// Weapons without a usable candidate and modules that are not weapons keep their
+/// current charge.
+pub fn load_best_charges(
+    fit: &FitVariant,
+    candidates: &[Charge],
+    target: &TargetProfile,
+    user_skills: &[Skill],
+) -> FitVariant {
+    let mut loaded = fit.clone();
+    for index in 0..loaded.modules.len() {
+        if let Some(charge) = select_best_charge(&loaded, index, candidates, target, user_skills) {
+            loaded.modules[index].charge = Some(charge);
+        }
+    }
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::Skill;
+    use crate::fitting::{calculate_fit_stats, DamageProfile, TargetProfile};
+    use crate::test_support::{blaster_fit, hybrid_charge, large_blaster};
+
+    #[test]
+    fn test_check_charge_compatibility() {
+        let blaster = large_blaster();
+        let antimatter = hybrid_charge("Antimatter Charge L", 48.0, 0.5);
+        assert_eq!(check_charge_compatibility(&blaster, &antimatter), Ok(40));
+        let mut medium = antimatter.clone();
+        medium.attributes.insert("chargeSize".to_string(), 2.0);
+        assert!(check_charge_compatibility(&blaster, &medium).is_err());
+        let mut crystal = antimatter.clone();
+        crystal.group_id = 86;
+        assert!(check_charge_compatibility(&blaster, &crystal).is_err());
+    }
+
+    #[test]
+    fn test_select_best_charge_for_range() {
+        let fit = blaster_fit();
+        let candidates = vec![
+            hybrid_charge("Antimatter Charge L", 48.0, 0.5),
+            hybrid_charge("Iridium Charge L", 32.0, 1.6),
+        ];
+        let close = TargetProfile {
+            resists: DamageProfile::default(),
+            distance: 2_000.0,
+        };
+        let far = TargetProfile {
+            resists: DamageProfile::default(),
+            distance: 12_000.0,
+        };
+        let best_close = select_best_charge(&fit, 0, &candidates, &close, &[]).unwrap();
+        let best_far = select_best_charge(&fit, 0, &candidates, &far, &[]).unwrap();
+        assert_eq!(best_close.charge_name, "Antimatter Charge L");
+        assert_eq!(best_far.charge_name, "Iridium Charge L");
+        // Without a charge the turret has no damage profile
+        assert_eq!(calculate_fit_stats(&fit, &[]).dps, 0.0);
+
+        // T2 ammo only once the specialization is trained
+        let mut void = hybrid_charge("Void L", 60.0, 0.75);
+        void.required_skills = vec![(12209, "Large Blaster Specialization".to_string(), 1)];
+        let candidates = vec![candidates[0].clone(), void];
+        let unskilled = select_best_charge(&fit, 0, &candidates, &close, &[]).unwrap();
+        assert_eq!(unskilled.charge_name, "Antimatter Charge L");
+        let specialized = vec![Skill {
+            skill_id: 12209,
+            skill_name: None,
+            active_level: 1,
+        }];
+        let skilled = select_best_charge(&fit, 0, &candidates, &close, &specialized).unwrap();
+        assert_eq!(skilled.charge_name, "Void L");
+
+        // Non-weapons have no DPS to pick a charge by
+        let mut scripted = fit.clone();
+        scripted.modules[0].group_id = 212;
+        assert!(select_best_charge(&scripted, 0, &candidates, &close, &[]).is_none());
+    }
+}
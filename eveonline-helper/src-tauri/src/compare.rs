@@ -0,0 +1,243 @@
+//! Side-by-side fit comparison for EveOnline Helper
+//
+// Evaluates two or more fits for the same character and lines their key stats up in
+// rows (one value per fit), marking the best value in each row. Modules whose count
+// differs between the fits are listed separately.
+
+use crate::esi::{generate_skill_plan_for_fit, FitVariant};
+use crate::fitting::{calculate_fit_stats_with, CalcContext};
+use crate::prices::{fit_cost, PriceTable};
+use crate::saved_fits::get_saved_fit;
+use crate::training::{fit_skill_ranks, plan_training_seconds, DEFAULT_SP_PER_MINUTE};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A fit to compare: either sent in full or referenced by saved fit ID
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum FitSource {
+    Saved(u64),
+    Fit(Box<FitVariant>),
+}
+
+impl FitSource {
+    pub fn resolve(self) -> Result<FitVariant, String> {
+        match self {
+            FitSource::Saved(id) => get_saved_fit(id),
+            FitSource::Fit(fit) => Ok(*fit),
+        }
+    }
+}
+
+/// One compared stat, with a value per fit
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatRow {
+    pub stat: String,
+    /// Value per fit, in the order the fits were given (None if unknown, e.g. no price)
+    pub values: Vec<Option<f64>>,
+    /// Difference to the first fit
+    pub deltas: Vec<Option<f64>>,
+    /// Index of the fit with the best value, if the values differ
+    pub best_index: Option<usize>,
+}
+
+/// A module fitted a different number of times across the fits
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModuleDifference {
+    pub module_name: String,
+    /// How many of the module each fit has
+    pub counts: Vec<usize>,
+}
+
+/// Result of comparing fits
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FitComparison {
+    pub fit_names: Vec<String>,
+    pub stats: Vec<StatRow>,
+    pub module_differences: Vec<ModuleDifference>,
+}
+
+fn stat_row(stat: &str, values: Vec<Option<f64>>, higher_is_better: bool) -> StatRow {
+    let first = values.first().copied().flatten();
+    let deltas = values
+        .iter()
+        .map(|value| Some((*value)? - first?))
+        .collect();
+    let known: Vec<(usize, f64)> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(index, value)| value.map(|v| (index, v)))
+        .collect();
+    let all_equal = known.windows(2).all(|pair| pair[0].1 == pair[1].1);
+    let best_index = if all_equal {
+        None
+    } else {
+        // Ties keep the earliest fit
+        known
+            .iter()
+            .copied()
+            .reduce(|best, candidate| {
+                let better = if higher_is_better {
+                    candidate.1 > best.1
+                } else {
+                    candidate.1 < best.1
+                };
+                if better {
+                    candidate
+                } else {
+                    best
+                }
+            })
+            .map(|(index, _)| index)
+    };
+    StatRow {
+        stat: stat.to_string(),
+        values,
+        deltas,
+        best_index,
+    }
+}
+
+/// Compare fits for one character
+///
+/// # Arguments
+/// * `fits` - Two or more fits
+/// * `ctx` - The character's skills, implants and other context
+/// * `prices` - ISK prices by type ID, for the cost row
+///
+/// # Returns
+/// - Aligned stat rows (DPS, EHP, capacitor, speed, targeting, cost, training time) and
+///   the modules that differ, or an error if fewer than two fits were given
+pub fn compare_fits(
+    fits: &[FitVariant],
+    ctx: &CalcContext,
+    prices: &PriceTable,
+) -> Result<FitComparison, String> {
+    if fits.len() < 2 {
+        return Err("At least two fits are needed for a comparison".into());
+    }
+    let stats: Vec<_> = fits
+        .iter()
+        .map(|fit| calculate_fit_stats_with(fit, ctx))
+        .collect();
+    let column = |f: &dyn Fn(usize) -> Option<f64>| (0..fits.len()).map(f).collect::<Vec<_>>();
+    let training_hours = |index: usize| {
+        let plan = generate_skill_plan_for_fit(&fits[index], &ctx.skills);
+        Some(
+            plan_training_seconds(&plan, &fit_skill_ranks(&fits[index]), DEFAULT_SP_PER_MINUTE)
+                / 3600.0,
+        )
+    };
+    let rows = vec![
+        stat_row("DPS", column(&|i| Some(stats[i].dps)), true),
+        stat_row("Volley", column(&|i| Some(stats[i].volley)), true),
+        stat_row("EHP", column(&|i| Some(stats[i].ehp)), true),
+        stat_row(
+            "Capacitor balance (GJ/s)",
+            column(&|i| Some(stats[i].capacitor_recharge - stats[i].capacitor_usage)),
+            true,
+        ),
+        stat_row(
+            "Max velocity (m/s)",
+            column(&|i| Some(stats[i].max_velocity)),
+            true,
+        ),
+        stat_row(
+            "Align time (s)",
+            column(&|i| Some(stats[i].align_time)),
+            false,
+        ),
+        stat_row(
+            "Max target range (m)",
+            column(&|i| Some(stats[i].max_target_range)),
+            true,
+        ),
+        stat_row(
+            "Scan resolution (mm)",
+            column(&|i| Some(stats[i].scan_resolution)),
+            true,
+        ),
+        stat_row("Cost (ISK)", column(&|i| fit_cost(&fits[i], prices)), false),
+        stat_row("Training time (h)", column(&training_hours), false),
+    ];
+
+    let mut counts: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (index, fit) in fits.iter().enumerate() {
+        for module in &fit.modules {
+            counts
+                .entry(module.module_name.as_str())
+                .or_insert_with(|| vec![0; fits.len()])[index] += 1;
+        }
+    }
+    let module_differences = counts
+        .into_iter()
+        .filter(|(_, counts)| counts.windows(2).any(|pair| pair[0] != pair[1]))
+        .map(|(name, counts)| ModuleDifference {
+            module_name: name.to_string(),
+            counts,
+        })
+        .collect();
+
+    Ok(FitComparison {
+        fit_names: fits.iter().map(|f| f.fit_name.clone()).collect(),
+        stats: rows,
+        module_differences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fitting::CalcContext;
+    use crate::test_support::{blaster_fit, hybrid_charge};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_compare_fits() {
+        let mut single = blaster_fit();
+        single.modules[0].charge = Some(hybrid_charge("Antimatter Charge L", 10.0, 1.0));
+        let mut double = single.clone();
+        double.fit_name = "Double Blaster".to_string();
+        double.modules.push(double.modules[0].clone());
+        let ctx = CalcContext::default();
+
+        assert!(compare_fits(&[single.clone()], &ctx, &HashMap::new()).is_err());
+
+        let prices = HashMap::from([(641, 200_000_000.0), (3186, 1_000_000.0), (0, 1_000.0)]);
+        let comparison = compare_fits(&[single.clone(), double.clone()], &ctx, &prices).unwrap();
+        assert_eq!(comparison.fit_names, vec!["Blaster Test", "Double Blaster"]);
+        let row = |name: &str| {
+            comparison
+                .stats
+                .iter()
+                .find(|row| row.stat == name)
+                .unwrap()
+                .clone()
+        };
+        let dps = row("DPS");
+        assert_eq!(dps.best_index, Some(1));
+        let single_dps = dps.values[0].unwrap();
+        assert!(single_dps > 0.0);
+        assert!((dps.deltas[1].unwrap() - single_dps).abs() < 1e-6);
+        // The cheaper fit wins the cost row
+        let cost = row("Cost (ISK)");
+        assert_eq!(cost.values, vec![Some(201_001_000.0), Some(202_002_000.0)]);
+        assert_eq!(cost.best_index, Some(0));
+        // Both fits need the same skills, so training time is a tie
+        let training = row("Training time (h)");
+        assert!(training.values[0].unwrap() > 0.0);
+        assert_eq!(training.best_index, None);
+        assert_eq!(comparison.module_differences.len(), 1);
+        assert_eq!(comparison.module_differences[0].counts, vec![1, 2]);
+
+        // Without a price for the hull the cost is unknown
+        let comparison = compare_fits(&[single, double], &ctx, &HashMap::new()).unwrap();
+        let cost = comparison
+            .stats
+            .iter()
+            .find(|row| row.stat == "Cost (ISK)")
+            .unwrap();
+        assert_eq!(cost.values, vec![None, None]);
+        assert_eq!(cost.best_index, None);
+    }
+}
@@ -1,26 +1,38 @@
 //! Encrypted SQLite schema and access logic for EveOnline Helper
 //
-// Uses sqlx for async, type-safe DB access. For encryption, use sqlcipher if available.
+// Uses sqlx for async, type-safe DB access. SQLite is built as SQLCipher (libsqlite3-sys
+// `bundled-sqlcipher-vendored-openssl` in Cargo.toml); `open_db` checks that it is, and
+// that the key unlocks the file, before anything is stored.
 //
 // Tables:
 // - users: stores EVE character/user info
 // - skills: stores skill data for each user
 // - ships: stores ship and fitting data
 // - modules: stores module data for fits
+// - prices: the local ISK price table (price per type ID)
+// - doctrines: doctrine fits by doctrine name (as JSON)
 //
-// NOTE: In production, store the encryption key securely (e.g., OS keychain).
+// The encryption key is a random 256-bit key created on first start and kept in the OS
+// keychain; the database file lives under `data/` with the other local data. If it cannot
+// be opened, the app runs on an in-memory database (`open_memory_db`) and nothing is saved.
 
-use sqlx::{SqlitePool, sqlite::SqliteConnectOptions, sqlite::SqliteJournalMode, sqlite::SqliteSynchronous, ConnectOptions};
+use keyring::Entry;
+use sqlx::{SqlitePool, sqlite::SqliteConnectOptions, sqlite::SqliteJournalMode, sqlite::SqlitePoolOptions, sqlite::SqliteSynchronous, Connection, SqliteConnection};
+use std::fs;
 use std::str::FromStr;
 
+const DB_PATH: &str = "sqlite:data/eveonline-helper.db";
+const KEY_SERVICE: &str = "eveonline-helper-db";
+const KEY_USER: &str = "encryption_key";
+
 /// Initialize the encrypted SQLite database connection
 pub async fn init_db(db_path: &str, encryption_key: &str) -> Result<SqlitePool, sqlx::Error> {
     let mut options = SqliteConnectOptions::from_str(db_path)?
         .journal_mode(SqliteJournalMode::Wal)
         .synchronous(SqliteSynchronous::Full)
         .create_if_missing(true);
-    // Enable encryption if using sqlcipher
-    options = options.pragma("key", encryption_key);
+    // SQLCipher key, applied before any other pragma
+    options = options.pragma("key", encryption_key.to_owned());
     SqlitePool::connect_with(options).await
 }
 
@@ -70,6 +82,130 @@ pub async fn create_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             FOREIGN KEY(ship_id) REFERENCES ships(id)
         )"#
     ).execute(pool).await?;
+    // Prices table
+    sqlx::query(
+        r#"CREATE TABLE IF NOT EXISTS prices (
+            type_id INTEGER PRIMARY KEY,
+            price REAL NOT NULL
+        )"#
+    ).execute(pool).await?;
+    // Doctrines table
+    sqlx::query(
+        r#"CREATE TABLE IF NOT EXISTS doctrines (
+            name TEXT PRIMARY KEY,
+            doctrine_json TEXT NOT NULL
+        )"#
+    ).execute(pool).await?;
+    Ok(())
+}
+
+/// Database encryption key (hex) from the OS keychain, created on first use
+async fn encryption_key() -> Result<String, String> {
+    let entry = Entry::new(KEY_SERVICE, KEY_USER).map_err(|e| format!("Keyring error: {}", e))?;
+    if let Ok(key) = entry.get_password() {
+        return Ok(key);
+    }
+    // SQLite's randomness source is seeded by the OS
+    let mut conn = SqliteConnection::connect("sqlite::memory:")
+        .await
+        .map_err(|e| format!("Failed to generate database key: {}", e))?;
+    let key: String = sqlx::query_scalar("SELECT hex(randomblob(32))")
+        .fetch_one(&mut conn)
+        .await
+        .map_err(|e| format!("Failed to generate database key: {}", e))?;
+    entry
+        .set_password(&key)
+        .map_err(|e| format!("Failed to store database key: {}", e))?;
+    Ok(key)
+}
+
+/// Open the encrypted database and create missing tables
+pub async fn open_db() -> Result<SqlitePool, String> {
+    fs::create_dir_all("data").map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let key = encryption_key().await?;
+    // Raw key syntax: the hex key is used as is, without passphrase derivation
+    let pool = init_db(DB_PATH, &format!("\"x'{}'\"", key))
+        .await
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    // Plain SQLite ignores the key pragma and has no cipher_version: never store data unencrypted
+    let cipher_version: Option<String> = sqlx::query_scalar("PRAGMA cipher_version")
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to check database encryption: {}", e))?;
+    if cipher_version.is_none() {
+        return Err("Database encryption is unavailable: SQLite was built without SQLCipher".to_string());
+    }
+    // Reading the schema fails if the key does not unlock the file
+    sqlx::query("SELECT count(*) FROM sqlite_master")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to unlock database: {}", e))?;
+    create_tables(&pool)
+        .await
+        .map_err(|e| format!("Failed to create tables: {}", e))?;
+    Ok(pool)
+}
+
+/// Open an empty in-memory database with all tables; nothing in it is saved
+pub async fn open_memory_db() -> Result<SqlitePool, String> {
+    // Each in-memory connection is its own database: keep one connection open for good
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .map_err(|e| format!("Failed to open in-memory database: {}", e))?;
+    create_tables(&pool)
+        .await
+        .map_err(|e| format!("Failed to create tables: {}", e))?;
+    Ok(pool)
+}
+
+/// Every stored (type_id, price)
+pub async fn load_prices(pool: &SqlitePool) -> Result<Vec<(i64, f64)>, sqlx::Error> {
+    sqlx::query_as("SELECT type_id, price FROM prices")
+        .fetch_all(pool)
+        .await
+}
+
+/// Insert or replace prices in one transaction
+pub async fn upsert_prices(pool: &SqlitePool, prices: &[(i64, f64)]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for (type_id, price) in prices {
+        sqlx::query("INSERT OR REPLACE INTO prices (type_id, price) VALUES (?, ?)")
+            .bind(type_id)
+            .bind(price)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+/// Every stored doctrine as JSON, oldest saved first
+pub async fn load_doctrines(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT doctrine_json FROM doctrines ORDER BY rowid")
+        .fetch_all(pool)
+        .await
+}
+
+/// Insert a doctrine, or update the one with the same name in place (it keeps its rowid,
+/// so its position in the library)
+pub async fn upsert_doctrine(pool: &SqlitePool, name: &str, doctrine_json: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO doctrines (name, doctrine_json) VALUES (?, ?) ON CONFLICT(name) DO UPDATE SET doctrine_json = excluded.doctrine_json")
+        .bind(name)
+        .bind(doctrine_json)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Delete the doctrine with a name, if stored
+pub async fn delete_doctrine(pool: &SqlitePool, name: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM doctrines WHERE name = ?")
+        .bind(name)
+        .execute(pool)
+        .await?;
     Ok(())
 }
 
@@ -0,0 +1,310 @@
+//! Doctrine library for EveOnline Helper
+//
+// A doctrine is a named set of fits a corporation or alliance flies, one per role
+// (e.g. "DPS", "Logi", "Tackle"). Doctrines are imported from EFT text and stored in
+// the encrypted database (`db.rs`); saving a doctrine replaces the one with the same
+// name.
+//
+// Readiness is reported per character: which doctrine fits they can fly now (the fit
+// validates with their skills), and for the others the missing skills (from
+// `generate_skill_plan_for_fit`) and the time to train them.
+
+use crate::db;
+use crate::eft::{parse_eft_fits, EftTypes};
+use crate::esi::{generate_skill_plan_for_fit, validate_fit, FitVariant, Skill};
+use crate::training::{fit_skill_ranks, plan_training_seconds, DEFAULT_SP_PER_MINUTE};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// A doctrine fit and the role it fills
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DoctrineFit {
+    pub role: String,
+    pub fit: FitVariant,
+}
+
+/// A named set of fits, one per role
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Doctrine {
+    pub name: String,
+    pub fits: Vec<DoctrineFit>,
+}
+
+/// A character and their skills, as known to the frontend
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CharacterSkills {
+    pub character_id: i64,
+    pub character_name: String,
+    pub skills: Vec<Skill>,
+    /// Training speed (the default character's if not known)
+    #[serde(default)]
+    pub sp_per_minute: Option<f64>,
+}
+
+/// Whether a character can fly one doctrine fit
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FitReadiness {
+    pub doctrine: String,
+    pub role: String,
+    pub fit_name: String,
+    /// The fit validates with the character's skills (fitting resources included)
+    pub can_fly: bool,
+    /// (skill_id, skill_name, required_level, current_level) still to train
+    pub missing_skills: Vec<(i64, String, i32, i32)>,
+    /// Seconds to train the missing skills
+    pub training_seconds: f64,
+}
+
+/// Doctrine readiness of one character
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CharacterReadiness {
+    pub character_id: i64,
+    pub character_name: String,
+    pub fits: Vec<FitReadiness>,
+}
+
+/// Load all stored doctrines (empty if none were saved yet)
+pub async fn load_doctrines(pool: &SqlitePool) -> Result<Vec<Doctrine>, String> {
+    db::load_doctrines(pool)
+        .await
+        .map_err(|e| format!("Failed to load doctrines: {}", e))?
+        .iter()
+        .map(|json| {
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse doctrine: {}", e))
+        })
+        .collect()
+}
+
+/// Save a doctrine, replacing the one with the same name
+pub async fn save_doctrine(pool: &SqlitePool, doctrine: &Doctrine) -> Result<(), String> {
+    let json = serde_json::to_string(doctrine)
+        .map_err(|e| format!("Failed to serialize doctrine: {}", e))?;
+    db::upsert_doctrine(pool, &doctrine.name, &json)
+        .await
+        .map_err(|e| format!("Failed to save doctrine: {}", e))
+}
+
+/// Delete a stored doctrine
+pub async fn delete_doctrine(pool: &SqlitePool, name: &str) -> Result<(), String> {
+    db::delete_doctrine(pool, name)
+        .await
+        .map_err(|e| format!("Failed to delete doctrine: {}", e))
+}
+
+/// Build a doctrine from EFT fits
+///
+/// # Arguments
+/// * `name` - Name of the doctrine
+/// * `eft` - One or more fits in EFT format; each fit's name is used as its role
+/// * `types` - SDE types to resolve the item names against
+///
+/// # Returns
+/// - The doctrine, or an error if a fit has lines that could not be resolved (a
+///   doctrine fit missing modules would be misleading)
+pub fn doctrine_from_eft(name: &str, eft: &str, types: &EftTypes) -> Result<Doctrine, String> {
+    let mut fits = vec![];
+    for import in parse_eft_fits(eft, types)? {
+        if !import.unresolved.is_empty() {
+            return Err(format!(
+                "Unknown items in EFT fit {}: {}",
+                import.fit.fit_name,
+                import.unresolved.join(", ")
+            ));
+        }
+        fits.push(DoctrineFit {
+            role: import.fit.fit_name.clone(),
+            fit: import.fit,
+        });
+    }
+    Ok(Doctrine {
+        name: name.to_string(),
+        fits,
+    })
+}
+
+/// Which doctrine fits each character can fly, and what the others still need
+///
+/// # Arguments
+/// * `doctrines` - The doctrines to check
+/// * `characters` - The characters and their skills
+///
+/// # Returns
+/// - One entry per character, with every doctrine fit in order
+pub fn doctrine_readiness(
+    doctrines: &[Doctrine],
+    characters: &[CharacterSkills],
+) -> Vec<CharacterReadiness> {
+    characters
+        .iter()
+        .map(|character| {
+            let sp_per_minute = character.sp_per_minute.unwrap_or(DEFAULT_SP_PER_MINUTE);
+            let fits = doctrines
+                .iter()
+                .flat_map(|doctrine| doctrine.fits.iter().map(move |fit| (doctrine, fit)))
+                .map(|(doctrine, doctrine_fit)| {
+                    let missing_skills =
+                        generate_skill_plan_for_fit(&doctrine_fit.fit, &character.skills);
+                    FitReadiness {
+                        doctrine: doctrine.name.clone(),
+                        role: doctrine_fit.role.clone(),
+                        fit_name: doctrine_fit.fit.fit_name.clone(),
+                        can_fly: validate_fit(&doctrine_fit.fit, &character.skills).is_valid,
+                        training_seconds: plan_training_seconds(
+                            &missing_skills,
+                            &fit_skill_ranks(&doctrine_fit.fit),
+                            sp_per_minute,
+                        ),
+                        missing_skills,
+                    }
+                })
+                .collect();
+            CharacterReadiness {
+                character_id: character.character_id,
+                character_name: character.character_name.clone(),
+                fits,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eft::parse_eft;
+    use crate::esi::{ModuleState, Skill};
+    use crate::test_support::{attrs, eft_types};
+
+    #[test]
+    fn test_doctrines() {
+        let types = eft_types();
+        let megathron = "[Megathron, DPS]
+1600mm Steel Plates II
+1600mm Steel Plates II /OFFLINE
+[Empty Low slot]
+
+Neutron Blaster Cannon II, Antimatter Charge L
+Neutron Blaster Cannon II, Antimatter Charge L
+
+Hammerhead II x3
+Hammerhead II x2
+
+Antimatter Charge L x1000
+";
+        let import = parse_eft(megathron, &types).unwrap();
+        let fit = import.fit;
+        assert_eq!(
+            (fit.ship.ship_name.as_str(), fit.fit_name.as_str()),
+            ("Megathron", "DPS")
+        );
+        assert_eq!(fit.modules.len(), 4);
+        assert_eq!(fit.modules[1].state, ModuleState::Offline);
+        assert!(fit.modules[2..].iter().all(|m| m
+            .charge
+            .as_ref()
+            .is_some_and(|c| c.charge_name == "Antimatter Charge L")));
+        assert_eq!(fit.drones.len(), 1);
+        assert_eq!(fit.drones[0].quantity, 5);
+
+        assert_eq!(import.cargo.len(), 1);
+        assert_eq!(import.cargo[0].quantity, 1000);
+        assert!(import.unresolved.is_empty());
+
+        // Unresolved lines are reported, the rest of the fit is kept
+        let unknown = "[Megathron, DPS]\nNeutron Blaster Cannon II, Void L\nDamage Control II";
+        let import = parse_eft(unknown, &types).unwrap();
+        assert_eq!(
+            import.unresolved,
+            vec!["Neutron Blaster Cannon II, Void L", "Damage Control II"]
+        );
+        assert_eq!(import.fit.modules.len(), 1);
+        assert!(import.fit.modules[0].charge.is_none());
+        assert_eq!(
+            doctrine_from_eft("Armor Battleships", unknown, &types).unwrap_err(),
+            "Unknown items in EFT fit DPS: Neutron Blaster Cannon II, Void L, Damage Control II"
+        );
+        assert!(parse_eft("[Raven, DPS]", &types).is_err());
+
+        // Hulls with room for the doctrine fits, so only skills decide who can fly them
+        let mut types = types;
+        types.ships[0].attributes = attrs(&[
+            ("hiSlots", 2.0),
+            ("lowSlots", 3.0),
+            ("turretSlotsLeft", 2.0),
+            ("cpuOutput", 500.0),
+            ("powerOutput", 20_000.0),
+        ]);
+        types.ships[1].attributes = attrs(&[("hiSlots", 1.0), ("cpuOutput", 300.0)]);
+        let basilisk = "[Basilisk, Logi]\nMedium Remote Shield Booster II";
+        let doctrine = doctrine_from_eft(
+            "Armor Battleships",
+            &format!("{}\n\n{}", megathron, basilisk),
+            &types,
+        )
+        .unwrap();
+        let roles: Vec<&str> = doctrine.fits.iter().map(|f| f.role.as_str()).collect();
+        assert_eq!(roles, vec!["DPS", "Logi"]);
+
+        let skill = |skill_id, active_level| Skill {
+            skill_id,
+            skill_name: None,
+            active_level,
+        };
+        let characters = vec![
+            CharacterSkills {
+                character_id: 1,
+                character_name: "Line Pilot".to_string(),
+                skills: vec![
+                    skill(3336, 3),
+                    skill(3307, 5),
+                    skill(3394, 4),
+                    skill(3436, 1),
+                ],
+                sp_per_minute: None,
+            },
+            CharacterSkills {
+                character_id: 2,
+                character_name: "Logi Pilot".to_string(),
+                skills: vec![skill(12096, 1), skill(3422, 4), skill(3307, 4)],
+                sp_per_minute: Some(45.0),
+            },
+        ];
+        let readiness = doctrine_readiness(std::slice::from_ref(&doctrine), &characters);
+        assert_eq!(readiness.len(), 2);
+
+        let line = &readiness[0];
+        assert!(line.fits[0].can_fly);
+        assert_eq!(line.fits[0].training_seconds, 0.0);
+        assert!(!line.fits[1].can_fly);
+        assert_eq!(line.fits[1].missing_skills.len(), 2);
+
+        // Large Hybrid Turret IV to V, Gallente Battleship I (rank 8), Hull Upgrades I and
+        // Drones I (for the Hammerheads) at 45 SP/min
+        let logi = &readiness[1];
+        assert!(logi.fits[1].can_fly);
+        assert!(!logi.fits[0].can_fly);
+        let mut missing: Vec<(i64, i32, i32)> = logi.fits[0]
+            .missing_skills
+            .iter()
+            .map(|(id, _, required, current)| (*id, *required, *current))
+            .collect();
+        missing.sort();
+        assert_eq!(
+            missing,
+            vec![(3307, 5, 4), (3336, 1, 0), (3394, 1, 0), (3436, 1, 0)]
+        );
+        let sp =
+            (250.0 * 32f64.sqrt().powi(4) - 250.0 * 32f64.sqrt().powi(3)) + 2_000.0 + 250.0 + 250.0;
+        assert!((logi.fits[0].training_seconds - sp / 45.0 * 60.0).abs() < 1e-6);
+
+        // Skills alone are not enough: a fit the hull cannot power cannot be flown
+        let mut underpowered = doctrine;
+        underpowered.fits[0]
+            .fit
+            .ship
+            .attributes
+            .insert("powerOutput".to_string(), 1.0);
+        let line = &doctrine_readiness(&[underpowered], &characters)[0];
+        assert!(!line.fits[0].can_fly);
+        assert!(line.fits[0].missing_skills.is_empty());
+    }
+}
@@ -0,0 +1,407 @@
+//! EFT fit text for EveOnline Helper
+//
+// EFT is the plain-text fit format the game client, Pyfa and most fitting tools copy and
+// paste (and how fits are shared on Discord):
+//
+//   [Megathron, Blaster Megathron]
+//   Magnetic Field Stabilizer II
+//   [Empty Low slot]
+//
+//   Large Armor Repairer II /OFFLINE
+//
+//   Neutron Blaster Cannon II, Antimatter Charge L
+//
+//   Hammerhead II x5
+//
+//   Antimatter Charge L x1000
+//
+// The header names the hull and the fit; then come low, mid and high slot modules,
+// rigs, subsystems, drones and cargo, in sections separated by blank lines. A charge
+// follows its module after a comma, "/OFFLINE" marks an offline module, "xN" a drone
+// or cargo stack and "[Empty ... slot]" an empty slot. Stacks are drones until the
+// first stack that is not a drone or that follows the drone section; the rest is cargo.
+//
+// Items are referred to by name only, so they are resolved against SDE types the
+// frontend provides; a module's slot comes from its SDE type, not from the section it is
+// listed in. Lines that cannot be resolved are reported and the rest of the fit is kept.
+// Exported fits list every slot of the hull, empty ones included.
+
+use crate::esi::{Charge, Drone, FitVariant, Module, ModuleState, Ship, ShipMode, SlotType};
+use crate::fitting::{calculate_attributes_with, CalcContext};
+use serde::{Deserialize, Serialize};
+
+const OFFLINE_SUFFIX: &str = "/OFFLINE";
+/// Slot sections in the order EFT lists them
+const SLOT_ORDER: [SlotType; 5] = [
+    SlotType::Low,
+    SlotType::Mid,
+    SlotType::High,
+    SlotType::Rig,
+    SlotType::Subsystem,
+];
+
+/// SDE types EFT item names are resolved against
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EftTypes {
+    pub ships: Vec<Ship>,
+    #[serde(default)]
+    pub modules: Vec<Module>,
+    #[serde(default)]
+    pub charges: Vec<Charge>,
+    #[serde(default)]
+    pub drones: Vec<Drone>,
+    /// Tactical destroyer modes, listed like modules
+    #[serde(default)]
+    pub modes: Vec<ShipMode>,
+}
+
+impl EftTypes {
+    /// Type ID of a module, charge or drone by name
+    fn type_id(&self, name: &str) -> Option<i64> {
+        self.modules
+            .iter()
+            .find(|m| m.module_name == name)
+            .map(|m| m.module_id)
+            .or_else(|| {
+                self.charges
+                    .iter()
+                    .find(|c| c.charge_name == name)
+                    .map(|c| c.charge_id)
+            })
+            .or_else(|| {
+                self.drones
+                    .iter()
+                    .find(|d| d.drone_name == name)
+                    .map(|d| d.drone_id)
+            })
+    }
+}
+
+/// An item in the cargo hold (not part of the fit itself)
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CargoItem {
+    /// None if the name could not be resolved
+    pub type_id: Option<i64>,
+    pub name: String,
+    pub quantity: u32,
+}
+
+/// A fit read from EFT text
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EftImport {
+    pub fit: FitVariant,
+    pub cargo: Vec<CargoItem>,
+    /// Lines naming items that could not be resolved (left out of the fit)
+    pub unresolved: Vec<String>,
+}
+
+/// "[Ship, Fit name]" header, split into ship and fit name
+fn parse_header(line: &str) -> Option<(&str, &str)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (ship, name) = inner.split_once(',')?;
+    Some((ship.trim(), name.trim()))
+}
+
+/// "Name xN" stack, split into name and quantity
+fn parse_stack(line: &str) -> Option<(&str, u32)> {
+    let (name, quantity) = line.rsplit_once(" x")?;
+    Some((name.trim(), quantity.trim().parse().ok()?))
+}
+
+/// Parse one EFT fit
+///
+/// # Arguments
+/// * `text` - The fit in EFT format
+/// * `types` - SDE types to resolve the item names against
+///
+/// # Returns
+/// - The fit with its cargo and the lines that could not be resolved, or an error if
+///   the header or the hull is not valid
+pub fn parse_eft(text: &str, types: &EftTypes) -> Result<EftImport, String> {
+    let mut lines = text.lines().map(str::trim).skip_while(|l| l.is_empty());
+    let header = lines.next().ok_or("The EFT fit is empty")?;
+    let (ship_name, fit_name) =
+        parse_header(header).ok_or_else(|| format!("Invalid EFT header: {}", header))?;
+    let ship = types
+        .ships
+        .iter()
+        .find(|s| s.ship_name == ship_name)
+        .ok_or_else(|| format!("Unknown ship in EFT fit: {}", ship_name))?;
+    let mut import = EftImport {
+        fit: FitVariant {
+            fit_name: fit_name.to_string(),
+            ship: ship.clone(),
+            ..Default::default()
+        },
+        cargo: vec![],
+        unresolved: vec![],
+    };
+    let fit = &mut import.fit;
+    let mut in_drone_section = false;
+    let mut in_cargo = false;
+
+    for line in lines {
+        if line.is_empty() {
+            // Stacks after the drone section are cargo
+            in_cargo |= in_drone_section;
+            in_drone_section = false;
+            continue;
+        }
+        if line.starts_with("[Empty ") {
+            continue;
+        }
+        if let Some((name, quantity)) = parse_stack(line) {
+            let drone = types.drones.iter().find(|d| d.drone_name == name);
+            match drone {
+                Some(drone) if !in_cargo => {
+                    in_drone_section = true;
+                    match fit.drones.iter_mut().find(|d| d.drone_id == drone.drone_id) {
+                        Some(fitted) => fitted.quantity += quantity,
+                        None => fit.drones.push(Drone {
+                            quantity,
+                            ..drone.clone()
+                        }),
+                    }
+                }
+                _ => {
+                    in_cargo = true;
+                    let type_id = types.type_id(name);
+                    if type_id.is_none() {
+                        import.unresolved.push(line.to_string());
+                    }
+                    import.cargo.push(CargoItem {
+                        type_id,
+                        name: name.to_string(),
+                        quantity,
+                    });
+                }
+            }
+            continue;
+        }
+        let (item, offline) = match line.strip_suffix(OFFLINE_SUFFIX) {
+            Some(online_part) => (online_part.trim(), true),
+            None => (line, false),
+        };
+        let (module_name, charge_name) = match item.split_once(',') {
+            Some((module, charge)) => (module.trim(), Some(charge.trim())),
+            None => (item, None),
+        };
+        if let Some(mode) = types.modes.iter().find(|m| m.mode_name == module_name) {
+            fit.mode = Some(mode.clone());
+            continue;
+        }
+        let Some(module) = types.modules.iter().find(|m| m.module_name == module_name) else {
+            import.unresolved.push(line.to_string());
+            continue;
+        };
+        let mut module = module.clone();
+        if offline {
+            module.state = ModuleState::Offline;
+        }
+        if let Some(charge_name) = charge_name {
+            // The module is kept without its charge
+            match types.charges.iter().find(|c| c.charge_name == charge_name) {
+                Some(charge) => module.charge = Some(charge.clone()),
+                None => import.unresolved.push(line.to_string()),
+            }
+        }
+        fit.modules.push(module);
+    }
+    Ok(import)
+}
+
+/// Parse every fit in a text of EFT fits (each starting with its header)
+pub fn parse_eft_fits(text: &str, types: &EftTypes) -> Result<Vec<EftImport>, String> {
+    let mut blocks: Vec<Vec<&str>> = vec![];
+    for line in text.lines() {
+        if parse_header(line.trim()).is_some() {
+            blocks.push(vec![]);
+        }
+        match blocks.last_mut() {
+            Some(block) => block.push(line),
+            None if line.trim().is_empty() => {}
+            None => return Err(format!("Expected an EFT header, found: {}", line.trim())),
+        }
+    }
+    if blocks.is_empty() {
+        return Err("No EFT fit found".to_string());
+    }
+    blocks
+        .iter()
+        .map(|block| parse_eft(&block.join("\n"), types))
+        .collect()
+}
+
+fn empty_slot_line(slot: SlotType) -> &'static str {
+    match slot {
+        SlotType::Low => "[Empty Low slot]",
+        SlotType::Mid => "[Empty Med slot]",
+        SlotType::High => "[Empty High slot]",
+        SlotType::Rig => "[Empty Rig slot]",
+        SlotType::Subsystem => "[Empty Subsystem slot]",
+    }
+}
+
+fn module_line(module: &Module) -> String {
+    let mut line = module.module_name.clone();
+    if let Some(charge) = &module.charge {
+        line = format!("{}, {}", line, charge.charge_name);
+    }
+    if module.state == ModuleState::Offline {
+        line = format!("{} {}", line, OFFLINE_SUFFIX);
+    }
+    line
+}
+
+/// Write a fit as EFT text
+///
+/// # Arguments
+/// * `fit` - The fit to export
+/// * `cargo` - Cargo to list after the drones
+///
+/// # Returns
+/// - The fit in EFT format: slot sections with empty slots filled in, then the
+///   tactical destroyer mode, drones and cargo
+pub fn export_eft(fit: &FitVariant, cargo: &[CargoItem]) -> String {
+    // Slot counts include subsystem and other modifiers
+    let calc = calculate_attributes_with(fit, &CalcContext::default());
+    let mut sections: Vec<Vec<String>> = vec![];
+    for slot in SLOT_ORDER {
+        let mut lines: Vec<String> = fit
+            .modules
+            .iter()
+            .filter(|m| m.slot == Some(slot))
+            .map(module_line)
+            .collect();
+        let empty = calc.slot_count(slot).saturating_sub(lines.len());
+        lines.extend((0..empty).map(|_| empty_slot_line(slot).to_string()));
+        // T3 subsystems only appear on hulls that have them
+        if !lines.is_empty() || slot != SlotType::Subsystem {
+            sections.push(lines);
+        }
+    }
+    let slotless: Vec<String> = fit
+        .modules
+        .iter()
+        .filter(|m| m.slot.is_none())
+        .map(module_line)
+        .collect();
+    let mode: Vec<String> = fit.mode.iter().map(|m| m.mode_name.clone()).collect();
+    let drones: Vec<String> = fit
+        .drones
+        .iter()
+        .map(|d| format!("{} x{}", d.drone_name, d.quantity))
+        .collect();
+    let cargo: Vec<String> = cargo
+        .iter()
+        .map(|c| format!("{} x{}", c.name, c.quantity))
+        .collect();
+    sections.extend(
+        [slotless, mode, drones, cargo]
+            .into_iter()
+            .filter(|lines| !lines.is_empty()),
+    );
+
+    let mut text = format!("[{}, {}]\n", fit.ship.ship_name, fit.fit_name);
+    let body: Vec<String> = sections.iter().map(|lines| lines.join("\n")).collect();
+    text.push_str(&body.join("\n\n"));
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::FitVariant;
+    use crate::test_support::{attrs, eft_types};
+
+    #[test]
+    fn test_eft_export() {
+        let types = eft_types();
+        let mut megathron = types.ships[0].clone();
+        megathron.attributes = attrs(&[
+            ("hiSlots", 3.0),
+            ("medSlots", 1.0),
+            ("lowSlots", 2.0),
+            ("rigSlots", 1.0),
+        ]);
+        let types = EftTypes {
+            ships: vec![megathron],
+            ..types
+        };
+        let eft = "[Megathron, DPS]
+1600mm Steel Plates II /OFFLINE
+
+[Empty Med slot]
+
+Neutron Blaster Cannon II, Antimatter Charge L
+Neutron Blaster Cannon II
+Medium Remote Shield Booster II
+
+Hammerhead II x5
+
+Antimatter Charge L x1000
+Nanite Repair Paste x50
+";
+        let import = parse_eft(eft, &types).unwrap();
+        assert_eq!(import.unresolved, vec!["Nanite Repair Paste x50"]);
+        assert_eq!(
+            import.cargo[1],
+            CargoItem {
+                type_id: None,
+                name: "Nanite Repair Paste".to_string(),
+                quantity: 50,
+            }
+        );
+
+        let exported = export_eft(&import.fit, &import.cargo);
+        assert_eq!(
+            exported,
+            "[Megathron, DPS]
+1600mm Steel Plates II /OFFLINE
+[Empty Low slot]
+
+[Empty Med slot]
+
+Neutron Blaster Cannon II, Antimatter Charge L
+Neutron Blaster Cannon II
+Medium Remote Shield Booster II
+
+[Empty Rig slot]
+
+Hammerhead II x5
+
+Antimatter Charge L x1000
+Nanite Repair Paste x50
+"
+        );
+
+        // Round trip: the exported text reads back to the same fit and exports the same
+        let reimport = parse_eft(&exported, &types).unwrap();
+        assert_eq!(reimport.cargo, import.cargo);
+        assert_eq!(export_eft(&reimport.fit, &reimport.cargo), exported);
+        let summary = |fit: &FitVariant| {
+            fit.modules
+                .iter()
+                .map(|m| {
+                    (
+                        m.module_name.clone(),
+                        m.state,
+                        m.charge.as_ref().map(|c| c.charge_name.clone()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&reimport.fit), summary(&import.fit));
+        assert_eq!(reimport.fit.drones[0].quantity, 5);
+
+        // Drones listed after the drone section are cargo
+        let spare = parse_eft(
+            "[Megathron, DPS]\n\nHammerhead II x5\n\nHammerhead II x2",
+            &types,
+        )
+        .unwrap();
+        assert_eq!(spare.fit.drones[0].quantity, 5);
+        assert_eq!(spare.cargo[0].type_id, Some(2185));
+    }
+}
@@ -0,0 +1,250 @@
+//! Environmental effects for EveOnline Helper
+//
+// Wormhole system effects, abyssal weather and incursion effects are SDE types whose
+// dogma attributes describe how ships in the system are changed. This module turns those
+// types into environment profiles holding ordinary modifiers for the fit calculation.
+//
+// Attribute mapping:
+// - `<shipAttribute>Multiplier` (e.g. `shieldCapacityMultiplier`, `armorEmDamageResonanceMultiplier`)
+//   multiplies that ship attribute
+// - a few weapon/repair multipliers (see `environment_modifiers`) apply to fitted modules or charges
+// Attributes the app does not model are ignored.
+
+use crate::fitting::{
+    attr, Modifier, ModifierTarget, Operation, ARMOR_REPAIRER_GROUP, SHIELD_BOOSTER_GROUP,
+    TURRET_GROUPS,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// SDE group of wormhole and incursion effect beacons
+pub const EFFECT_BEACON_GROUP: i64 = 920;
+
+/// Abyssal weather type names (the SDE names start with one of these)
+const ABYSSAL_WEATHER: &[&str] = &[
+    "Electrical Storm",
+    "Dark Matter Field",
+    "Exotic Particle Storm",
+    "Gamma-Ray Afterglow",
+    "Plasma Firestorm",
+];
+
+/// Missile Launcher Operation, required by every missile charge
+const MISSILE_LAUNCHER_OPERATION: i64 = 3319;
+
+/// Ship attributes an environment can multiply
+const SHIP_ATTRIBUTES: &[&str] = &[
+    attr::SHIELD_CAPACITY,
+    attr::SHIELD_RECHARGE_RATE,
+    attr::ARMOR_HP,
+    attr::HULL_HP,
+    attr::CAPACITOR_CAPACITY,
+    attr::CAPACITOR_RECHARGE,
+    attr::MAX_VELOCITY,
+    attr::AGILITY,
+    attr::SIGNATURE_RADIUS,
+    attr::MAX_TARGET_RANGE,
+    attr::SCAN_RESOLUTION,
+];
+
+/// Where an environment profile comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EnvironmentKind {
+    Wormhole,
+    Abyssal,
+    Incursion,
+}
+
+/// A set of system-wide effects applied to every ship on grid
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EnvironmentProfile {
+    /// SDE type ID of the beacon or weather type
+    pub type_id: i64,
+    pub name: String,
+    pub kind: EnvironmentKind,
+    /// Wormhole class (1-6) or abyssal tier, when known
+    pub class: Option<i32>,
+    pub effects: Vec<Modifier>,
+}
+
+/// An SDE type with its dogma attributes, as needed to read environment types
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SdeType {
+    pub type_id: i64,
+    pub type_name: String,
+    pub group_id: i64,
+    #[serde(default)]
+    pub attributes: HashMap<String, f64>,
+}
+
+fn multiply(target: ModifierTarget, attribute: &str, value: f64) -> Modifier {
+    Modifier {
+        target,
+        attribute: attribute.to_string(),
+        operation: Operation::PostMul,
+        value,
+        stacking_penalized: false,
+    }
+}
+
+fn resonance_attributes() -> impl Iterator<Item = &'static str> {
+    attr::SHIELD_RESONANCES
+        .into_iter()
+        .chain(attr::ARMOR_RESONANCES)
+        .chain(attr::HULL_RESONANCES)
+}
+
+/// Modifiers for one environment attribute, or none if the app does not model it
+fn environment_modifiers(name: &str, value: f64) -> Vec<Modifier> {
+    let damage_types = [
+        attr::EM_DAMAGE,
+        attr::THERMAL_DAMAGE,
+        attr::KINETIC_DAMAGE,
+        attr::EXPLOSIVE_DAMAGE,
+    ];
+    match name {
+        "damageMultiplierMultiplier" => TURRET_GROUPS
+            .iter()
+            .map(|g| {
+                multiply(
+                    ModifierTarget::ModuleGroup(*g),
+                    attr::DAMAGE_MULTIPLIER,
+                    value,
+                )
+            })
+            .collect(),
+        "missileDamageMultiplier" => damage_types
+            .iter()
+            .map(|d| {
+                multiply(
+                    ModifierTarget::ChargeSkill(MISSILE_LAUNCHER_OPERATION),
+                    d,
+                    value,
+                )
+            })
+            .collect(),
+        "armorDamageAmountMultiplier" => vec![multiply(
+            ModifierTarget::ModuleGroup(ARMOR_REPAIRER_GROUP),
+            attr::ARMOR_REPAIR,
+            value,
+        )],
+        "shieldBonusMultiplier" => vec![multiply(
+            ModifierTarget::ModuleGroup(SHIELD_BOOSTER_GROUP),
+            attr::SHIELD_BONUS,
+            value,
+        )],
+        "falloffMultiplier" => TURRET_GROUPS
+            .iter()
+            .map(|g| multiply(ModifierTarget::ModuleGroup(*g), attr::FALLOFF, value))
+            .collect(),
+        _ => {
+            let Some(ship_attribute) = name.strip_suffix("Multiplier") else {
+                return vec![];
+            };
+            let known = SHIP_ATTRIBUTES.contains(&ship_attribute)
+                || resonance_attributes().any(|r| r == ship_attribute);
+            if known {
+                vec![multiply(ModifierTarget::Ship, ship_attribute, value)]
+            } else {
+                vec![]
+            }
+        }
+    }
+}
+
+/// Trailing "Class N" or "Tier N" number in a type name
+fn class_from_name(name: &str) -> Option<i32> {
+    let mut words = name.split_whitespace().rev();
+    let number = words.next()?.parse().ok()?;
+    match words.next()? {
+        "Class" | "Tier" => Some(number),
+        _ => None,
+    }
+}
+
+/// Build an environment profile from an SDE beacon or abyssal weather type
+///
+/// # Returns
+/// - Some(profile) if the type is a wormhole/incursion beacon or abyssal weather, None otherwise
+pub fn environment_from_sde_type(sde_type: &SdeType) -> Option<EnvironmentProfile> {
+    let name = sde_type.type_name.as_str();
+    let kind = if ABYSSAL_WEATHER.iter().any(|w| name.starts_with(w)) {
+        EnvironmentKind::Abyssal
+    } else if sde_type.group_id == EFFECT_BEACON_GROUP && name.contains("Incursion") {
+        EnvironmentKind::Incursion
+    } else if sde_type.group_id == EFFECT_BEACON_GROUP {
+        EnvironmentKind::Wormhole
+    } else {
+        return None;
+    };
+    // Sort by attribute name so profiles are identical across runs
+    let mut attributes: Vec<(&String, &f64)> = sde_type.attributes.iter().collect();
+    attributes.sort_by(|a, b| a.0.cmp(b.0));
+    let effects = attributes
+        .into_iter()
+        .filter(|(_, value)| **value != 1.0)
+        .flat_map(|(name, value)| environment_modifiers(name, *value))
+        .collect();
+    Some(EnvironmentProfile {
+        type_id: sde_type.type_id,
+        name: sde_type.type_name.clone(),
+        kind,
+        class: class_from_name(name),
+        effects,
+    })
+}
+
+/// Build environment profiles for every beacon and weather type in an SDE type list
+pub fn load_environment_profiles(sde_types: &[SdeType]) -> Vec<EnvironmentProfile> {
+    sde_types
+        .iter()
+        .filter_map(environment_from_sde_type)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fitting::{calculate_fit_stats_with, CalcContext};
+    use crate::test_support::{attrs, blaster_fit};
+
+    #[test]
+    fn test_wormhole_environment() {
+        let pulsar = SdeType {
+            type_id: 30883,
+            type_name: "Pulsar Effect Beacon Class 5".to_string(),
+            group_id: 920,
+            attributes: attrs(&[
+                ("shieldCapacityMultiplier", 1.44),
+                ("armorEmDamageResonanceMultiplier", 1.44),
+                ("signatureRadiusMultiplier", 1.44),
+                ("unmodelledMultiplier", 2.0),
+            ]),
+        };
+        let profile = environment_from_sde_type(&pulsar).unwrap();
+        assert_eq!(profile.kind, EnvironmentKind::Wormhole);
+        assert_eq!(profile.class, Some(5));
+        assert_eq!(profile.effects.len(), 3);
+        let weather = SdeType {
+            type_name: "Dark Matter Field Tier 3".to_string(),
+            group_id: 1,
+            ..Default::default()
+        };
+        let weather = environment_from_sde_type(&weather).unwrap();
+        assert_eq!(weather.kind, EnvironmentKind::Abyssal);
+        assert!(environment_from_sde_type(&SdeType::default()).is_none());
+
+        let mut fit = blaster_fit();
+        fit.ship.attributes.extend(attrs(&[
+            ("shieldCapacity", 1_000.0),
+            ("armorEmDamageResonance", 0.5),
+        ]));
+        let ctx = CalcContext {
+            environment: Some(profile),
+            ..Default::default()
+        };
+        let stats = calculate_fit_stats_with(&fit, &ctx);
+        assert!((stats.shield_hp - 1_440.0).abs() < 1e-6);
+        assert!((stats.armor_resists.em - (1.0 - 0.72)).abs() < 1e-9);
+    }
+}
@@ -5,9 +5,25 @@
 //
 // Dependencies: oauth2, url, reqwest, tauri (for command exposure), keyring (for secure storage)
 
+use crate::activity::{Activity, ActivityKind, PvpRole};
+use crate::alternatives::repair_fit;
+use crate::assets::ItemAvailability;
+use crate::charges::check_charge_compatibility;
+use crate::exploration::generate_exploration_fits;
+use crate::fitting::{
+    attr, calculate_attributes_with, calculate_fit_stats_with, is_launcher, is_turret, CalcContext,
+    FitStats, Modifier, StatDelta, TargetProfile,
+};
+use crate::implants::Implant;
+use crate::logistics::generate_logistics_fits;
+use crate::optimizer::{generate_optimized_fits, locked_base, LockedModule, SearchBudget};
+use crate::prices::{fit_cost, PriceTable};
+use crate::rationale::{explain_fit, Rationale};
+use crate::safety::SafetyReport;
+use crate::t3::validate_t3;
 use keyring::Entry;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -52,7 +68,7 @@ pub struct CharacterInfo {
 }
 
 /// ESI skills response
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Skill {
     pub skill_id: i64,
     pub skill_name: Option<String>,
@@ -61,32 +77,206 @@ pub struct Skill {
 }
 
 /// Ship and Module structs for SDE data (to be expanded)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Ship {
     pub ship_id: i64,
     pub ship_name: String,
-    // Add more fields as needed (attributes, required skills, etc.)
+    /// SDE group ID of the hull
+    #[serde(default)]
+    pub group_id: i64,
+    /// Base dogma attributes keyed by SDE attribute name (e.g. `cpuOutput`)
+    #[serde(default)]
+    pub attributes: HashMap<String, f64>,
+    /// Hull bonuses applied by the calculation engine
+    #[serde(default)]
+    pub bonuses: Vec<HullBonus>,
+    /// Skills required to fly the hull: (skill_id, skill_name, level)
+    #[serde(default)]
+    pub required_skills: Vec<(i64, String, i32)>,
+    /// Training rank (SDE `skillTimeConstant`) of the required skills, by skill ID
+    #[serde(default)]
+    pub skill_ranks: HashMap<i64, f64>,
+}
+
+/// A hull bonus, scaled by the level of `skill_id` or flat for role bonuses
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HullBonus {
+    /// Skill whose level multiplies the bonus (`None` for role bonuses)
+    pub skill_id: Option<i64>,
+    /// Modifier applied per skill level
+    pub modifier: Modifier,
+}
+
+/// Slot a module is fitted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum SlotType {
+    High,
+    Mid,
+    Low,
+    Rig,
+    /// T3 cruiser subsystem
+    Subsystem,
+}
+
+/// Running state of a fitted module
+///
+/// Passive modules (no cycle time) are treated as online when set higher.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+pub enum ModuleState {
+    /// Fitted but uses no CPU or powergrid and applies no effects
+    Offline,
+    /// Uses CPU and powergrid; passive effects apply
+    Online,
+    /// Cycling; active effects apply and capacitor is used
+    #[default]
+    Active,
+    /// Cycling with overload bonuses, taking heat damage
+    Overheated,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Module {
     pub module_id: i64,
     pub module_name: String,
-    // Add more fields as needed (slot type, required skills, etc.)
+    /// SDE group ID of the module (e.g. 74 for hybrid turrets)
+    #[serde(default)]
+    pub group_id: i64,
+    /// Slot the module fits into
+    #[serde(default)]
+    pub slot: Option<SlotType>,
+    /// Base dogma attributes keyed by SDE attribute name (e.g. `cpu`, `power`)
+    #[serde(default)]
+    pub attributes: HashMap<String, f64>,
+    /// Modifiers the module applies to the ship or other modules
+    #[serde(default)]
+    pub effects: Vec<Modifier>,
+    /// Skills required to fit the module: (skill_id, skill_name, level)
+    #[serde(default)]
+    pub required_skills: Vec<(i64, String, i32)>,
+    /// Training rank (SDE `skillTimeConstant`) of the required skills, by skill ID
+    #[serde(default)]
+    pub skill_ranks: HashMap<i64, f64>,
+    /// Charge loaded in the module, if any
+    #[serde(default)]
+    pub charge: Option<Charge>,
+    /// Whether the module is offline, online, active or overheated
+    #[serde(default)]
+    pub state: ModuleState,
+    /// Bonuses scaled by a skill level, like a hull bonus (T3 subsystem bonuses)
+    #[serde(default)]
+    pub bonuses: Vec<HullBonus>,
+    /// SDE type this module is a meta variation of (`None` for T1 modules)
+    #[serde(default)]
+    pub variation_parent_id: Option<i64>,
+    /// SDE meta group (1 tech I, 2 tech II, 4 faction, 5 officer, 6 deadspace, ...), if known
+    #[serde(default)]
+    pub meta_group_id: Option<i64>,
+}
+
+/// Tactical destroyer mode (Defense, Propulsion or Sharpshooter)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ShipMode {
+    pub mode_id: i64,
+    pub mode_name: String,
+    /// Hull the mode belongs to
+    pub ship_id: i64,
+    /// Modifiers the mode applies while active
+    #[serde(default)]
+    pub effects: Vec<Modifier>,
+}
+
+/// Charge (ammo, crystal, script) that can be loaded into a module
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Charge {
+    pub charge_id: i64,
+    pub charge_name: String,
+    /// SDE group ID of the charge, matched against the module's `chargeGroupN`
+    #[serde(default)]
+    pub group_id: i64,
+    /// Base dogma attributes keyed by SDE attribute name (e.g. `volume`, `emDamage`)
+    #[serde(default)]
+    pub attributes: HashMap<String, f64>,
+    /// Skills required to use the charge: (skill_id, skill_name, level)
+    #[serde(default)]
+    pub required_skills: Vec<(i64, String, i32)>,
+    /// Training rank (SDE `skillTimeConstant`) of the required skills, by skill ID
+    #[serde(default)]
+    pub skill_ranks: HashMap<i64, f64>,
+}
+
+/// Drone carried in the fit's drone bay
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Drone {
+    pub drone_id: i64,
+    pub drone_name: String,
+    /// SDE group ID of the drone (e.g. 101 for mining drones)
+    #[serde(default)]
+    pub group_id: i64,
+    /// Base dogma attributes keyed by SDE attribute name
+    #[serde(default)]
+    pub attributes: HashMap<String, f64>,
+    /// Skills required to use the drone: (skill_id, skill_name, level)
+    #[serde(default)]
+    pub required_skills: Vec<(i64, String, i32)>,
+    /// Training rank (SDE `skillTimeConstant`) of the required skills, by skill ID
+    #[serde(default)]
+    pub skill_ranks: HashMap<i64, f64>,
+    /// Number of drones of this type in the bay
+    pub quantity: u32,
 }
 
 /// Fit variant struct for ship fitting recommendations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct FitVariant {
     pub fit_name: String,
     pub ship: Ship,
     pub modules: Vec<Module>,
+    /// Drones in the drone bay
+    #[serde(default)]
+    pub drones: Vec<Drone>,
+    /// Active mode of a tactical destroyer
+    #[serde(default)]
+    pub mode: Option<ShipMode>,
     pub rationale: String, // Explanation for the fit
-                           // Add more fields as needed (performance metrics, etc.)
+    /// Calculated stats, filled in once the fit has been evaluated
+    #[serde(default)]
+    pub stats: Option<FitStats>,
+    /// Implants the fit relies on (e.g. fitting implants), plugged in on top of the clone's
+    #[serde(default)]
+    pub implants: Vec<Implant>,
+    /// Changes made to turn another fit into this one, with their stat cost
+    #[serde(default)]
+    pub changes: Vec<FitChange>,
+    /// Total ISK cost, if every item in the fit has a price
+    #[serde(default)]
+    pub cost: Option<f64>,
+    /// Stats lost to an ISK budget: this fit's stats minus those of the fit built for the
+    /// same goal without the budget
+    #[serde(default)]
+    pub budget_delta: Option<StatDelta>,
+    /// Items on hand and to buy, when the character's assets are known
+    #[serde(default)]
+    pub availability: Vec<ItemAvailability>,
+    /// Structured explanation of the fit; `rationale` is its rendered text
+    #[serde(default)]
+    pub explanation: Option<Rationale>,
+    /// Safety rating and warnings for the activity the fit was recommended for
+    #[serde(default)]
+    pub safety: Option<SafetyReport>,
+}
+
+/// One change made to a fit (e.g. a module downgraded so the character can use it)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FitChange {
+    pub description: String,
+    /// Stats after the change minus stats before it
+    pub stat_delta: StatDelta,
 }
 
 /// Validation result for a fit
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FitValidation {
     pub is_valid: bool,
     pub missing_requirements: Vec<String>,
@@ -104,7 +294,7 @@ pub enum ShipTier {
     // Add more as needed
 }
 
-/// Validate a fit for powergrid, CPU, slots, and skill requirements
+/// Validate a fit for powergrid, CPU, slots, skill and charge requirements
 ///
 /// # Arguments
 /// * `fit` - The fit variant to validate
@@ -113,12 +303,135 @@ pub enum ShipTier {
 /// # Returns
 /// - FitValidation struct with validation results
 pub fn validate_fit(fit: &FitVariant, user_skills: &[Skill]) -> FitValidation {
-    // TODO: Check powergrid, CPU, slot layout, and required skills
-    // Populate missing_requirements and warnings as needed
+    validate_fit_with(fit, &CalcContext::from_skills(user_skills))
+}
+
+/// Validate a fit with implants, boosters, fleet boosts and environment applied
+pub fn validate_fit_with(fit: &FitVariant, ctx: &CalcContext) -> FitValidation {
+    let mut missing_requirements = vec![];
+    let mut warnings = vec![];
+    let calc = calculate_attributes_with(fit, ctx);
+    let stats = calculate_fit_stats_with(fit, ctx);
+
+    // Fitting resources
+    if stats.cpu_used > stats.cpu_output {
+        missing_requirements.push(format!(
+            "CPU overloaded: {:.1} / {:.1} tf",
+            stats.cpu_used, stats.cpu_output
+        ));
+    }
+    if stats.powergrid_used > stats.powergrid_output {
+        missing_requirements.push(format!(
+            "Powergrid overloaded: {:.1} / {:.1} MW",
+            stats.powergrid_used, stats.powergrid_output
+        ));
+    }
+    if stats.calibration_used > stats.calibration_output {
+        missing_requirements.push(format!(
+            "Calibration overloaded: {:.0} / {:.0}",
+            stats.calibration_used, stats.calibration_output
+        ));
+    }
+
+    // Slot layout and hardpoints
+    for slot in [
+        SlotType::High,
+        SlotType::Mid,
+        SlotType::Low,
+        SlotType::Rig,
+        SlotType::Subsystem,
+    ] {
+        let used = fit.modules.iter().filter(|m| m.slot == Some(slot)).count();
+        let available = calc.slot_count(slot);
+        if used > available {
+            missing_requirements.push(format!(
+                "Too many {:?} slot modules: {} / {}",
+                slot, used, available
+            ));
+        }
+    }
+    let turrets = fit.modules.iter().filter(|m| is_turret(m)).count();
+    let launchers = fit.modules.iter().filter(|m| is_launcher(m)).count();
+    let turret_hardpoints = calc.ship_attr(attr::TURRET_SLOTS) as usize;
+    let launcher_hardpoints = calc.ship_attr(attr::LAUNCHER_SLOTS) as usize;
+    if turrets > turret_hardpoints {
+        missing_requirements.push(format!(
+            "Not enough turret hardpoints: {} / {}",
+            turrets, turret_hardpoints
+        ));
+    }
+    if launchers > launcher_hardpoints {
+        missing_requirements.push(format!(
+            "Not enough launcher hardpoints: {} / {}",
+            launchers, launcher_hardpoints
+        ));
+    }
+    let mut checked_groups = vec![];
+    for module in &fit.modules {
+        let Some(max) = module.attributes.get(attr::MAX_GROUP_FITTED) else {
+            continue;
+        };
+        if checked_groups.contains(&module.group_id) {
+            continue;
+        }
+        checked_groups.push(module.group_id);
+        let count = fit
+            .modules
+            .iter()
+            .filter(|m| m.group_id == module.group_id)
+            .count();
+        if count > *max as usize {
+            missing_requirements.push(format!(
+                "{}: at most {} of this group can be fitted",
+                module.module_name, max
+            ));
+        }
+    }
+
+    // T3 subsystems and tactical modes
+    missing_requirements.extend(validate_t3(fit));
+
+    // Skills for hull, modules, charges and drones
+    let mut check_skills = |item: &str, required: &[(i64, String, i32)]| {
+        for (skill_id, skill_name, level) in required {
+            if ctx.skill_level(*skill_id) < *level {
+                missing_requirements.push(format!("{} requires {} {}", item, skill_name, level));
+            }
+        }
+    };
+    check_skills(&fit.ship.ship_name, &fit.ship.required_skills);
+    for module in &fit.modules {
+        check_skills(&module.module_name, &module.required_skills);
+        if let Some(charge) = &module.charge {
+            check_skills(&charge.charge_name, &charge.required_skills);
+        }
+    }
+    for drone in &fit.drones {
+        check_skills(&drone.drone_name, &drone.required_skills);
+    }
+
+    // Charges
+    for module in &fit.modules {
+        match &module.charge {
+            Some(charge) => {
+                if let Err(reason) = check_charge_compatibility(module, charge) {
+                    missing_requirements.push(reason);
+                }
+            }
+            None if is_turret(module) || is_launcher(module) => {
+                warnings.push(format!("{} has no charge loaded", module.module_name));
+            }
+            None => {}
+        }
+    }
+    if !stats.capacitor_stable {
+        warnings.push("Capacitor is not stable".into());
+    }
+
     FitValidation {
-        is_valid: false,
-        missing_requirements: vec!["Not implemented".into()],
-        warnings: vec![],
+        is_valid: missing_requirements.is_empty(),
+        missing_requirements,
+        warnings,
     }
 }
 
@@ -217,6 +530,27 @@ pub async fn fetch_character_info(
         .map_err(|e| format!("Failed to parse character info: {}", e))
 }
 
+/// Fetch the implant type IDs of the character's active clone from ESI
+pub async fn fetch_character_implants(
+    access_token: &str,
+    character_id: i64,
+) -> Result<Vec<i64>, String> {
+    let url = format!(
+        "https://esi.evetech.net/latest/characters/{}/implants/?datasource=tranquility",
+        character_id
+    );
+    let client = Client::new();
+    let resp = client
+        .get(&url)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch implants: {}", e))?;
+    resp.json::<Vec<i64>>()
+        .await
+        .map_err(|e| format!("Failed to parse implants: {}", e))
+}
+
 /// Fetch character skills from ESI
 pub async fn fetch_character_skills(
     access_token: &str,
@@ -253,56 +587,129 @@ pub async fn fetch_character_skills(
 /// * `all_modules` - List of all modules from SDE
 ///
 /// # Returns
-/// - List of ships and modules the user is eligible to use
+/// - List of ships and modules the user is eligible to use: those whose required
+///   skills are all trained to at least the required level
 pub fn match_skills_to_ships_and_modules(
     user_skills: &[Skill],
     all_ships: &[Ship],
     all_modules: &[Module],
 ) -> (Vec<Ship>, Vec<Module>) {
-    // TODO: Filter ships and modules based on user skill levels
-    // Example: Only include ships/modules where user meets all required skills
-    (vec![], vec![])
+    let levels: HashMap<i64, i32> = user_skills
+        .iter()
+        .map(|s| (s.skill_id, s.active_level))
+        .collect();
+    let trained = |required: &[(i64, String, i32)]| {
+        required
+            .iter()
+            .all(|(skill_id, _, level)| levels.get(skill_id).copied().unwrap_or(0) >= *level)
+    };
+    let ships = all_ships
+        .iter()
+        .filter(|ship| trained(&ship.required_skills))
+        .cloned()
+        .collect();
+    let modules = all_modules
+        .iter()
+        .filter(|module| trained(&module.required_skills))
+        .cloned()
+        .collect();
+    (ships, modules)
 }
 
 /// Generate five fit variants per ship/activity
 ///
 /// # Arguments
 /// * `ship` - The ship to generate fits for
-/// * `activity` - The selected activity (e.g., "Mission Running", "PVP")
+/// * `activity` - The selected activity profile
+/// * `ctx` - Skills, implants, boosters, fleet boosts and environment the fits are built
+///   and scored with
 /// * `eligible_modules` - List of modules the user can use
+/// * `charges` - Charges available for the fits' weapons
+/// * `prices` - ISK price per type ID, for each fit's cost
+/// * `budget` - Optimizer limits: the maximum ISK cost of a fit (hull, modules and
+///   charges), if any, and the search's deadline and cancel flag
+/// * `locked` - Modules every fit must have; the optimizer fills the remaining slots
 ///
 /// # Returns
-/// - List of five recommended fit variants
+/// - List of recommended fit variants: max DPS, max tank, balanced, cap-stable and
+///   activity-optimized (exploration and logistics get their own specialised fits
+///   unless modules are locked), each with its cost and, under a budget, the stats the
+///   budget cost it
+/// - An error if the locked modules cannot be fitted together or break the budget
+#[allow(clippy::too_many_arguments)]
 pub fn generate_fit_variants(
     ship: &Ship,
-    activity: &str,
+    activity: &Activity,
+    ctx: &CalcContext,
     eligible_modules: &[Module],
-) -> Vec<FitVariant> {
-    // TODO: Implement logic for:
-    // - Maximum DPS
-    // - Maximum Tank
-    // - Balanced
-    // - Cap-Stable
-    // - Activity-Optimized
-    vec![]
+    charges: &[Charge],
+    prices: &PriceTable,
+    locked: &[LockedModule],
+    budget: SearchBudget,
+) -> Result<Vec<FitVariant>, String> {
+    let cost_limit = budget.cost_limit;
+    let base = locked_base(ship, locked, charges, activity, ctx)?;
+    if cost_limit.is_some_and(|limit| !limit.allows(&base)) {
+        return Err(format!(
+            "The locked modules on the {} exceed the ISK budget or have no price",
+            ship.ship_name
+        ));
+    }
+    let mut fits = match activity.kind {
+        _ if !locked.is_empty() => {
+            generate_optimized_fits(&base, eligible_modules, charges, activity, ctx, budget)
+        }
+        ActivityKind::Exploration { .. } => generate_exploration_fits(ship, eligible_modules, ctx),
+        ActivityKind::Pvp {
+            role: PvpRole::Logistics,
+        } => generate_logistics_fits(ship, eligible_modules, ctx),
+        _ => generate_optimized_fits(&base, eligible_modules, charges, activity, ctx, budget),
+    };
+    // Specialised fits are not built to a budget: drop those over it
+    fits.retain(|fit| cost_limit.is_none_or(|limit| limit.allows(fit)));
+    for fit in &mut fits {
+        fit.cost = fit_cost(fit, prices);
+        if fit.explanation.is_none() {
+            let rationale = explain_fit(fit, fit.rationale.clone(), vec![], activity, ctx);
+            fit.rationale = rationale.render();
+            fit.explanation = Some(rationale);
+        }
+    }
+    Ok(fits)
 }
 
 /// Suggest alternative fits if requirements are not met
 ///
 /// # Arguments
 /// * `fit` - The original fit variant
-/// * `user_skills` - The user's skills
+/// * `ctx` - The character's skills, clone implants and the rest of the context
 /// * `eligible_modules` - List of modules the user can use
+/// * `charges` - Charges to reload weapons with when the loaded one needs missing skills
+/// * `target` - Target the replacement charges are picked for
+/// * `fitting_implants` - Implants that may be plugged in for more CPU or powergrid
+/// * `locked` - Modules the player locked; they are never swapped or removed
 ///
 /// # Returns
-/// - List of alternative FitVariant suggestions
+/// - List of flyable alternative FitVariant suggestions, each with its changes and their
+///   stat cost
 pub fn suggest_alternative_fits(
     fit: &FitVariant,
-    user_skills: &[Skill],
+    ctx: &CalcContext,
     eligible_modules: &[Module],
+    charges: &[Charge],
+    target: &TargetProfile,
+    fitting_implants: &[Implant],
+    locked: &[LockedModule],
 ) -> Vec<FitVariant> {
-    // TODO: Generate alternative fits by replacing modules or downgrading requirements
-    vec![]
+    repair_fit(
+        fit,
+        ctx,
+        eligible_modules,
+        charges,
+        target,
+        fitting_implants,
+        locked,
+    )
 }
 
 /// Generate a prioritized skill plan for a recommended fit
@@ -315,9 +722,9 @@ pub fn suggest_alternative_fits(
 /// - Ordered list of (skill_id, skill_name, required_level, current_level) for missing or under-leveled skills
 ///
 /// # Logic
-/// - For each module and the ship in the fit, collect all required skills and levels
+/// - For the ship, each module, its loaded charge and each drone in the fit, collect all required skills and levels
 /// - Compare with user_skills; if user is missing a skill or has insufficient level, add to plan
-/// - Order the plan by fit unlock dependencies (e.g., ship skills first, then modules)
+/// - Order the plan by fit unlock dependencies (ship skills first, then modules, charges and drones)
 /// - No duplicate skills; if multiple modules require the same skill at different levels, use the highest required
 pub fn generate_skill_plan_for_fit(
     fit: &FitVariant,
@@ -327,6 +734,8 @@ pub fn generate_skill_plan_for_fit(
     use std::collections::HashMap;
     // Map skill_id -> (skill_name, required_level)
     let mut required_skills: HashMap<i64, (String, i32)> = HashMap::new();
+    // Skill IDs in the order they are first required
+    let mut order: Vec<i64> = vec![];
     // Helper: add or update required skill if higher level needed
     let mut add_required = |skill_id: i64, skill_name: &str, level: i32| {
         required_skills
@@ -336,7 +745,10 @@ pub fn generate_skill_plan_for_fit(
                     e.1 = level;
                 }
             })
-            .or_insert((skill_name.to_string(), level));
+            .or_insert_with(|| {
+                order.push(skill_id);
+                (skill_name.to_string(), level)
+            });
     };
     // --- SDE DB lookup (async) would go here ---
     // Example:
@@ -351,24 +763,38 @@ pub fn generate_skill_plan_for_fit(
     //     // Fallback placeholder logic
     // }
     // --- End SDE DB lookup ---
-    // Placeholder: assume ship requires "Spaceship Command" level 4
-    add_required(333, "Spaceship Command", 4);
-    // Placeholder: assume all modules require "Engineering" level 3
+    for (skill_id, skill_name, level) in &fit.ship.required_skills {
+        add_required(*skill_id, skill_name, *level);
+    }
     for module in &fit.modules {
-        add_required(444, "Engineering", 3);
+        for (skill_id, skill_name, level) in &module.required_skills {
+            add_required(*skill_id, skill_name, *level);
+        }
+        // Loaded charges (e.g. T2 ammo) carry their own specialization requirements
+        if let Some(charge) = &module.charge {
+            for (skill_id, skill_name, level) in &charge.required_skills {
+                add_required(*skill_id, skill_name, *level);
+            }
+        }
+    }
+    for drone in &fit.drones {
+        for (skill_id, skill_name, level) in &drone.required_skills {
+            add_required(*skill_id, skill_name, *level);
+        }
     }
     // Map user skills for quick lookup
     let mut user_skill_map: HashMap<i64, (String, i32)> = HashMap::new();
     for s in user_skills {
         user_skill_map.insert(
             s.skill_id,
-            s.skill_name.clone().unwrap_or_default(),
-            s.active_level,
+            (s.skill_name.clone().unwrap_or_default(), s.active_level),
         );
     }
-    // Build the plan: (skill_id, skill_name, required_level, current_level)
+    // Build the plan in the order the skills were required: (skill_id, skill_name,
+    // required_level, current_level)
     let mut plan = vec![];
-    for (skill_id, (skill_name, required_level)) in required_skills.iter() {
+    for skill_id in &order {
+        let (skill_name, required_level) = &required_skills[skill_id];
         let current_level = user_skill_map
             .get(skill_id)
             .map(|(_, lvl)| *lvl)
@@ -382,8 +808,6 @@ pub fn generate_skill_plan_for_fit(
             ));
         }
     }
-    // Order: ship skills first (id 333), then others
-    plan.sort_by_key(|(skill_id, _, _, _)| if *skill_id == 333 { 0 } else { 1 });
     plan
 }
 
@@ -522,3 +946,197 @@ pub fn set_eula_accepted(accepted: bool) -> Result<(), String> {
 }
 
 // TODO: Add logic to update the local database with fetched character and skill data
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        activity, armor_plate, attrs, blaster_fit, core_probe_launcher, data_analyzer,
+        hybrid_charge, large_blaster,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_match_skills_to_ships_and_modules() {
+        let megathron = Ship {
+            ship_id: 641,
+            ship_name: "Megathron".to_string(),
+            required_skills: vec![(3336, "Gallente Battleship".to_string(), 1)],
+            ..Default::default()
+        };
+        let rifter = Ship {
+            ship_id: 587,
+            ship_name: "Rifter".to_string(),
+            required_skills: vec![(3329, "Minmatar Frigate".to_string(), 1)],
+            ..Default::default()
+        };
+        let modules = vec![large_blaster(), armor_plate()];
+        let skill = |skill_id, active_level| Skill {
+            skill_id,
+            skill_name: None,
+            active_level,
+        };
+
+        // Large Blaster Specialization (3307) is only at 4 of the 5 needed
+        let (ships, eligible) = match_skills_to_ships_and_modules(
+            &[skill(3336, 3), skill(3307, 4)],
+            &[megathron.clone(), rifter.clone()],
+            &modules,
+        );
+        let ship_ids: Vec<i64> = ships.iter().map(|s| s.ship_id).collect();
+        assert_eq!(ship_ids, vec![641]);
+        let module_ids: Vec<i64> = eligible.iter().map(|m| m.module_id).collect();
+        assert_eq!(module_ids, vec![20353]);
+
+        let (ships, eligible) =
+            match_skills_to_ships_and_modules(&[skill(3307, 5)], &[megathron, rifter], &modules);
+        assert!(ships.is_empty());
+        assert!(eligible.iter().any(|m| m.module_id == 3186));
+    }
+
+    #[test]
+    fn test_generate_fit_variants() {
+        let heron = Ship {
+            ship_id: 605,
+            ship_name: "Heron".to_string(),
+            attributes: attrs(&[
+                ("hiSlots", 2.0),
+                ("medSlots", 4.0),
+                ("cpuOutput", 200.0),
+                ("powerOutput", 30.0),
+            ]),
+            ..Default::default()
+        };
+        let mut sisters = core_probe_launcher();
+        sisters.module_name = "Sisters Core Probe Launcher".to_string();
+        sisters
+            .attributes
+            .insert("scanStrengthBonus".to_string(), 10.0);
+        let modules = vec![core_probe_launcher(), sisters, data_analyzer()];
+        let fits = generate_fit_variants(
+            &heron,
+            &activity("Exploration"),
+            &CalcContext::default(),
+            &modules,
+            &[],
+            &HashMap::new(),
+            &[],
+            SearchBudget::default(),
+        )
+        .unwrap();
+        assert_eq!(fits.len(), 2);
+        // The Sisters launcher gives the stronger probes
+        assert!(fits[0].modules[0].module_name.starts_with("Sisters"));
+        assert!(fits[0].modules.iter().any(|m| m.group_id == 538));
+    }
+
+    #[test]
+    fn test_validate_fit() {
+        let mut fit = blaster_fit();
+        fit.modules[0].charge = Some(hybrid_charge("Antimatter Charge L", 48.0, 0.5));
+        let skilled = vec![Skill {
+            skill_id: 3307,
+            skill_name: Some("Large Hybrid Turret".to_string()),
+            active_level: 5,
+        }];
+        let validation = validate_fit(&fit, &skilled);
+        assert!(validation.is_valid, "{:?}", validation.missing_requirements);
+        // Missing turret skill and too little powergrid
+        fit.ship
+            .attributes
+            .insert("powerOutput".to_string(), 1_000.0);
+        let validation = validate_fit(&fit, &[]);
+        assert!(!validation.is_valid);
+        assert!(validation
+            .missing_requirements
+            .iter()
+            .any(|r| r.contains("Large Hybrid Turret 5")));
+        assert!(validation
+            .missing_requirements
+            .iter()
+            .any(|r| r.starts_with("Powergrid overloaded")));
+    }
+
+    #[test]
+    fn test_skill_plan_includes_charge_skills() {
+        let mut fit = blaster_fit();
+        let mut void = hybrid_charge("Void L", 60.0, 0.75);
+        void.required_skills = vec![(12209, "Large Blaster Specialization".to_string(), 1)];
+        fit.modules[0].charge = Some(void);
+        let plan = generate_skill_plan_for_fit(&fit, &[]);
+        assert!(plan
+            .iter()
+            .any(|(id, _, req, cur)| *id == 12209 && *req == 1 && *cur == 0));
+        assert!(plan.iter().any(|(id, _, req, _)| *id == 3307 && *req == 5));
+    }
+
+    #[test]
+    fn test_generate_skill_plan_for_fit() {
+        // Mock fit: requires Spaceship Command 4 (ship), Engineering 3 (module) and
+        // Drones 1 (drone); the second module has no requirements
+        let fit = FitVariant {
+            fit_name: "Test Fit".to_string(),
+            ship: Ship {
+                ship_id: 1,
+                ship_name: "Test Ship".to_string(),
+                required_skills: vec![(333, "Spaceship Command".to_string(), 4)],
+                ..Default::default()
+            },
+            modules: vec![
+                Module {
+                    module_id: 10,
+                    module_name: "Test Module".to_string(),
+                    required_skills: vec![(444, "Engineering".to_string(), 3)],
+                    ..Default::default()
+                },
+                Module {
+                    module_id: 11,
+                    module_name: "Unskilled Module".to_string(),
+                    ..Default::default()
+                },
+            ],
+            drones: vec![Drone {
+                drone_id: 2488,
+                drone_name: "Warrior I".to_string(),
+                required_skills: vec![(3436, "Drones".to_string(), 1)],
+                quantity: 5,
+                ..Default::default()
+            }],
+            rationale: "Test rationale".to_string(),
+            ..Default::default()
+        };
+        // User has Spaceship Command 2, no Engineering or Drones
+        let user_skills = vec![Skill {
+            skill_id: 333,
+            skill_name: Some("Spaceship Command".to_string()),
+            active_level: 2,
+        }];
+        let plan = generate_skill_plan_for_fit(&fit, &user_skills);
+        // Ship skills first, then modules and drones; nothing is assumed for the
+        // unskilled module
+        assert_eq!(
+            plan,
+            vec![
+                (333, "Spaceship Command".to_string(), 4, 2),
+                (444, "Engineering".to_string(), 3, 0),
+                (3436, "Drones".to_string(), 1, 0),
+            ]
+        );
+        let validation = validate_fit(&fit, &user_skills);
+        assert!(validation
+            .missing_requirements
+            .iter()
+            .any(|r| r == "Warrior I requires Drones 1"));
+    }
+
+    #[test]
+    fn test_export_skill_plan_evemon() {
+        let plan = vec![
+            (333, "Spaceship Command".to_string(), 4, 2),
+            (444, "Engineering".to_string(), 3, 0),
+        ];
+        let export = export_skill_plan_evemon(&plan);
+        let expected = "Spaceship Command Level 4\nEngineering Level 3";
+        assert_eq!(export, expected);
+    }
+}
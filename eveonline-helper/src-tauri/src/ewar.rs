@@ -0,0 +1,314 @@
+//! Electronic warfare and tackle for EveOnline Helper
+//
+// Reports the strength and range of running tackle and EWAR modules after skill, hull
+// and module bonuses:
+// - stasis webifiers: velocity reduction in percent (`speedFactor`)
+// - warp scramblers/disruptors: warp disruption points (`warpScrambleStrength`)
+// - ECM: jam strength per sensor type; jam chance = ECM strength / target sensor strength
+// - sensor dampeners: lock range / scan resolution reduction
+// - weapon disruptors: optimal range and tracking reduction
+// - target painters: signature radius increase
+// Range is the module's optimal (`maxRange`) plus falloff (`falloffEffectiveness`).
+
+use crate::esi::Module;
+use crate::fitting::{apply_modifiers, attr, FitStats, Modifier, ModifierTarget};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// SDE group of warp scramblers and warp disruptors
+pub const WARP_SCRAMBLER_GROUP: i64 = 52;
+/// SDE group of stasis webifiers
+pub const STASIS_WEB_GROUP: i64 = 65;
+/// SDE group of ECM modules
+pub const ECM_GROUP: i64 = 201;
+/// SDE group of sensor dampeners
+pub const SENSOR_DAMPENER_GROUP: i64 = 208;
+/// SDE group of weapon (tracking) disruptors
+pub const WEAPON_DISRUPTOR_GROUP: i64 = 291;
+/// SDE group of target painters
+pub const TARGET_PAINTER_GROUP: i64 = 379;
+
+/// Sensor type of a ship, which decides which ECM strength applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum SensorType {
+    #[default]
+    Radar,
+    Ladar,
+    Magnetometric,
+    Gravimetric,
+}
+
+impl SensorType {
+    /// Index into `attr::SENSOR_STRENGTHS` and `attr::ECM_STRENGTHS`
+    fn index(self) -> usize {
+        match self {
+            SensorType::Radar => 0,
+            SensorType::Ladar => 1,
+            SensorType::Magnetometric => 2,
+            SensorType::Gravimetric => 3,
+        }
+    }
+}
+
+/// Sensors of the ship being jammed
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct SensorProfile {
+    pub sensor_type: SensorType,
+    pub strength: f64,
+}
+
+/// What a tackle or EWAR module does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EwarKind {
+    StasisWebifier,
+    WarpScrambler,
+    WarpDisruptor,
+    Ecm,
+    SensorDampener,
+    WeaponDisruptor,
+    TargetPainter,
+}
+
+/// Strength and range of one running tackle or EWAR module
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EwarModuleStats {
+    /// Index of the module in `FitVariant::modules`
+    pub module_index: usize,
+    pub module_name: String,
+    pub kind: EwarKind,
+    /// Main effect strength: velocity reduction %, warp disruption points, lock range
+    /// reduction % (damps), optimal reduction % (disruptors) or signature increase % (painters)
+    pub strength: f64,
+    /// Secondary effect: scan resolution reduction % (damps), tracking reduction %
+    /// (disruptors), 0 otherwise
+    pub secondary_strength: f64,
+    /// ECM strength per sensor type (radar, ladar, magnetometric, gravimetric)
+    pub ecm_strengths: [f64; 4],
+    pub optimal_range: f64,
+    pub falloff: f64,
+}
+
+impl EwarModuleStats {
+    /// Chance this ECM jams a target per cycle (0.0 for other modules)
+    pub fn jam_chance(&self, target: &SensorProfile) -> f64 {
+        if self.kind != EwarKind::Ecm || target.strength <= 0.0 {
+            return 0.0;
+        }
+        (self.ecm_strengths[target.sensor_type.index()] / target.strength).min(1.0)
+    }
+}
+
+/// Tackle and EWAR stats of a module, or None if it is not a tackle/EWAR module
+pub fn ewar_module_stats(
+    index: usize,
+    module: &Module,
+    attributes: &HashMap<String, f64>,
+) -> Option<EwarModuleStats> {
+    let get = |name: &str| attributes.get(name).copied().unwrap_or(0.0);
+    let (kind, strength, secondary_strength) = match module.group_id {
+        STASIS_WEB_GROUP => (EwarKind::StasisWebifier, -get(attr::SPEED_FACTOR), 0.0),
+        WARP_SCRAMBLER_GROUP => {
+            let kind = if get(attr::ACTIVATION_BLOCKED_STRENGTH) > 0.0 {
+                EwarKind::WarpScrambler
+            } else {
+                EwarKind::WarpDisruptor
+            };
+            (kind, get(attr::WARP_SCRAMBLE_STRENGTH), 0.0)
+        }
+        ECM_GROUP => (EwarKind::Ecm, 0.0, 0.0),
+        SENSOR_DAMPENER_GROUP => (
+            EwarKind::SensorDampener,
+            -get(attr::MAX_TARGET_RANGE_BONUS),
+            -get(attr::SCAN_RESOLUTION_BONUS),
+        ),
+        WEAPON_DISRUPTOR_GROUP => (
+            EwarKind::WeaponDisruptor,
+            -get(attr::MAX_RANGE_BONUS),
+            -get(attr::TRACKING_SPEED_BONUS),
+        ),
+        TARGET_PAINTER_GROUP => (
+            EwarKind::TargetPainter,
+            get(attr::SIGNATURE_RADIUS_BONUS),
+            0.0,
+        ),
+        _ => return None,
+    };
+    Some(EwarModuleStats {
+        module_index: index,
+        module_name: module.module_name.clone(),
+        kind,
+        strength,
+        secondary_strength,
+        ecm_strengths: attr::ECM_STRENGTHS.map(get),
+        optimal_range: get(attr::MAX_RANGE),
+        falloff: get(attr::FALLOFF_EFFECTIVENESS),
+    })
+}
+
+/// Combined velocity reduction of all running webs in percent (webs are stacking penalized)
+pub fn combined_web_strength(stats: &FitStats) -> f64 {
+    let modifiers: Vec<Modifier> = stats
+        .ewar
+        .iter()
+        .filter(|m| m.kind == EwarKind::StasisWebifier)
+        .map(|m| Modifier {
+            stacking_penalized: true,
+            ..Modifier::percent(ModifierTarget::Ship, attr::MAX_VELOCITY, -m.strength)
+        })
+        .collect();
+    let refs: Vec<&Modifier> = modifiers.iter().collect();
+    (1.0 - apply_modifiers(1.0, &refs)) * 100.0
+}
+
+/// Total warp disruption strength of all running scramblers and disruptors
+pub fn warp_disruption_strength(stats: &FitStats) -> f64 {
+    stats
+        .ewar
+        .iter()
+        .filter(|m| matches!(m.kind, EwarKind::WarpScrambler | EwarKind::WarpDisruptor))
+        .map(|m| m.strength)
+        .sum()
+}
+
+/// Longest range at which the fit can hold a target (best scram/point optimal)
+pub fn tackle_range(stats: &FitStats) -> f64 {
+    stats
+        .ewar
+        .iter()
+        .filter(|m| matches!(m.kind, EwarKind::WarpScrambler | EwarKind::WarpDisruptor))
+        .map(|m| m.optimal_range)
+        .fold(0.0, f64::max)
+}
+
+/// Chance that at least one running ECM jams the target in a cycle
+pub fn ecm_jam_chance(stats: &FitStats, target: &SensorProfile) -> f64 {
+    1.0 - stats
+        .ewar
+        .iter()
+        .map(|m| 1.0 - m.jam_chance(target))
+        .product::<f64>()
+}
+
+/// Score of a fit for the "tackle" activity
+///
+/// Rewards holding power (warp disruption points), reach (tackle range in km), web
+/// strength and speed to get into range. Fits without a scram or point score 0.
+pub fn tackle_score(stats: &FitStats) -> f64 {
+    let points = warp_disruption_strength(stats);
+    if points <= 0.0 {
+        return 0.0;
+    }
+    points * 10.0
+        + tackle_range(stats) / 1000.0
+        + combined_web_strength(stats) / 10.0
+        + stats.max_velocity / 100.0
+}
+
+/// Fleet-facing totals of a fit's tackle and EWAR
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EwarSummary {
+    /// Combined web velocity reduction in percent
+    pub web_strength: f64,
+    pub warp_disruption_strength: f64,
+    /// Best scram/point range in metres
+    pub tackle_range: f64,
+    /// Chance per cycle to jam the given target
+    pub ecm_jam_chance: f64,
+    pub tackle_score: f64,
+}
+
+/// Summarize a fit's tackle and EWAR against a target's sensors
+pub fn summarize_ewar(stats: &FitStats, target: &SensorProfile) -> EwarSummary {
+    EwarSummary {
+        web_strength: combined_web_strength(stats),
+        warp_disruption_strength: warp_disruption_strength(stats),
+        tackle_range: tackle_range(stats),
+        ecm_jam_chance: ecm_jam_chance(stats, target),
+        tackle_score: tackle_score(stats),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{FitVariant, Module, Skill, SlotType};
+    use crate::fitting::calculate_fit_stats;
+    use crate::test_support::attrs;
+
+    fn ewar_module(name: &str, group_id: i64, pairs: &[(&str, f64)]) -> Module {
+        Module {
+            module_name: name.to_string(),
+            group_id,
+            slot: Some(SlotType::Mid),
+            attributes: attrs(pairs),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tackle_and_ewar() {
+        let web = || {
+            ewar_module(
+                "Stasis Webifier II",
+                65,
+                &[
+                    ("speedFactor", -60.0),
+                    ("maxRange", 10_000.0),
+                    ("duration", 5_000.0),
+                ],
+            )
+        };
+        let scram = ewar_module(
+            "Warp Scrambler II",
+            52,
+            &[
+                ("warpScrambleStrength", 2.0),
+                ("activationBlockedStrenth", 1.0),
+                ("maxRange", 9_000.0),
+                ("duration", 5_000.0),
+            ],
+        );
+        let ecm = ewar_module(
+            "Multispectral ECM II",
+            201,
+            &[
+                ("scanRadarStrengthBonus", 4.0),
+                ("scanGravimetricStrengthBonus", 4.0),
+                ("maxRange", 30_000.0),
+                ("falloffEffectiveness", 30_000.0),
+                ("duration", 20_000.0),
+            ],
+        );
+        let fit = FitVariant {
+            modules: vec![web(), web(), scram, ecm],
+            ..Default::default()
+        };
+        let skills = [Skill {
+            skill_id: 19760,
+            skill_name: Some("Signal Dispersion".to_string()),
+            active_level: 5,
+        }];
+        let stats = calculate_fit_stats(&fit, &skills);
+        assert_eq!(stats.ewar.len(), 4);
+        assert_eq!(stats.ewar[2].kind, EwarKind::WarpScrambler);
+        let target = SensorProfile {
+            sensor_type: SensorType::Gravimetric,
+            strength: 20.0,
+        };
+        let summary = summarize_ewar(&stats, &target);
+        // Second web is stacking penalized
+        let second = 0.6 * (-(1.0_f64 / 2.67).powi(2)).exp();
+        let expected_web = (1.0 - 0.4 * (1.0 - second)) * 100.0;
+        assert!((summary.web_strength - expected_web).abs() < 1e-9);
+        assert_eq!(summary.warp_disruption_strength, 2.0);
+        assert_eq!(summary.tackle_range, 9_000.0);
+        // Signal Dispersion V: 4 * 1.25 = 5 strength against 20 sensor strength
+        assert!((summary.ecm_jam_chance - 0.25).abs() < 1e-9);
+        assert!(summary.tackle_score > 0.0);
+        let ladar = SensorProfile {
+            sensor_type: SensorType::Ladar,
+            strength: 20.0,
+        };
+        assert_eq!(summarize_ewar(&stats, &ladar).ecm_jam_chance, 0.0);
+    }
+}
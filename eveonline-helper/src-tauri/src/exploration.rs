@@ -0,0 +1,368 @@
+//! Exploration stats and fits for EveOnline Helper
+//
+// Scanning: a probe's scan strength is its `baseSensorStrength` after skill, rig, implant
+// and hull modifiers (which target probes through Astrometrics), multiplied by the probe
+// launcher's `scanStrengthBonus`. Probes with `probeCanScanShips` are combat probes, the
+// rest core probes.
+//
+// Hacking: data and relic analyzers report `virusCoherence` and `virusStrength` after
+// Hacking/Archaeology, hull and implant bonuses.
+//
+// Exploration fits are ranked on probe strength, analyzer stats and align time, each
+// relative to the best candidate so no single stat dominates.
+
+use crate::charges::check_charge_compatibility;
+use crate::esi::{Charge, FitVariant, Module, ModuleState, Ship, SlotType};
+use crate::fitting::{
+    attr, calculate_attributes_with, calculate_fit_stats_with, module_state, CalcContext,
+};
+use serde::{Deserialize, Serialize};
+
+/// SDE group of scan probe launchers
+pub const PROBE_LAUNCHER_GROUP: i64 = 481;
+/// SDE group of data analyzers
+pub const DATA_ANALYZER_GROUP: i64 = 538;
+/// SDE group of relic analyzers
+pub const RELIC_ANALYZER_GROUP: i64 = 1223;
+/// Astrometrics skill ID, required by every scanner probe
+const ASTROMETRICS: i64 = 3412;
+/// Number of exploration fits returned per ship
+const MAX_FITS: usize = 5;
+
+/// Scanning and hacking stats of a fit
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExplorationStats {
+    pub fit_name: String,
+    /// Best scan strength of the carried core probes
+    pub core_probe_strength: f64,
+    /// Best scan strength of the carried combat probes
+    pub combat_probe_strength: f64,
+    /// Factor the fit applies to any probe's base strength (1.0 = no bonus)
+    pub probe_strength_multiplier: f64,
+    pub data_virus_coherence: f64,
+    pub data_virus_strength: f64,
+    pub relic_virus_coherence: f64,
+    pub relic_virus_strength: f64,
+    pub align_time: f64,
+}
+
+/// A probe with 1.0 base strength, used to measure the fit's probe strength bonuses
+fn reference_probe() -> Charge {
+    Charge {
+        charge_name: "Reference Probe".to_string(),
+        attributes: [(attr::SCAN_STRENGTH.to_string(), 1.0)].into(),
+        required_skills: vec![(ASTROMETRICS, "Astrometrics".to_string(), 1)],
+        ..Default::default()
+    }
+}
+
+/// Scan strength of a probe loaded into the launcher at `launcher_index`
+fn probe_strength(
+    fit: &FitVariant,
+    ctx: &CalcContext,
+    launcher_index: usize,
+    probe: &Charge,
+) -> f64 {
+    let mut trial = fit.clone();
+    trial.modules[launcher_index].charge = Some(probe.clone());
+    let calc = calculate_attributes_with(&trial, ctx);
+    let strength = calc.charges[launcher_index]
+        .as_ref()
+        .and_then(|charge| charge.get(attr::SCAN_STRENGTH))
+        .copied()
+        .unwrap_or(0.0);
+    strength * (1.0 + calc.module_attr(launcher_index, attr::SCAN_STRENGTH_BONUS) / 100.0)
+}
+
+/// Calculate the scanning and hacking stats of a fit
+///
+/// # Arguments
+/// * `fit` - The exploration fit
+/// * `ctx` - Skills, implants and other context
+/// * `probes` - Probe types carried in cargo (the launcher's loaded probe is always included)
+///
+/// # Returns
+/// - ExplorationStats for the fit
+pub fn calculate_exploration_stats(
+    fit: &FitVariant,
+    ctx: &CalcContext,
+    probes: &[Charge],
+) -> ExplorationStats {
+    let mut stats = ExplorationStats {
+        fit_name: fit.fit_name.clone(),
+        align_time: calculate_fit_stats_with(fit, ctx).align_time,
+        ..Default::default()
+    };
+    let launcher = fit.modules.iter().position(|m| {
+        m.group_id == PROBE_LAUNCHER_GROUP && module_state(m) != ModuleState::Offline
+    });
+    if let Some(index) = launcher {
+        stats.probe_strength_multiplier = probe_strength(fit, ctx, index, &reference_probe());
+        let loaded = fit.modules[index].charge.iter();
+        for probe in probes.iter().chain(loaded) {
+            if check_charge_compatibility(&fit.modules[index], probe).is_err() {
+                continue;
+            }
+            let strength = probe_strength(fit, ctx, index, probe);
+            let combat = probe
+                .attributes
+                .get(attr::PROBE_CAN_SCAN_SHIPS)
+                .copied()
+                .unwrap_or(0.0)
+                > 0.0;
+            let best = if combat {
+                &mut stats.combat_probe_strength
+            } else {
+                &mut stats.core_probe_strength
+            };
+            *best = best.max(strength);
+        }
+    }
+
+    let calc = calculate_attributes_with(fit, ctx);
+    for (index, module) in fit.modules.iter().enumerate() {
+        if module_state(module) == ModuleState::Offline {
+            continue;
+        }
+        let (coherence, strength) = match module.group_id {
+            DATA_ANALYZER_GROUP => (
+                &mut stats.data_virus_coherence,
+                &mut stats.data_virus_strength,
+            ),
+            RELIC_ANALYZER_GROUP => (
+                &mut stats.relic_virus_coherence,
+                &mut stats.relic_virus_strength,
+            ),
+            _ => continue,
+        };
+        *coherence = coherence.max(calc.module_attr(index, attr::VIRUS_COHERENCE));
+        *strength = strength.max(calc.module_attr(index, attr::VIRUS_STRENGTH));
+    }
+    stats
+}
+
+/// Rank exploration fits, best first
+///
+/// Each stat is scored relative to the best candidate (align time relative to the
+/// fastest), and the scores are summed. Equal scores keep the input order.
+pub fn rank_exploration_fits(
+    fits: &[FitVariant],
+    ctx: &CalcContext,
+    probes: &[Charge],
+) -> Vec<(FitVariant, ExplorationStats)> {
+    let stats: Vec<ExplorationStats> = fits
+        .iter()
+        .map(|fit| calculate_exploration_stats(fit, ctx, probes))
+        .collect();
+    let higher_is_better: [fn(&ExplorationStats) -> f64; 7] = [
+        |s| s.core_probe_strength,
+        |s| s.combat_probe_strength,
+        |s| s.probe_strength_multiplier,
+        |s| s.data_virus_coherence,
+        |s| s.data_virus_strength,
+        |s| s.relic_virus_coherence,
+        |s| s.relic_virus_strength,
+    ];
+    let fastest_align = stats
+        .iter()
+        .map(|s| s.align_time)
+        .filter(|t| *t > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    let score = |s: &ExplorationStats| -> f64 {
+        let mut score = 0.0;
+        for stat in higher_is_better {
+            let best = stats.iter().map(stat).fold(0.0, f64::max);
+            if best > 0.0 {
+                score += stat(s) / best;
+            }
+        }
+        if s.align_time > 0.0 && fastest_align.is_finite() {
+            score += fastest_align / s.align_time;
+        }
+        score
+    };
+    let mut ranked: Vec<(f64, FitVariant, ExplorationStats)> = fits
+        .iter()
+        .cloned()
+        .zip(stats.iter().cloned())
+        .map(|(fit, s)| (score(&s), fit, s))
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranked.into_iter().map(|(_, fit, s)| (fit, s)).collect()
+}
+
+/// Whether a module boosts scanning or hacking (scan rigs, analyzer rigs, ...)
+fn is_exploration_upgrade(module: &Module) -> bool {
+    module.effects.iter().any(|m| {
+        [
+            attr::SCAN_STRENGTH,
+            attr::VIRUS_COHERENCE,
+            attr::VIRUS_STRENGTH,
+        ]
+        .contains(&m.attribute.as_str())
+    })
+}
+
+/// Best analyzer of a group by virus strength, then coherence
+fn best_analyzer(modules: &[Module], group_id: i64) -> Option<&Module> {
+    let get = |m: &Module, name: &str| m.attributes.get(name).copied().unwrap_or(0.0);
+    modules
+        .iter()
+        .filter(|m| m.group_id == group_id && m.slot == Some(SlotType::Mid))
+        .max_by(|a, b| {
+            get(a, attr::VIRUS_STRENGTH)
+                .total_cmp(&get(b, attr::VIRUS_STRENGTH))
+                .then(get(a, attr::VIRUS_COHERENCE).total_cmp(&get(b, attr::VIRUS_COHERENCE)))
+        })
+}
+
+/// Build exploration fits for a hull from the modules the user can fit
+///
+/// One fit is built per probe launcher: the launcher, the best data and relic analyzers
+/// that fit in the mid slots, and exploration rigs up to the hull's rig slots and
+/// calibration. Fits that exceed CPU or powergrid are dropped.
+///
+/// # Returns
+/// - Up to five fits, ranked with `rank_exploration_fits`
+pub fn generate_exploration_fits(
+    ship: &Ship,
+    eligible_modules: &[Module],
+    ctx: &CalcContext,
+) -> Vec<FitVariant> {
+    let base = FitVariant {
+        fit_name: format!("{} Exploration", ship.ship_name),
+        ship: ship.clone(),
+        ..Default::default()
+    };
+    let calc = calculate_attributes_with(&base, ctx);
+    if calc.slot_count(SlotType::High) == 0 {
+        return vec![];
+    }
+
+    let mut mids = vec![];
+    for group in [DATA_ANALYZER_GROUP, RELIC_ANALYZER_GROUP] {
+        if let Some(analyzer) = best_analyzer(eligible_modules, group) {
+            if mids.len() < calc.slot_count(SlotType::Mid) {
+                mids.push(analyzer.clone());
+            }
+        }
+    }
+    let mut rigs = vec![];
+    let mut calibration = calc.ship_attr(attr::UPGRADE_CAPACITY);
+    for rig in eligible_modules
+        .iter()
+        .filter(|m| m.slot == Some(SlotType::Rig) && is_exploration_upgrade(m))
+    {
+        let cost = rig
+            .attributes
+            .get(attr::UPGRADE_COST)
+            .copied()
+            .unwrap_or(0.0);
+        if rigs.len() < calc.slot_count(SlotType::Rig) && cost <= calibration {
+            calibration -= cost;
+            rigs.push(rig.clone());
+        }
+    }
+
+    let fits: Vec<FitVariant> = eligible_modules
+        .iter()
+        .filter(|m| m.group_id == PROBE_LAUNCHER_GROUP && m.slot == Some(SlotType::High))
+        .map(|launcher| {
+            let mut fit = base.clone();
+            fit.fit_name = format!("{} Exploration ({})", ship.ship_name, launcher.module_name);
+            fit.modules.push(launcher.clone());
+            fit.modules.extend(mids.iter().cloned());
+            fit.modules.extend(rigs.iter().cloned());
+            fit
+        })
+        .filter(|fit| {
+            let stats = calculate_fit_stats_with(fit, ctx);
+            stats.cpu_used <= stats.cpu_output && stats.powergrid_used <= stats.powergrid_output
+        })
+        .collect();
+    rank_exploration_fits(&fits, ctx, &[])
+        .into_iter()
+        .take(MAX_FITS)
+        .map(|(mut fit, stats)| {
+            fit.rationale = format!(
+                "Probe strength x{:.2}, data analyzer {:.0}/{:.0}, relic analyzer {:.0}/{:.0} \
+                 (strength/coherence), aligns in {:.1}s",
+                stats.probe_strength_multiplier,
+                stats.data_virus_strength,
+                stats.data_virus_coherence,
+                stats.relic_virus_strength,
+                stats.relic_virus_coherence,
+                stats.align_time
+            );
+            fit
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{Charge, FitVariant, Module, Ship, Skill, SlotType};
+    use crate::fitting::{CalcContext, Modifier, ModifierTarget};
+    use crate::test_support::{attrs, core_probe_launcher, data_analyzer};
+
+    fn probe(name: &str, strength: f64, combat: bool) -> Charge {
+        Charge {
+            charge_name: name.to_string(),
+            group_id: 479,
+            attributes: attrs(&[
+                ("baseSensorStrength", strength),
+                ("volume", 0.1),
+                ("probeCanScanShips", if combat { 1.0 } else { 0.0 }),
+            ]),
+            required_skills: vec![(3412, "Astrometrics".to_string(), 1)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_exploration_stats() {
+        let gravity_rig = Module {
+            module_name: "Small Gravity Capacitor Upgrade I".to_string(),
+            slot: Some(SlotType::Rig),
+            effects: vec![Modifier::percent(
+                ModifierTarget::ChargeSkill(3412),
+                "baseSensorStrength",
+                10.0,
+            )],
+            ..Default::default()
+        };
+        let fit = FitVariant {
+            ship: Ship {
+                ship_name: "Heron".to_string(),
+                ..Default::default()
+            },
+            modules: vec![core_probe_launcher(), data_analyzer(), gravity_rig],
+            ..Default::default()
+        };
+        let ctx = CalcContext::from_skills(&[
+            Skill {
+                skill_id: 3412,
+                skill_name: Some("Astrometrics".to_string()),
+                active_level: 4,
+            },
+            Skill {
+                skill_id: 21718,
+                skill_name: Some("Hacking".to_string()),
+                active_level: 3,
+            },
+        ]);
+        let probes = [
+            probe("Core Scanner Probe I", 40.0, false),
+            probe("Combat Scanner Probe I", 30.0, true),
+        ];
+        let stats = calculate_exploration_stats(&fit, &ctx, &probes);
+        let multiplier = 1.2 * 1.1;
+        assert!((stats.probe_strength_multiplier - multiplier).abs() < 1e-9);
+        assert!((stats.core_probe_strength - 40.0 * multiplier).abs() < 1e-9);
+        assert!((stats.combat_probe_strength - 30.0 * multiplier).abs() < 1e-9);
+        assert!((stats.data_virus_coherence - 70.0).abs() < 1e-9);
+        assert!((stats.data_virus_strength - 20.0).abs() < 1e-9);
+        assert_eq!(stats.relic_virus_coherence, 0.0);
+    }
+}
@@ -0,0 +1,1257 @@
+//! Fit calculation engine for EveOnline Helper
+//
+// Applies skill, hull and module modifiers to SDE dogma attributes and derives the
+// stats shown for a fit: fitting resources, damage, tank, capacitor, navigation and targeting.
+//
+// Attributes are keyed by their SDE dogma attribute name (see `attr`). Modifiers are
+// evaluated in a single pass: additive modifiers first, then multiplicative ones
+// (stacking penalized where flagged), then assignments.
+
+use crate::environment::EnvironmentProfile;
+use crate::esi::{FitVariant, HullBonus, Module, ModuleState, Skill, SlotType};
+use crate::ewar::{
+    ewar_module_stats, EwarModuleStats, ECM_GROUP, SENSOR_DAMPENER_GROUP, TARGET_PAINTER_GROUP,
+    WEAPON_DISRUPTOR_GROUP,
+};
+use crate::fleet::{boost_modifiers, FleetBoost};
+use crate::implants::{booster_modifiers, implant_modifiers, Booster, Implant};
+use crate::logistics::{
+    REMOTE_ARMOR_REPAIRER_GROUP, REMOTE_CAPACITOR_GROUP, REMOTE_SHIELD_BOOSTER_GROUP,
+};
+use crate::t3::subsystem_slot_modifiers;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// SDE dogma attribute names used by the calculation engine
+pub mod attr {
+    // Fitting
+    pub const CPU: &str = "cpu";
+    pub const POWER: &str = "power";
+    pub const CPU_OUTPUT: &str = "cpuOutput";
+    pub const POWER_OUTPUT: &str = "powerOutput";
+    pub const UPGRADE_COST: &str = "upgradeCost";
+    pub const UPGRADE_CAPACITY: &str = "upgradeCapacity";
+    pub const HI_SLOTS: &str = "hiSlots";
+    pub const MED_SLOTS: &str = "medSlots";
+    pub const LOW_SLOTS: &str = "lowSlots";
+    pub const RIG_SLOTS: &str = "rigSlots";
+    pub const SUBSYSTEM_SLOTS: &str = "maxSubSystems";
+    pub const TURRET_SLOTS: &str = "turretSlotsLeft";
+    pub const LAUNCHER_SLOTS: &str = "launcherSlotsLeft";
+    pub const MAX_GROUP_FITTED: &str = "maxGroupFitted";
+    pub const FITS_TO_SHIP_TYPE: &str = "fitsToShipType";
+    pub const META_LEVEL: &str = "metaLevelOld";
+    // Charges
+    pub const CAPACITY: &str = "capacity";
+    pub const VOLUME: &str = "volume";
+    pub const CHARGE_SIZE: &str = "chargeSize";
+    pub const CHARGE_GROUPS: [&str; 5] = [
+        "chargeGroup1",
+        "chargeGroup2",
+        "chargeGroup3",
+        "chargeGroup4",
+        "chargeGroup5",
+    ];
+    pub const WEAPON_RANGE_MULTIPLIER: &str = "weaponRangeMultiplier";
+    pub const FALLOFF_MULTIPLIER: &str = "fallofMultiplier";
+    pub const TRACKING_MULTIPLIER: &str = "trackingSpeedMultiplier";
+    pub const EXPLOSION_DELAY: &str = "explosionDelay";
+    // Weapons
+    pub const EM_DAMAGE: &str = "emDamage";
+    pub const THERMAL_DAMAGE: &str = "thermalDamage";
+    pub const KINETIC_DAMAGE: &str = "kineticDamage";
+    pub const EXPLOSIVE_DAMAGE: &str = "explosiveDamage";
+    pub const DAMAGE_MULTIPLIER: &str = "damageMultiplier";
+    pub const RATE_OF_FIRE: &str = "speed";
+    pub const DURATION: &str = "duration";
+    pub const MAX_RANGE: &str = "maxRange";
+    pub const FALLOFF: &str = "falloff";
+    pub const TRACKING_SPEED: &str = "trackingSpeed";
+    // Tank
+    pub const SHIELD_CAPACITY: &str = "shieldCapacity";
+    pub const SHIELD_RECHARGE_RATE: &str = "shieldRechargeRate";
+    pub const ARMOR_HP: &str = "armorHP";
+    pub const HULL_HP: &str = "hp";
+    pub const SHIELD_RESONANCES: [&str; 4] = [
+        "shieldEmDamageResonance",
+        "shieldThermalDamageResonance",
+        "shieldKineticDamageResonance",
+        "shieldExplosiveDamageResonance",
+    ];
+    pub const ARMOR_RESONANCES: [&str; 4] = [
+        "armorEmDamageResonance",
+        "armorThermalDamageResonance",
+        "armorKineticDamageResonance",
+        "armorExplosiveDamageResonance",
+    ];
+    pub const HULL_RESONANCES: [&str; 4] = [
+        "emDamageResonance",
+        "thermalDamageResonance",
+        "kineticDamageResonance",
+        "explosiveDamageResonance",
+    ];
+    pub const SHIELD_BONUS: &str = "shieldBonus";
+    pub const ARMOR_REPAIR: &str = "armorDamageAmount";
+    // Capacitor
+    pub const CAPACITOR_CAPACITY: &str = "capacitorCapacity";
+    pub const CAPACITOR_RECHARGE: &str = "rechargeRate";
+    pub const CAPACITOR_NEED: &str = "capacitorNeed";
+    // Navigation
+    pub const MAX_VELOCITY: &str = "maxVelocity";
+    pub const MASS: &str = "mass";
+    pub const AGILITY: &str = "agility";
+    pub const SPEED_FACTOR: &str = "speedFactor";
+    pub const SPEED_BOOST_FACTOR: &str = "speedBoostFactor";
+    pub const MASS_ADDITION: &str = "massAddition";
+    pub const SIGNATURE_RADIUS: &str = "signatureRadius";
+    pub const SIGNATURE_RADIUS_BONUS: &str = "signatureRadiusBonus";
+    // Heat
+    pub const MODULE_HP: &str = "hp";
+    pub const HEAT_DAMAGE: &str = "heatDamage";
+    pub const HEAT_ABSORPTION: &str = "heatAbsorbtionRateModifier";
+    pub const HEAT_GENERATION_MULTIPLIER: &str = "heatGenerationMultiplier";
+    pub const HEAT_DISSIPATION_HI: &str = "heatDissipationRateHi";
+    pub const HEAT_DISSIPATION_MED: &str = "heatDissipationRateMed";
+    pub const HEAT_DISSIPATION_LOW: &str = "heatDissipationRateLow";
+    pub const OVERLOAD_HARDENING_BONUS: &str = "overloadHardeningBonus";
+    // Targeting
+    pub const MAX_TARGET_RANGE: &str = "maxTargetRange";
+    pub const SCAN_RESOLUTION: &str = "scanResolution";
+    pub const MAX_LOCKED_TARGETS: &str = "maxLockedTargets";
+    pub const SENSOR_STRENGTHS: [&str; 4] = [
+        "scanRadarStrength",
+        "scanLadarStrength",
+        "scanMagnetometricStrength",
+        "scanGravimetricStrength",
+    ];
+    // Mining
+    pub const MINING_AMOUNT: &str = "miningAmount";
+    pub const CRYSTAL_VOLATILITY_CHANCE: &str = "crystalVolatilityChance";
+    pub const SPECIALISATION_GROUP: &str = "specialisationAsteroidGroup";
+    pub const SPECIALISATION_YIELD: &str = "specialisationAsteroidYieldMultiplier";
+    pub const RESIDUE_CHANCE: &str = "miningWasteProbability";
+    pub const RESIDUE_VOLUME_MULTIPLIER: &str = "miningWastedVolumeMultiplier";
+    pub const ORE_HOLD_CAPACITY: &str = "specialOreHoldCapacity";
+    // Exploration
+    pub const SCAN_STRENGTH: &str = "baseSensorStrength";
+    pub const SCAN_STRENGTH_BONUS: &str = "scanStrengthBonus";
+    pub const PROBE_CAN_SCAN_SHIPS: &str = "probeCanScanShips";
+    pub const VIRUS_COHERENCE: &str = "virusCoherence";
+    pub const VIRUS_STRENGTH: &str = "virusStrength";
+    // Tackle and EWAR
+    pub const WARP_SCRAMBLE_STRENGTH: &str = "warpScrambleStrength";
+    pub const ACTIVATION_BLOCKED_STRENGTH: &str = "activationBlockedStrenth";
+    pub const ECM_STRENGTHS: [&str; 4] = [
+        "scanRadarStrengthBonus",
+        "scanLadarStrengthBonus",
+        "scanMagnetometricStrengthBonus",
+        "scanGravimetricStrengthBonus",
+    ];
+    pub const FALLOFF_EFFECTIVENESS: &str = "falloffEffectiveness";
+    pub const MAX_TARGET_RANGE_BONUS: &str = "maxTargetRangeBonus";
+    pub const SCAN_RESOLUTION_BONUS: &str = "scanResolutionBonus";
+    pub const MAX_RANGE_BONUS: &str = "maxRangeBonus";
+    pub const TRACKING_SPEED_BONUS: &str = "trackingSpeedBonus";
+    // Remote assistance
+    pub const POWER_TRANSFER_AMOUNT: &str = "powerTransferAmount";
+    // Drones
+    pub const DRONE_BANDWIDTH: &str = "droneBandwidth";
+    pub const DRONE_BANDWIDTH_USED: &str = "droneBandwidthUsed";
+}
+
+/// SDE groups of turret modules (energy, projectile, hybrid, precursor, vorton)
+pub const TURRET_GROUPS: &[i64] = &[53, 55, 74, 1986, 4060];
+/// SDE groups of missile launchers
+pub const LAUNCHER_GROUPS: &[i64] = &[506, 507, 508, 509, 510, 511, 524, 771, 1245, 1673, 1674];
+/// SDE group of afterburners and microwarpdrives
+pub const PROPULSION_GROUP: i64 = 46;
+/// SDE group of shield boosters
+pub const SHIELD_BOOSTER_GROUP: i64 = 40;
+/// SDE group of armor repairers
+pub const ARMOR_REPAIRER_GROUP: i64 = 62;
+/// SDE groups of mining lasers, strip miners and frequency mining lasers
+pub const MINING_GROUPS: &[i64] = &[54, 464, 483];
+
+/// Overload bonus attributes and the module attribute each one boosts (in percent)
+const OVERLOAD_BONUSES: &[(&str, &str)] = &[
+    ("overloadRofBonus", attr::RATE_OF_FIRE),
+    ("overloadDamageModifier", attr::DAMAGE_MULTIPLIER),
+    ("overloadSpeedFactorBonus", attr::SPEED_FACTOR),
+    ("overloadSelfDurationBonus", attr::DURATION),
+    ("overloadArmorDamageAmount", attr::ARMOR_REPAIR),
+    ("overloadShieldBonus", attr::SHIELD_BONUS),
+    ("overloadRangeBonus", attr::MAX_RANGE),
+];
+
+/// How a modifier combines with the attribute it targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Operation {
+    /// Adds the value to the attribute
+    ModAdd,
+    /// Multiplies the attribute by the value
+    PostMul,
+    /// Changes the attribute by the value in percent (e.g. -30.0 for a hardener)
+    PostPercent,
+    /// Replaces the attribute with the value
+    PostAssign,
+}
+
+/// Which items a modifier applies to
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum ModifierTarget {
+    /// The ship hull
+    Ship,
+    /// Fitted modules in the given SDE group
+    ModuleGroup(i64),
+    /// Fitted modules that require the given skill
+    ModuleSkill(i64),
+    /// Loaded charges that require the given skill
+    ChargeSkill(i64),
+    /// Loaded charges in the given SDE group
+    ChargeGroup(i64),
+    /// Drones that require the given skill
+    DroneSkill(i64),
+}
+
+/// A single dogma modifier from a skill, hull bonus or module effect
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Modifier {
+    pub target: ModifierTarget,
+    /// SDE attribute name the modifier changes
+    pub attribute: String,
+    pub operation: Operation,
+    pub value: f64,
+    /// Whether the modifier is subject to the stacking penalty
+    #[serde(default)]
+    pub stacking_penalized: bool,
+}
+
+impl Modifier {
+    /// Non-penalized percentage modifier (the common case for skills and hull bonuses)
+    pub fn percent(target: ModifierTarget, attribute: &str, value: f64) -> Self {
+        Modifier {
+            target,
+            attribute: attribute.to_string(),
+            operation: Operation::PostPercent,
+            value,
+            stacking_penalized: false,
+        }
+    }
+
+    /// Non-penalized flat modifier added to the base value
+    pub fn add(target: ModifierTarget, attribute: &str, value: f64) -> Self {
+        Modifier {
+            target,
+            attribute: attribute.to_string(),
+            operation: Operation::ModAdd,
+            value,
+            stacking_penalized: false,
+        }
+    }
+
+    /// Same modifier with its value multiplied by `factor` (e.g. a skill level)
+    pub fn scaled(&self, factor: f64) -> Self {
+        let value = match self.operation {
+            Operation::PostMul => 1.0 + (self.value - 1.0) * factor,
+            Operation::PostAssign => self.value,
+            _ => self.value * factor,
+        };
+        Modifier {
+            value,
+            ..self.clone()
+        }
+    }
+}
+
+/// Damage split across the four damage types
+///
+/// Used both for damage amounts and for resist fractions (0.0-1.0).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub struct DamageProfile {
+    pub em: f64,
+    pub thermal: f64,
+    pub kinetic: f64,
+    pub explosive: f64,
+}
+
+impl DamageProfile {
+    pub fn new(em: f64, thermal: f64, kinetic: f64, explosive: f64) -> Self {
+        DamageProfile {
+            em,
+            thermal,
+            kinetic,
+            explosive,
+        }
+    }
+
+    /// Equal split across all damage types
+    pub fn uniform() -> Self {
+        DamageProfile::new(0.25, 0.25, 0.25, 0.25)
+    }
+
+    pub fn total(&self) -> f64 {
+        self.em + self.thermal + self.kinetic + self.explosive
+    }
+
+    pub fn scaled(&self, factor: f64) -> Self {
+        DamageProfile::new(
+            self.em * factor,
+            self.thermal * factor,
+            self.kinetic * factor,
+            self.explosive * factor,
+        )
+    }
+
+    pub fn add(&self, other: &DamageProfile) -> Self {
+        DamageProfile::new(
+            self.em + other.em,
+            self.thermal + other.thermal,
+            self.kinetic + other.kinetic,
+            self.explosive + other.explosive,
+        )
+    }
+
+    /// Profile scaled so its types sum to 1.0 (uniform if empty)
+    pub fn normalized(&self) -> Self {
+        let total = self.total();
+        if total <= 0.0 {
+            DamageProfile::uniform()
+        } else {
+            self.scaled(1.0 / total)
+        }
+    }
+
+    /// Damage left after the given resist fractions
+    pub fn applied_against(&self, resists: &DamageProfile) -> f64 {
+        self.em * (1.0 - resists.em)
+            + self.thermal * (1.0 - resists.thermal)
+            + self.kinetic * (1.0 - resists.kinetic)
+            + self.explosive * (1.0 - resists.explosive)
+    }
+
+    fn from_attributes(attributes: &HashMap<String, f64>) -> Self {
+        let get = |name: &str| attributes.get(name).copied().unwrap_or(0.0);
+        DamageProfile::new(
+            get(attr::EM_DAMAGE),
+            get(attr::THERMAL_DAMAGE),
+            get(attr::KINETIC_DAMAGE),
+            get(attr::EXPLOSIVE_DAMAGE),
+        )
+    }
+
+    fn resists_from(attributes: &HashMap<String, f64>, resonances: &[&str; 4]) -> Self {
+        let resist = |name: &str| 1.0 - attributes.get(name).copied().unwrap_or(1.0);
+        DamageProfile::new(
+            resist(resonances[0]),
+            resist(resonances[1]),
+            resist(resonances[2]),
+            resist(resonances[3]),
+        )
+    }
+}
+
+/// What the fit is expected to shoot at
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TargetProfile {
+    /// Resist fractions (0.0-1.0) of the target's active tank layer
+    pub resists: DamageProfile,
+    /// Expected engagement range in metres
+    pub distance: f64,
+}
+
+/// Kind of weapon a fitted module is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WeaponKind {
+    Turret,
+    Launcher,
+}
+
+/// Damage and range of one fitted weapon with its loaded charge
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WeaponStats {
+    /// Index of the weapon in `FitVariant::modules`
+    pub module_index: usize,
+    pub module_name: String,
+    pub charge_name: String,
+    pub kind: WeaponKind,
+    /// Damage dealt per shot, by damage type
+    pub volley: DamageProfile,
+    /// Seconds between shots
+    pub cycle_time: f64,
+    /// Optimal range (turrets) or flight range (missiles) in metres
+    pub optimal_range: f64,
+    /// Falloff in metres (turrets only)
+    pub falloff: f64,
+    pub tracking: f64,
+}
+
+impl WeaponStats {
+    pub fn dps(&self) -> f64 {
+        if self.cycle_time > 0.0 {
+            self.volley.total() / self.cycle_time
+        } else {
+            0.0
+        }
+    }
+
+    /// DPS after the target's resists and the damage falloff at the target's range
+    pub fn applied_dps(&self, target: &TargetProfile) -> f64 {
+        if self.cycle_time <= 0.0 {
+            return 0.0;
+        }
+        let range_factor = match self.kind {
+            WeaponKind::Turret => {
+                let past_optimal = (target.distance - self.optimal_range).max(0.0);
+                if past_optimal == 0.0 {
+                    1.0
+                } else if self.falloff <= 0.0 {
+                    0.0
+                } else {
+                    0.5_f64.powf((past_optimal / self.falloff).powi(2))
+                }
+            }
+            WeaponKind::Launcher => {
+                if target.distance <= self.optimal_range {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        };
+        self.volley.applied_against(&target.resists) / self.cycle_time * range_factor
+    }
+}
+
+/// Everything besides the fit itself that affects calculated stats
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CalcContext {
+    /// The character's skills
+    pub skills: Vec<Skill>,
+    /// Implants and hardwirings in the active clone
+    #[serde(default)]
+    pub implants: Vec<Implant>,
+    /// Active combat boosters
+    #[serde(default)]
+    pub boosters: Vec<Booster>,
+    /// Apply every booster side effect (worst case) instead of ignoring them
+    #[serde(default)]
+    pub booster_side_effects: bool,
+    /// Command burst effects from a fleet booster
+    #[serde(default)]
+    pub fleet_boosts: Vec<FleetBoost>,
+    /// System effect (wormhole, abyssal weather, incursion) the fit operates in
+    #[serde(default)]
+    pub environment: Option<EnvironmentProfile>,
+}
+
+impl CalcContext {
+    /// Context with only the character's skills
+    pub fn from_skills(skills: &[Skill]) -> Self {
+        CalcContext {
+            skills: skills.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    pub fn skill_level(&self, skill_id: i64) -> i32 {
+        self.skills
+            .iter()
+            .find(|s| s.skill_id == skill_id)
+            .map(|s| s.active_level)
+            .unwrap_or(0)
+    }
+}
+
+/// Calculated stats for a fit
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FitStats {
+    // Fitting
+    pub cpu_used: f64,
+    pub cpu_output: f64,
+    pub powergrid_used: f64,
+    pub powergrid_output: f64,
+    pub calibration_used: f64,
+    pub calibration_output: f64,
+    // Offense
+    pub weapons: Vec<WeaponStats>,
+    pub dps: f64,
+    pub volley: f64,
+    /// Outgoing damage split per damage type (per second)
+    pub damage_profile: DamageProfile,
+    // Defense
+    pub shield_hp: f64,
+    pub armor_hp: f64,
+    pub hull_hp: f64,
+    pub shield_resists: DamageProfile,
+    pub armor_resists: DamageProfile,
+    pub hull_resists: DamageProfile,
+    /// Effective HP against uniform damage
+    pub ehp: f64,
+    /// Peak passive shield regeneration in HP/s
+    pub passive_shield_regen: f64,
+    pub shield_boost_rate: f64,
+    pub armor_repair_rate: f64,
+    // Capacitor
+    pub capacitor_capacity: f64,
+    /// Peak capacitor recharge in GJ/s
+    pub capacitor_recharge: f64,
+    /// Capacitor used by running modules in GJ/s
+    pub capacitor_usage: f64,
+    pub capacitor_stable: bool,
+    // Navigation
+    pub max_velocity: f64,
+    /// Seconds to reach warp-align speed
+    pub align_time: f64,
+    pub signature_radius: f64,
+    // Targeting
+    pub max_target_range: f64,
+    pub scan_resolution: f64,
+    pub max_locked_targets: f64,
+    // Mining
+    /// Ore mined by running mining modules in m3/s
+    pub mining_yield: f64,
+    // Cargo
+    pub cargo_capacity: f64,
+    // Tackle and EWAR
+    /// Running webs, scrams, points, ECM, damps, disruptors and painters
+    pub ewar: Vec<EwarModuleStats>,
+}
+
+impl FitStats {
+    /// Effective HP against the given incoming damage profile
+    pub fn ehp_against(&self, incoming: &DamageProfile) -> f64 {
+        let incoming = incoming.normalized();
+        let layer = |hp: f64, resists: &DamageProfile| {
+            let taken = incoming.applied_against(resists);
+            if taken > 0.0 {
+                hp / taken
+            } else {
+                hp
+            }
+        };
+        layer(self.shield_hp, &self.shield_resists)
+            + layer(self.armor_hp, &self.armor_resists)
+            + layer(self.hull_hp, &self.hull_resists)
+    }
+}
+
+/// Change in a fit's key stats between two versions of it (after minus before)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StatDelta {
+    pub dps: f64,
+    pub volley: f64,
+    pub ehp: f64,
+    /// Capacitor recharge minus usage, in GJ/s
+    pub capacitor_balance: f64,
+    pub max_velocity: f64,
+    pub align_time: f64,
+    pub max_target_range: f64,
+    pub scan_resolution: f64,
+}
+
+impl StatDelta {
+    pub fn between(before: &FitStats, after: &FitStats) -> Self {
+        let balance = |s: &FitStats| s.capacitor_recharge - s.capacitor_usage;
+        StatDelta {
+            dps: after.dps - before.dps,
+            volley: after.volley - before.volley,
+            ehp: after.ehp - before.ehp,
+            capacitor_balance: balance(after) - balance(before),
+            max_velocity: after.max_velocity - before.max_velocity,
+            align_time: after.align_time - before.align_time,
+            max_target_range: after.max_target_range - before.max_target_range,
+            scan_resolution: after.scan_resolution - before.scan_resolution,
+        }
+    }
+}
+
+/// Attributes of the hull, modules and charges after all modifiers were applied
+#[derive(Debug, Clone, Default)]
+pub struct CalculatedFit {
+    pub ship: HashMap<String, f64>,
+    /// One entry per module in `FitVariant::modules`
+    pub modules: Vec<HashMap<String, f64>>,
+    /// Attributes of the loaded charge, per module
+    pub charges: Vec<Option<HashMap<String, f64>>>,
+    /// One entry per drone type in `FitVariant::drones`
+    pub drones: Vec<HashMap<String, f64>>,
+}
+
+impl CalculatedFit {
+    pub fn ship_attr(&self, name: &str) -> f64 {
+        self.ship.get(name).copied().unwrap_or(0.0)
+    }
+
+    pub fn module_attr(&self, index: usize, name: &str) -> f64 {
+        self.modules
+            .get(index)
+            .and_then(|attrs| attrs.get(name))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub fn drone_attr(&self, index: usize, name: &str) -> f64 {
+        self.drones
+            .get(index)
+            .and_then(|attrs| attrs.get(name))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Number of slots the hull provides for a slot type
+    pub fn slot_count(&self, slot: SlotType) -> usize {
+        let name = match slot {
+            SlotType::High => attr::HI_SLOTS,
+            SlotType::Mid => attr::MED_SLOTS,
+            SlotType::Low => attr::LOW_SLOTS,
+            SlotType::Rig => attr::RIG_SLOTS,
+            SlotType::Subsystem => attr::SUBSYSTEM_SLOTS,
+        };
+        self.ship_attr(name).max(0.0) as usize
+    }
+}
+
+/// Stacking penalty factor for the n-th strongest penalized modifier (0-based)
+pub fn stacking_penalty(index: usize) -> f64 {
+    (-(index as f64 / 2.67).powi(2)).exp()
+}
+
+/// Skills with built-in bonuses in `skill_modifiers`
+pub const BONUS_SKILLS: &[i64] = &[
+    3426, 3413, 3418, 3417, 3419, 3416, 3394, 3392, 3393, 3422, 16069, 3423, 3449, 3453, 3452,
+    3428, 3431, 19760, 19761, 19922, 19921, 3386, 3410, 3438, 3412, 25739, 21718, 13278, 3300,
+    3310, 3315, 3311, 3317, 3312, 3301, 3302, 3303, 3304, 3305, 3306, 3307, 3308, 3309, 3318, 3319,
+    20315, 3320, 3321, 3324, 3325, 3326, 25719,
+];
+
+/// Built-in skill bonuses, per skill level
+///
+/// Only skills that change stats shown by the app are listed; everything else
+/// only matters for requirements.
+pub fn skill_modifiers(skill_id: i64) -> Vec<Modifier> {
+    use ModifierTarget::*;
+    let p = Modifier::percent;
+    match skill_id {
+        // Engineering
+        3426 => vec![p(Ship, attr::CPU_OUTPUT, 5.0)], // CPU Management
+        3413 => vec![p(Ship, attr::POWER_OUTPUT, 5.0)], // Power Grid Management
+        3418 => vec![p(Ship, attr::CAPACITOR_CAPACITY, 5.0)], // Capacitor Management
+        3417 => vec![p(Ship, attr::CAPACITOR_RECHARGE, -5.0)], // Capacitor Systems Operation
+        // Shields and armor
+        3419 => vec![p(Ship, attr::SHIELD_CAPACITY, 5.0)], // Shield Management
+        3416 => vec![p(Ship, attr::SHIELD_RECHARGE_RATE, -5.0)], // Shield Operation
+        3394 => vec![p(Ship, attr::ARMOR_HP, 5.0)],        // Hull Upgrades
+        3392 => vec![p(Ship, attr::HULL_HP, 5.0)],         // Mechanics
+        3393 => vec![p(ModuleSkill(3393), attr::DURATION, -5.0)], // Repair Systems
+        // Remote assistance
+        3422 => vec![p(
+            ModuleGroup(REMOTE_SHIELD_BOOSTER_GROUP),
+            attr::CAPACITOR_NEED,
+            -5.0,
+        )], // Shield Emission Systems
+        16069 => vec![p(
+            ModuleGroup(REMOTE_ARMOR_REPAIRER_GROUP),
+            attr::DURATION,
+            -5.0,
+        )], // Remote Armor Repair Systems
+        3423 => vec![p(
+            ModuleGroup(REMOTE_CAPACITOR_GROUP),
+            attr::CAPACITOR_NEED,
+            -5.0,
+        )], // Capacitor Emission Systems
+        // Navigation
+        3449 => vec![p(Ship, attr::MAX_VELOCITY, 5.0)], // Navigation
+        3453 => vec![p(Ship, attr::AGILITY, -5.0)],     // Evasive Maneuvering
+        3452 => vec![p(ModuleGroup(PROPULSION_GROUP), attr::SPEED_FACTOR, 5.0)], // Acceleration Control
+        // Targeting
+        3428 => vec![p(Ship, attr::MAX_TARGET_RANGE, 5.0)], // Long Range Targeting
+        3431 => vec![p(Ship, attr::SCAN_RESOLUTION, 5.0)],  // Signature Analysis
+        // Electronic warfare
+        19760 => attr::ECM_STRENGTHS
+            .iter()
+            .map(|name| p(ModuleGroup(ECM_GROUP), name, 5.0))
+            .collect(), // Signal Dispersion
+        19761 => vec![
+            p(
+                ModuleGroup(SENSOR_DAMPENER_GROUP),
+                attr::MAX_TARGET_RANGE_BONUS,
+                5.0,
+            ),
+            p(
+                ModuleGroup(SENSOR_DAMPENER_GROUP),
+                attr::SCAN_RESOLUTION_BONUS,
+                5.0,
+            ),
+        ], // Signal Suppression
+        19922 => vec![
+            p(
+                ModuleGroup(WEAPON_DISRUPTOR_GROUP),
+                attr::MAX_RANGE_BONUS,
+                5.0,
+            ),
+            p(
+                ModuleGroup(WEAPON_DISRUPTOR_GROUP),
+                attr::TRACKING_SPEED_BONUS,
+                5.0,
+            ),
+        ], // Weapon Destabilization
+        19921 => vec![p(
+            ModuleGroup(TARGET_PAINTER_GROUP),
+            attr::SIGNATURE_RADIUS_BONUS,
+            5.0,
+        )], // Signature Focusing
+        // Mining
+        3386 => vec![p(ModuleSkill(3386), attr::MINING_AMOUNT, 5.0)], // Mining
+        3410 => vec![p(ModuleSkill(3386), attr::MINING_AMOUNT, 5.0)], // Astrogeology
+        3438 => vec![p(DroneSkill(3438), attr::MINING_AMOUNT, 5.0)],  // Mining Drone Operation
+        // Exploration
+        3412 => vec![p(ChargeSkill(3412), attr::SCAN_STRENGTH, 5.0)], // Astrometrics
+        25739 => vec![p(ChargeSkill(3412), attr::SCAN_STRENGTH, 5.0)], // Astrometric Rangefinding
+        21718 => vec![Modifier::add(
+            ModuleSkill(21718),
+            attr::VIRUS_COHERENCE,
+            10.0,
+        )], // Hacking
+        13278 => vec![Modifier::add(
+            ModuleSkill(13278),
+            attr::VIRUS_COHERENCE,
+            10.0,
+        )], // Archaeology
+        // Gunnery
+        3300 => vec![p(ModuleSkill(3300), attr::RATE_OF_FIRE, -2.0)], // Gunnery
+        3310 => vec![p(ModuleSkill(3300), attr::RATE_OF_FIRE, -4.0)], // Rapid Firing
+        3315 => vec![p(ModuleSkill(3300), attr::DAMAGE_MULTIPLIER, 3.0)], // Surgical Strike
+        3311 => vec![p(ModuleSkill(3300), attr::MAX_RANGE, 5.0)],     // Sharpshooter
+        3317 => vec![p(ModuleSkill(3300), attr::FALLOFF, 5.0)],       // Trajectory Analysis
+        3312 => vec![p(ModuleSkill(3300), attr::TRACKING_SPEED, 5.0)], // Motion Prediction
+        3301..=3309 => vec![p(ModuleSkill(skill_id), attr::DAMAGE_MULTIPLIER, 5.0)], // Turret sizes
+        3318 => vec![
+            p(ModuleSkill(3300), attr::CPU, -5.0),
+            p(ModuleSkill(3319), attr::CPU, -5.0),
+        ], // Weapon Upgrades
+        // Missiles
+        3319 => vec![p(ModuleSkill(3319), attr::RATE_OF_FIRE, -2.0)], // Missile Launcher Operation
+        20315 => charge_damage_modifiers(3319, 2.0),                  // Warhead Upgrades
+        3320 | 3321 | 3324 | 3325 | 3326 | 25719 => charge_damage_modifiers(skill_id, 5.0), // Missile sizes
+        _ => vec![],
+    }
+}
+
+fn charge_damage_modifiers(skill_id: i64, percent: f64) -> Vec<Modifier> {
+    [
+        attr::EM_DAMAGE,
+        attr::THERMAL_DAMAGE,
+        attr::KINETIC_DAMAGE,
+        attr::EXPLOSIVE_DAMAGE,
+    ]
+    .iter()
+    .map(|name| Modifier::percent(ModifierTarget::ChargeSkill(skill_id), name, percent))
+    .collect()
+}
+
+/// Kind of item a modifier is matched against
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ItemKind {
+    Ship,
+    Module,
+    Charge,
+    Drone,
+}
+
+fn targets(
+    modifier: &Modifier,
+    kind: ItemKind,
+    group_id: i64,
+    required_skills: &[(i64, String, i32)],
+) -> bool {
+    let requires = |skill_id: i64| required_skills.iter().any(|(id, _, _)| *id == skill_id);
+    match (&modifier.target, kind) {
+        (ModifierTarget::Ship, ItemKind::Ship) => true,
+        (ModifierTarget::ModuleGroup(group), ItemKind::Module) => *group == group_id,
+        (ModifierTarget::ModuleSkill(skill), ItemKind::Module) => requires(*skill),
+        (ModifierTarget::ChargeSkill(skill), ItemKind::Charge) => requires(*skill),
+        (ModifierTarget::ChargeGroup(group), ItemKind::Charge) => *group == group_id,
+        (ModifierTarget::DroneSkill(skill), ItemKind::Drone) => requires(*skill),
+        _ => false,
+    }
+}
+
+/// Default value of an attribute missing from an item's base attributes
+fn default_attribute(name: &str) -> f64 {
+    if name.ends_with("Resonance") || name.ends_with("Multiplier") {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Apply modifiers to a single base value
+pub fn apply_modifiers(base: f64, modifiers: &[&Modifier]) -> f64 {
+    let mut value = base;
+    for modifier in modifiers {
+        if modifier.operation == Operation::ModAdd {
+            value += modifier.value;
+        }
+    }
+    let mut bonuses = vec![];
+    let mut penalties = vec![];
+    for modifier in modifiers {
+        let factor = match modifier.operation {
+            Operation::PostMul => modifier.value,
+            Operation::PostPercent => 1.0 + modifier.value / 100.0,
+            _ => continue,
+        };
+        if !modifier.stacking_penalized {
+            value *= factor;
+        } else if factor >= 1.0 {
+            bonuses.push(factor);
+        } else {
+            penalties.push(factor);
+        }
+    }
+    for mut chain in [bonuses, penalties] {
+        chain.sort_by(|a, b| (b - 1.0).abs().total_cmp(&(a - 1.0).abs()));
+        for (index, factor) in chain.iter().enumerate() {
+            value *= 1.0 + (factor - 1.0) * stacking_penalty(index);
+        }
+    }
+    if let Some(assign) = modifiers
+        .iter()
+        .rev()
+        .find(|m| m.operation == Operation::PostAssign)
+    {
+        value = assign.value;
+    }
+    value
+}
+
+fn modified_attributes(
+    base: &HashMap<String, f64>,
+    kind: ItemKind,
+    group_id: i64,
+    required_skills: &[(i64, String, i32)],
+    modifiers: &[Modifier],
+) -> HashMap<String, f64> {
+    let mut by_attribute: HashMap<&str, Vec<&Modifier>> = HashMap::new();
+    for modifier in modifiers {
+        if targets(modifier, kind, group_id, required_skills) {
+            by_attribute
+                .entry(modifier.attribute.as_str())
+                .or_default()
+                .push(modifier);
+        }
+    }
+    let mut result = base.clone();
+    for (name, applicable) in by_attribute {
+        let base_value = base
+            .get(name)
+            .copied()
+            .unwrap_or_else(|| default_attribute(name));
+        result.insert(name.to_string(), apply_modifiers(base_value, &applicable));
+    }
+    result
+}
+
+/// Collect every modifier acting on the fit from skills, implants, boosters, fleet boosts,
+/// the environment, hull bonuses, tactical modes, subsystems and modules
+fn collect_modifiers(fit: &FitVariant, ctx: &CalcContext) -> Vec<Modifier> {
+    let mut modifiers = vec![];
+    for skill in &ctx.skills {
+        for modifier in skill_modifiers(skill.skill_id) {
+            modifiers.push(modifier.scaled(skill.active_level as f64));
+        }
+    }
+    let implants: Vec<Implant> = ctx.implants.iter().chain(&fit.implants).cloned().collect();
+    modifiers.extend(implant_modifiers(&implants));
+    modifiers.extend(booster_modifiers(&ctx.boosters, ctx.booster_side_effects));
+    modifiers.extend(boost_modifiers(&ctx.fleet_boosts));
+    if let Some(environment) = &ctx.environment {
+        modifiers.extend(environment.effects.iter().cloned());
+    }
+    let skill_scaled = |bonus: &HullBonus| {
+        let factor = bonus
+            .skill_id
+            .map(|id| ctx.skill_level(id) as f64)
+            .unwrap_or(1.0);
+        bonus.modifier.scaled(factor)
+    };
+    modifiers.extend(fit.ship.bonuses.iter().map(skill_scaled));
+    if let Some(mode) = &fit.mode {
+        modifiers.extend(mode.effects.iter().cloned());
+    }
+    for module in &fit.modules {
+        if !applies_effects(module) {
+            continue;
+        }
+        modifiers.extend(subsystem_slot_modifiers(module));
+        modifiers.extend(module.bonuses.iter().map(skill_scaled));
+        if module_state(module) == ModuleState::Overheated {
+            // Overheated hardeners get their overload bonus on top of the base effect
+            let hardening = module
+                .attributes
+                .get(attr::OVERLOAD_HARDENING_BONUS)
+                .copied()
+                .unwrap_or(0.0);
+            modifiers.extend(module.effects.iter().map(|m| match m.operation {
+                Operation::PostPercent => m.scaled(1.0 + hardening / 100.0),
+                _ => m.clone(),
+            }));
+        } else {
+            modifiers.extend(module.effects.iter().cloned());
+        }
+    }
+    modifiers
+}
+
+/// Apply all modifiers and return the final attributes of every item in the fit
+pub fn calculate_attributes(fit: &FitVariant, user_skills: &[Skill]) -> CalculatedFit {
+    calculate_attributes_with(fit, &CalcContext::from_skills(user_skills))
+}
+
+/// Same as `calculate_attributes`, with implants, boosters and other context
+pub fn calculate_attributes_with(fit: &FitVariant, ctx: &CalcContext) -> CalculatedFit {
+    let modifiers = collect_modifiers(fit, ctx);
+    let ship = modified_attributes(
+        &fit.ship.attributes,
+        ItemKind::Ship,
+        fit.ship.group_id,
+        &fit.ship.required_skills,
+        &modifiers,
+    );
+    let modules = fit
+        .modules
+        .iter()
+        .map(|module| {
+            let mut attributes = modified_attributes(
+                &module.attributes,
+                ItemKind::Module,
+                module.group_id,
+                &module.required_skills,
+                &modifiers,
+            );
+            if module_state(module) == ModuleState::Overheated {
+                for (bonus, target) in OVERLOAD_BONUSES {
+                    if let (Some(percent), Some(value)) =
+                        (module.attributes.get(*bonus), attributes.get_mut(*target))
+                    {
+                        *value *= 1.0 + percent / 100.0;
+                    }
+                }
+            }
+            attributes
+        })
+        .collect();
+    let charges = fit
+        .modules
+        .iter()
+        .map(|module| {
+            module.charge.as_ref().map(|charge| {
+                modified_attributes(
+                    &charge.attributes,
+                    ItemKind::Charge,
+                    charge.group_id,
+                    &charge.required_skills,
+                    &modifiers,
+                )
+            })
+        })
+        .collect();
+    let drones = fit
+        .drones
+        .iter()
+        .map(|drone| {
+            modified_attributes(
+                &drone.attributes,
+                ItemKind::Drone,
+                drone.group_id,
+                &drone.required_skills,
+                &modifiers,
+            )
+        })
+        .collect();
+    CalculatedFit {
+        ship,
+        modules,
+        charges,
+        drones,
+    }
+}
+
+/// Whether the module cycles when active (weapons, repairers, prop mods, hardeners)
+pub fn is_activatable(module: &Module) -> bool {
+    [attr::RATE_OF_FIRE, attr::DURATION]
+        .iter()
+        .any(|name| module.attributes.get(*name).copied().unwrap_or(0.0) > 0.0)
+}
+
+/// State the engine uses for a module; passive modules cannot go past online
+/// and rigs and subsystems cannot be offlined
+pub fn module_state(module: &Module) -> ModuleState {
+    let fixed = matches!(module.slot, Some(SlotType::Rig | SlotType::Subsystem));
+    if fixed || (!is_activatable(module) && module.state > ModuleState::Online) {
+        ModuleState::Online
+    } else {
+        module.state
+    }
+}
+
+/// Whether the module is cycling (active or overheated)
+pub fn is_running(module: &Module) -> bool {
+    module_state(module) >= ModuleState::Active
+}
+
+/// Whether the module's effects apply: passive modules when online, active ones when running
+fn applies_effects(module: &Module) -> bool {
+    if is_activatable(module) {
+        is_running(module)
+    } else {
+        module_state(module) >= ModuleState::Online
+    }
+}
+
+pub fn is_turret(module: &Module) -> bool {
+    TURRET_GROUPS.contains(&module.group_id)
+}
+
+pub fn is_launcher(module: &Module) -> bool {
+    LAUNCHER_GROUPS.contains(&module.group_id)
+}
+
+/// Cycle time of an activatable module or drone in seconds (weapons use rate of fire)
+pub fn cycle_time(attributes: &HashMap<String, f64>) -> f64 {
+    let get = |name: &str| attributes.get(name).copied().unwrap_or(0.0);
+    let ms = if get(attr::RATE_OF_FIRE) > 0.0 {
+        get(attr::RATE_OF_FIRE)
+    } else {
+        get(attr::DURATION)
+    };
+    ms / 1000.0
+}
+
+fn weapon_stats(
+    index: usize,
+    module: &Module,
+    attributes: &HashMap<String, f64>,
+    charge: Option<&HashMap<String, f64>>,
+) -> Option<WeaponStats> {
+    let kind = if is_turret(module) {
+        WeaponKind::Turret
+    } else if is_launcher(module) {
+        WeaponKind::Launcher
+    } else {
+        return None;
+    };
+    // A weapon without a charge has no damage profile
+    let charge_attrs = charge?;
+    let get = |attrs: &HashMap<String, f64>, name: &str| {
+        attrs
+            .get(name)
+            .copied()
+            .unwrap_or_else(|| default_attribute(name))
+    };
+    let base_damage = DamageProfile::from_attributes(charge_attrs);
+    let (volley, optimal_range, falloff, tracking) = match kind {
+        WeaponKind::Turret => (
+            base_damage.scaled(get(attributes, attr::DAMAGE_MULTIPLIER)),
+            get(attributes, attr::MAX_RANGE) * get(charge_attrs, attr::WEAPON_RANGE_MULTIPLIER),
+            get(attributes, attr::FALLOFF) * get(charge_attrs, attr::FALLOFF_MULTIPLIER),
+            get(attributes, attr::TRACKING_SPEED) * get(charge_attrs, attr::TRACKING_MULTIPLIER),
+        ),
+        WeaponKind::Launcher => (
+            base_damage,
+            get(charge_attrs, attr::MAX_VELOCITY) * get(charge_attrs, attr::EXPLOSION_DELAY)
+                / 1000.0,
+            0.0,
+            0.0,
+        ),
+    };
+    Some(WeaponStats {
+        module_index: index,
+        module_name: module.module_name.clone(),
+        charge_name: module
+            .charge
+            .as_ref()
+            .map(|c| c.charge_name.clone())
+            .unwrap_or_default(),
+        kind,
+        volley,
+        cycle_time: cycle_time(attributes),
+        optimal_range,
+        falloff,
+        tracking,
+    })
+}
+
+/// Calculate the stats of a fit for a character's skills
+///
+/// # Arguments
+/// * `fit` - The fit variant (hull, modules and loaded charges)
+/// * `user_skills` - The character's skills
+///
+/// # Returns
+/// - FitStats with fitting, damage, tank, capacitor, navigation and targeting numbers
+///
+/// # Notes
+/// - Offline modules use no CPU or powergrid; only running modules deal damage, use
+///   capacitor or repair, and only online passive / running active modules apply effects
+pub fn calculate_fit_stats(fit: &FitVariant, user_skills: &[Skill]) -> FitStats {
+    calculate_fit_stats_with(fit, &CalcContext::from_skills(user_skills))
+}
+
+/// Same as `calculate_fit_stats`, with implants, boosters and other context
+pub fn calculate_fit_stats_with(fit: &FitVariant, ctx: &CalcContext) -> FitStats {
+    let calc = calculate_attributes_with(fit, ctx);
+    let ship = |name: &str| calc.ship_attr(name);
+    let mut stats = FitStats {
+        cpu_output: ship(attr::CPU_OUTPUT),
+        powergrid_output: ship(attr::POWER_OUTPUT),
+        calibration_output: ship(attr::UPGRADE_CAPACITY),
+        shield_hp: ship(attr::SHIELD_CAPACITY),
+        armor_hp: ship(attr::ARMOR_HP),
+        hull_hp: ship(attr::HULL_HP),
+        shield_resists: DamageProfile::resists_from(&calc.ship, &attr::SHIELD_RESONANCES),
+        armor_resists: DamageProfile::resists_from(&calc.ship, &attr::ARMOR_RESONANCES),
+        hull_resists: DamageProfile::resists_from(&calc.ship, &attr::HULL_RESONANCES),
+        capacitor_capacity: ship(attr::CAPACITOR_CAPACITY),
+        max_target_range: ship(attr::MAX_TARGET_RANGE),
+        scan_resolution: ship(attr::SCAN_RESOLUTION),
+        max_locked_targets: ship(attr::MAX_LOCKED_TARGETS),
+        cargo_capacity: ship(attr::CAPACITY),
+        ..Default::default()
+    };
+
+    let mut mass = ship(attr::MASS);
+    let mut velocity = ship(attr::MAX_VELOCITY);
+    let mut signature = ship(attr::SIGNATURE_RADIUS);
+    let mut best_prop: Option<usize> = None;
+    for (index, module) in fit.modules.iter().enumerate() {
+        let module_attr = |name: &str| calc.module_attr(index, name);
+        if module.slot == Some(SlotType::Rig) {
+            stats.calibration_used += module_attr(attr::UPGRADE_COST);
+        }
+        if module_state(module) == ModuleState::Offline {
+            continue;
+        }
+        stats.cpu_used += module_attr(attr::CPU);
+        stats.powergrid_used += module_attr(attr::POWER);
+        if !is_running(module) {
+            continue;
+        }
+        if let Some(weapon) = weapon_stats(
+            index,
+            module,
+            &calc.modules[index],
+            calc.charges[index].as_ref(),
+        ) {
+            stats.volley += weapon.volley.total();
+            if weapon.cycle_time > 0.0 {
+                stats.damage_profile = stats
+                    .damage_profile
+                    .add(&weapon.volley.scaled(1.0 / weapon.cycle_time));
+            }
+            stats.weapons.push(weapon);
+        }
+        if let Some(ewar) = ewar_module_stats(index, module, &calc.modules[index]) {
+            stats.ewar.push(ewar);
+        }
+        let cycle = cycle_time(&calc.modules[index]);
+        if cycle > 0.0 {
+            stats.capacitor_usage += module_attr(attr::CAPACITOR_NEED) / cycle;
+            if MINING_GROUPS.contains(&module.group_id) {
+                stats.mining_yield += module_attr(attr::MINING_AMOUNT) / cycle;
+            }
+            match module.group_id {
+                SHIELD_BOOSTER_GROUP => {
+                    stats.shield_boost_rate += module_attr(attr::SHIELD_BONUS) / cycle
+                }
+                ARMOR_REPAIRER_GROUP => {
+                    stats.armor_repair_rate += module_attr(attr::ARMOR_REPAIR) / cycle
+                }
+                _ => {}
+            }
+        }
+        if module.group_id == PROPULSION_GROUP {
+            let better = best_prop
+                .map(|best| {
+                    module_attr(attr::SPEED_FACTOR) > calc.module_attr(best, attr::SPEED_FACTOR)
+                })
+                .unwrap_or(true);
+            if better {
+                best_prop = Some(index);
+            }
+        }
+    }
+    stats.dps = stats.damage_profile.total();
+
+    // Only one propulsion module can run at a time; use the strongest
+    if let Some(prop) = best_prop {
+        mass += calc.module_attr(prop, attr::MASS_ADDITION);
+        if mass > 0.0 {
+            velocity *= 1.0
+                + calc.module_attr(prop, attr::SPEED_FACTOR) / 100.0
+                    * calc.module_attr(prop, attr::SPEED_BOOST_FACTOR)
+                    / mass;
+        }
+        signature *= 1.0 + calc.module_attr(prop, attr::SIGNATURE_RADIUS_BONUS) / 100.0;
+    }
+    stats.max_velocity = velocity;
+    stats.signature_radius = signature;
+    stats.align_time = std::f64::consts::LN_2 * ship(attr::AGILITY) * mass / 1_000_000.0;
+
+    let shield_recharge = ship(attr::SHIELD_RECHARGE_RATE) / 1000.0;
+    if shield_recharge > 0.0 {
+        stats.passive_shield_regen = 2.5 * stats.shield_hp / shield_recharge;
+    }
+    let cap_recharge = ship(attr::CAPACITOR_RECHARGE) / 1000.0;
+    if cap_recharge > 0.0 {
+        stats.capacitor_recharge = 2.5 * stats.capacitor_capacity / cap_recharge;
+    }
+    stats.capacitor_stable = stats.capacitor_usage <= stats.capacitor_recharge;
+    stats.ehp = stats.ehp_against(&DamageProfile::uniform());
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::ModuleState;
+    use crate::test_support::{blaster_fit, em_hardener, hybrid_charge};
+
+    #[test]
+    fn test_module_states() {
+        let mut fit = blaster_fit();
+        fit.ship.attributes.insert("medSlots".to_string(), 4.0);
+        fit.modules = vec![em_hardener()];
+
+        fit.modules[0].state = ModuleState::Offline;
+        let stats = calculate_fit_stats(&fit, &[]);
+        assert_eq!(stats.cpu_used, 0.0);
+        assert_eq!(stats.shield_resists.em, 0.0);
+
+        fit.modules[0].state = ModuleState::Online;
+        let stats = calculate_fit_stats(&fit, &[]);
+        assert_eq!(stats.cpu_used, 44.0);
+        assert_eq!(stats.shield_resists.em, 0.0);
+
+        fit.modules[0].state = ModuleState::Active;
+        let stats = calculate_fit_stats(&fit, &[]);
+        assert!((stats.shield_resists.em - 0.30).abs() < 1e-9);
+
+        fit.modules[0].state = ModuleState::Overheated;
+        let stats = calculate_fit_stats(&fit, &[]);
+        assert!((stats.shield_resists.em - 0.36).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overheated_weapon_damage() {
+        let mut fit = blaster_fit();
+        fit.modules[0].charge = Some(hybrid_charge("Antimatter Charge L", 48.0, 0.5));
+        fit.modules[0]
+            .attributes
+            .insert("overloadDamageModifier".to_string(), 15.0);
+        let active = calculate_fit_stats(&fit, &[]).dps;
+        fit.modules[0].state = ModuleState::Overheated;
+        let overheated = calculate_fit_stats(&fit, &[]).dps;
+        assert!((overheated / active - 1.15).abs() < 1e-9);
+    }
+}
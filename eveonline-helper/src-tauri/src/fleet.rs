@@ -0,0 +1,349 @@
+//! Fleet command bursts for EveOnline Helper
+//
+// Command bursts (Shield, Armor, Skirmish, Information, Mining) are turned into modifiers
+// for the fit calculation. Burst strength is computed from the booster character:
+//
+//   strength = base charge value * (1.25 if T2 bursts) * (1 + 10% per command skill level)
+//              * (1 + hull bonus%) * (1.25 with a matching mindlink)
+//
+// Bursts loaded with the same charge do not stack; only the strongest applies.
+// Boost presets are saved as JSON under `data/` next to the other local settings.
+
+use crate::esi::Skill;
+use crate::ewar::{
+    ECM_GROUP, SENSOR_DAMPENER_GROUP, STASIS_WEB_GROUP, TARGET_PAINTER_GROUP, WARP_SCRAMBLER_GROUP,
+    WEAPON_DISRUPTOR_GROUP,
+};
+use crate::fitting::{
+    attr, Modifier, ModifierTarget, ARMOR_REPAIRER_GROUP, MINING_GROUPS, PROPULSION_GROUP,
+    SHIELD_BOOSTER_GROUP,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const PRESETS_PATH: &str = "data/boost_presets.json";
+
+/// Command burst family
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum BurstType {
+    Shield,
+    Armor,
+    Skirmish,
+    Information,
+    Mining,
+}
+
+impl BurstType {
+    /// Command skill that improves bursts of this type
+    pub fn command_skill_id(self) -> i64 {
+        match self {
+            BurstType::Shield => 3350,       // Shield Command
+            BurstType::Armor => 20494,       // Armored Command
+            BurstType::Skirmish => 3349,     // Skirmish Command
+            BurstType::Information => 20495, // Information Command
+            BurstType::Mining => 22536,      // Mining Foreman
+        }
+    }
+}
+
+/// Charge loaded in a command burst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum BurstCharge {
+    ShieldHarmonizing,
+    ActiveShielding,
+    ShieldExtension,
+    ArmorEnergizing,
+    RapidRepair,
+    ArmorReinforcement,
+    EvasiveManeuvers,
+    InterdictionManeuvers,
+    RapidDeployment,
+    SensorOptimization,
+    ElectronicSuperiority,
+    ElectronicHardening,
+    MiningLaserFieldEnhancement,
+    MiningLaserOptimization,
+    MiningEquipmentPreservation,
+}
+
+/// SDE groups of warp scramblers/disruptors and stasis webifiers
+const TACKLE_GROUPS: &[i64] = &[WARP_SCRAMBLER_GROUP, STASIS_WEB_GROUP];
+/// SDE groups of ECM, sensor dampeners, weapon disruptors and target painters
+const EWAR_GROUPS: &[i64] = &[
+    ECM_GROUP,
+    SENSOR_DAMPENER_GROUP,
+    WEAPON_DISRUPTOR_GROUP,
+    TARGET_PAINTER_GROUP,
+];
+/// SDE group of mining crystals
+const MINING_CRYSTAL_GROUP: i64 = 482;
+
+impl BurstCharge {
+    pub fn burst_type(self) -> BurstType {
+        use BurstCharge::*;
+        match self {
+            ShieldHarmonizing | ActiveShielding | ShieldExtension => BurstType::Shield,
+            ArmorEnergizing | RapidRepair | ArmorReinforcement => BurstType::Armor,
+            EvasiveManeuvers | InterdictionManeuvers | RapidDeployment => BurstType::Skirmish,
+            SensorOptimization | ElectronicSuperiority | ElectronicHardening => {
+                BurstType::Information
+            }
+            MiningLaserFieldEnhancement | MiningLaserOptimization | MiningEquipmentPreservation => {
+                BurstType::Mining
+            }
+        }
+    }
+
+    /// Strength of a T1 burst with this charge and no skills, in percent
+    pub fn base_strength(self) -> f64 {
+        use BurstCharge::*;
+        match self {
+            InterdictionManeuvers | RapidDeployment => 12.0,
+            MiningLaserFieldEnhancement | MiningEquipmentPreservation => 10.0,
+            _ => 8.0,
+        }
+    }
+
+    /// Modifiers the burst applies at the given strength (in percent)
+    pub fn modifiers(self, strength: f64) -> Vec<Modifier> {
+        use BurstCharge::*;
+        use ModifierTarget::*;
+        let p = Modifier::percent;
+        let groups = |groups: &[i64], name: &str, value: f64| -> Vec<Modifier> {
+            groups
+                .iter()
+                .map(|g| p(ModuleGroup(*g), name, value))
+                .collect()
+        };
+        match self {
+            ShieldHarmonizing => attr::SHIELD_RESONANCES
+                .iter()
+                .map(|name| p(Ship, name, -strength))
+                .collect(),
+            ActiveShielding => vec![
+                p(ModuleGroup(SHIELD_BOOSTER_GROUP), attr::DURATION, -strength),
+                p(
+                    ModuleGroup(SHIELD_BOOSTER_GROUP),
+                    attr::CAPACITOR_NEED,
+                    -strength,
+                ),
+            ],
+            ShieldExtension => vec![p(Ship, attr::SHIELD_CAPACITY, strength)],
+            ArmorEnergizing => attr::ARMOR_RESONANCES
+                .iter()
+                .map(|name| p(Ship, name, -strength))
+                .collect(),
+            RapidRepair => vec![
+                p(ModuleGroup(ARMOR_REPAIRER_GROUP), attr::DURATION, -strength),
+                p(
+                    ModuleGroup(ARMOR_REPAIRER_GROUP),
+                    attr::CAPACITOR_NEED,
+                    -strength,
+                ),
+            ],
+            ArmorReinforcement => vec![p(Ship, attr::ARMOR_HP, strength)],
+            EvasiveManeuvers => vec![
+                p(Ship, attr::SIGNATURE_RADIUS, -strength),
+                p(Ship, attr::AGILITY, -strength),
+            ],
+            InterdictionManeuvers => groups(TACKLE_GROUPS, attr::MAX_RANGE, strength),
+            RapidDeployment => vec![p(
+                ModuleGroup(PROPULSION_GROUP),
+                attr::SPEED_FACTOR,
+                strength,
+            )],
+            SensorOptimization => vec![
+                p(Ship, attr::SCAN_RESOLUTION, strength),
+                p(Ship, attr::MAX_TARGET_RANGE, strength),
+            ],
+            ElectronicSuperiority => groups(EWAR_GROUPS, attr::MAX_RANGE, strength),
+            ElectronicHardening => attr::SENSOR_STRENGTHS
+                .iter()
+                .map(|name| p(Ship, name, strength))
+                .collect(),
+            MiningLaserFieldEnhancement => groups(MINING_GROUPS, attr::MAX_RANGE, strength),
+            MiningLaserOptimization => {
+                let mut modifiers = groups(MINING_GROUPS, attr::DURATION, -strength);
+                modifiers.extend(groups(MINING_GROUPS, attr::CAPACITOR_NEED, -strength));
+                modifiers
+            }
+            MiningEquipmentPreservation => vec![p(
+                ChargeGroup(MINING_CRYSTAL_GROUP),
+                attr::CRYSTAL_VOLATILITY_CHANCE,
+                -strength,
+            )],
+        }
+    }
+}
+
+/// A burst effect active on the fit
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct FleetBoost {
+    pub charge: BurstCharge,
+    /// Effect strength in percent
+    pub strength: f64,
+}
+
+/// The fleet booster character and the bursts they run
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FleetBooster {
+    /// The booster's skills
+    #[serde(default)]
+    pub skills: Vec<Skill>,
+    /// Hull bonus to burst strength in percent (e.g. 15.0 for a Command Ship at level V)
+    #[serde(default)]
+    pub ship_bonus: f64,
+    /// Whether T2 burst modules are used
+    #[serde(default)]
+    pub tech_two: bool,
+    /// Burst types covered by a plugged-in mindlink implant
+    #[serde(default)]
+    pub mindlinks: Vec<BurstType>,
+    /// Charges loaded in the booster's bursts
+    pub bursts: Vec<BurstCharge>,
+}
+
+impl FleetBooster {
+    /// Strength of one burst charge run by this booster, in percent
+    pub fn burst_strength(&self, charge: BurstCharge) -> f64 {
+        let burst_type = charge.burst_type();
+        let command_level = self
+            .skills
+            .iter()
+            .find(|s| s.skill_id == burst_type.command_skill_id())
+            .map(|s| s.active_level)
+            .unwrap_or(0);
+        let mut strength = charge.base_strength() * (1.0 + 0.1 * command_level as f64);
+        if self.tech_two {
+            strength *= 1.25;
+        }
+        strength *= 1.0 + self.ship_bonus / 100.0;
+        if self.mindlinks.contains(&burst_type) {
+            strength *= 1.25;
+        }
+        strength
+    }
+
+    /// The boosts this booster gives to fleet members
+    pub fn boosts(&self) -> Vec<FleetBoost> {
+        self.bursts
+            .iter()
+            .map(|charge| FleetBoost {
+                charge: *charge,
+                strength: self.burst_strength(*charge),
+            })
+            .collect()
+    }
+}
+
+/// Modifiers from active fleet boosts; only the strongest boost per charge applies
+pub fn boost_modifiers(boosts: &[FleetBoost]) -> Vec<Modifier> {
+    let mut strongest: Vec<FleetBoost> = vec![];
+    for boost in boosts {
+        match strongest.iter_mut().find(|b| b.charge == boost.charge) {
+            Some(existing) if existing.strength < boost.strength => *existing = *boost,
+            Some(_) => {}
+            None => strongest.push(*boost),
+        }
+    }
+    strongest
+        .iter()
+        .flat_map(|boost| boost.charge.modifiers(boost.strength))
+        .collect()
+}
+
+/// A saved, named booster setup
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BoostPreset {
+    pub name: String,
+    pub booster: FleetBooster,
+}
+
+/// Load all saved boost presets (empty if none were saved yet)
+pub fn load_boost_presets() -> Result<Vec<BoostPreset>, String> {
+    match fs::read_to_string(PRESETS_PATH) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse boost presets: {}", e)),
+        Err(_) => Ok(vec![]),
+    }
+}
+
+fn write_boost_presets(presets: &[BoostPreset]) -> Result<(), String> {
+    fs::create_dir_all("data").map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(presets)
+        .map_err(|e| format!("Failed to serialize boost presets: {}", e))?;
+    fs::write(PRESETS_PATH, json).map_err(|e| format!("Failed to write boost presets: {}", e))
+}
+
+/// Save a boost preset, replacing any preset with the same name
+pub fn save_boost_preset(preset: BoostPreset) -> Result<(), String> {
+    let mut presets = load_boost_presets()?;
+    presets.retain(|p| p.name != preset.name);
+    presets.push(preset);
+    write_boost_presets(&presets)
+}
+
+/// Delete the boost preset with the given name
+pub fn delete_boost_preset(name: &str) -> Result<(), String> {
+    let mut presets = load_boost_presets()?;
+    presets.retain(|p| p.name != name);
+    write_boost_presets(&presets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::Skill;
+    use crate::fitting::{calculate_fit_stats_with, CalcContext};
+    use crate::test_support::{attrs, blaster_fit};
+
+    #[test]
+    fn test_fleet_burst_strength() {
+        let booster = FleetBooster {
+            skills: vec![Skill {
+                skill_id: 3350,
+                skill_name: Some("Shield Command".to_string()),
+                active_level: 5,
+            }],
+            ship_bonus: 15.0,
+            tech_two: true,
+            mindlinks: vec![BurstType::Shield],
+            bursts: vec![BurstCharge::ShieldHarmonizing, BurstCharge::RapidRepair],
+        };
+        // 8% base, +50% skill, +25% T2, +15% hull, +25% mindlink
+        let expected = 8.0 * 1.5 * 1.25 * 1.15 * 1.25;
+        let boosts = booster.boosts();
+        assert!((boosts[0].strength - expected).abs() < 1e-9);
+        // Armor bursts do not benefit from Shield Command or the shield mindlink
+        assert!((boosts[1].strength - 8.0 * 1.25 * 1.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fleet_boosts_feed_fit_stats() {
+        let mut fit = blaster_fit();
+        fit.ship.attributes.extend(attrs(&[
+            ("shieldCapacity", 1_000.0),
+            ("shieldEmDamageResonance", 1.0),
+        ]));
+        let weak = FleetBoost {
+            charge: BurstCharge::ShieldHarmonizing,
+            strength: 10.0,
+        };
+        let strong = FleetBoost {
+            charge: BurstCharge::ShieldHarmonizing,
+            strength: 20.0,
+        };
+        let extension = FleetBoost {
+            charge: BurstCharge::ShieldExtension,
+            strength: 10.0,
+        };
+        let ctx = CalcContext {
+            fleet_boosts: vec![weak, strong, extension],
+            ..Default::default()
+        };
+        let stats = calculate_fit_stats_with(&fit, &ctx);
+        // Same-charge bursts do not stack: only the 20% one applies
+        assert!((stats.shield_resists.em - 0.20).abs() < 1e-9);
+        assert!((stats.shield_hp - 1_100.0).abs() < 1e-6);
+    }
+}
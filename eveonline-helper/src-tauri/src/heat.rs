@@ -0,0 +1,163 @@
+//! Overheat damage simulation for EveOnline Helper
+//
+// Simplified model of EVE's heat mechanics, run per rack (high, mid, low):
+// - Every second each overheated module adds its `heatAbsorbtionRateModifier` (scaled by the
+//   hull's `heatGenerationMultiplier`) to the rack's heat, and the rack dissipates heat at the
+//   hull's `heatDissipationRate` for that rack. Rack heat is a 0.0-1.0 fraction.
+// - Each overheated cycle deals `heatDamage` to the module with a chance equal to the rack
+//   heat; the simulation accumulates the expected damage until it reaches the module's `hp`.
+// - Thermodynamics reduces heat damage by 5% per level.
+// Heat damage spreading to neighbouring modules is not modelled.
+
+use crate::esi::{FitVariant, ModuleState, Skill, SlotType};
+use crate::fitting::{attr, calculate_attributes, module_state};
+use serde::{Deserialize, Serialize};
+
+/// Thermodynamics skill ID
+const THERMODYNAMICS: i64 = 28164;
+/// Simulation cut-off; modules still alive after this are reported as not burning out
+const MAX_SECONDS: u32 = 1800;
+
+/// Estimated survival of an overheated module
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HeatSimulation {
+    /// Index of the module in `FitVariant::modules`
+    pub module_index: usize,
+    pub module_name: String,
+    /// Overheated cycles before the module burns out (None if it outlasts the simulation)
+    pub cycles_to_burnout: Option<u32>,
+    /// Seconds of overheating before the module burns out
+    pub seconds_to_burnout: Option<f64>,
+}
+
+struct HeatedModule {
+    index: usize,
+    cycle_time: f64,
+    absorption: f64,
+    heat_damage: f64,
+    hp: f64,
+    next_cycle: f64,
+    cycles: u32,
+    damage: f64,
+    burned_out_at: Option<(u32, f64)>,
+}
+
+/// Simulate heat damage for every overheated module in a fit
+///
+/// # Arguments
+/// * `fit` - The fit with some modules set to `ModuleState::Overheated`
+/// * `user_skills` - The character's skills (Thermodynamics reduces heat damage)
+///
+/// # Returns
+/// - One HeatSimulation per overheated module, in fit order
+pub fn simulate_heat(fit: &FitVariant, user_skills: &[Skill]) -> Vec<HeatSimulation> {
+    let calc = calculate_attributes(fit, user_skills);
+    let thermodynamics = user_skills
+        .iter()
+        .find(|s| s.skill_id == THERMODYNAMICS)
+        .map(|s| s.active_level)
+        .unwrap_or(0);
+    let damage_factor = 1.0 - 0.05 * thermodynamics as f64;
+    let generation = calc.ship_attr(attr::HEAT_GENERATION_MULTIPLIER);
+
+    let mut results = vec![];
+    for (slot, dissipation_attr) in [
+        (SlotType::High, attr::HEAT_DISSIPATION_HI),
+        (SlotType::Mid, attr::HEAT_DISSIPATION_MED),
+        (SlotType::Low, attr::HEAT_DISSIPATION_LOW),
+    ] {
+        let mut rack: Vec<HeatedModule> = fit
+            .modules
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.slot == Some(slot) && module_state(m) == ModuleState::Overheated)
+            .map(|(index, _)| {
+                let cycle_ms = [attr::RATE_OF_FIRE, attr::DURATION]
+                    .iter()
+                    .map(|name| calc.module_attr(index, name))
+                    .find(|ms| *ms > 0.0)
+                    .unwrap_or(0.0);
+                HeatedModule {
+                    index,
+                    cycle_time: cycle_ms / 1000.0,
+                    absorption: calc.module_attr(index, attr::HEAT_ABSORPTION),
+                    heat_damage: calc.module_attr(index, attr::HEAT_DAMAGE) * damage_factor,
+                    hp: calc.module_attr(index, attr::MODULE_HP),
+                    next_cycle: cycle_ms / 1000.0,
+                    cycles: 0,
+                    damage: 0.0,
+                    burned_out_at: None,
+                }
+            })
+            .collect();
+        let dissipation = calc.ship_attr(dissipation_attr);
+        let mut heat: f64 = 0.0;
+        for second in 1..=MAX_SECONDS {
+            let t = second as f64;
+            let heating: f64 = rack
+                .iter()
+                .filter(|m| m.burned_out_at.is_none())
+                .map(|m| m.absorption * generation)
+                .sum();
+            heat = (heat + heating * (1.0 - heat) - dissipation * heat).clamp(0.0, 1.0);
+            for module in rack.iter_mut().filter(|m| m.burned_out_at.is_none()) {
+                if module.cycle_time <= 0.0 || module.hp <= 0.0 || module.heat_damage <= 0.0 {
+                    continue;
+                }
+                while module.next_cycle <= t && module.burned_out_at.is_none() {
+                    module.cycles += 1;
+                    module.damage += module.heat_damage * heat;
+                    if module.damage >= module.hp {
+                        module.burned_out_at = Some((module.cycles, module.next_cycle));
+                    }
+                    module.next_cycle += module.cycle_time;
+                }
+            }
+        }
+        results.extend(rack.into_iter().map(|m| HeatSimulation {
+            module_index: m.index,
+            module_name: fit.modules[m.index].module_name.clone(),
+            cycles_to_burnout: m.burned_out_at.map(|(cycles, _)| cycles),
+            seconds_to_burnout: m.burned_out_at.map(|(_, seconds)| seconds),
+        }));
+    }
+    results.sort_by_key(|r| r.module_index);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{ModuleState, Skill};
+    use crate::test_support::{attrs, blaster_fit};
+
+    #[test]
+    fn test_heat_simulation() {
+        let mut fit = blaster_fit();
+        fit.ship.attributes.extend(attrs(&[
+            ("heatGenerationMultiplier", 1.0),
+            ("heatDissipationRateHi", 0.01),
+        ]));
+        fit.modules[0].attributes.extend(attrs(&[
+            ("hp", 40.0),
+            ("heatDamage", 4.0),
+            ("heatAbsorbtionRateModifier", 0.1),
+        ]));
+        // Not overheated: nothing to simulate
+        assert!(simulate_heat(&fit, &[]).is_empty());
+
+        fit.modules[0].state = ModuleState::Overheated;
+        let untrained = simulate_heat(&fit, &[]);
+        assert_eq!(untrained.len(), 1);
+        let untrained_cycles = untrained[0].cycles_to_burnout.unwrap();
+        let thermodynamics = vec![Skill {
+            skill_id: 28164,
+            skill_name: Some("Thermodynamics".to_string()),
+            active_level: 5,
+        }];
+        let trained_cycles = simulate_heat(&fit, &thermodynamics)[0]
+            .cycles_to_burnout
+            .unwrap();
+        assert!(trained_cycles > untrained_cycles);
+    }
+}
@@ -0,0 +1,185 @@
+//! Implants, hardwirings and boosters for EveOnline Helper
+//
+// Implants and boosters contribute modifiers to the fit calculation in the same way
+// skills do. Pirate implant sets (Slave, Snake, Crystal, ...) are modelled with a set
+// multiplier on every implant of the set: each set implant's bonuses are multiplied by
+// the product of the multipliers of all fitted implants in that set.
+//
+// Booster side effects are only applied when the caller opts in, since in game each
+// side effect only has a chance to occur.
+
+use crate::fitting::Modifier;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Membership of an implant in a pirate implant set
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ImplantSetBonus {
+    /// Set name, e.g. "Slave" or "Snake"
+    pub set_name: String,
+    /// Multiplier this implant contributes to the set's bonuses (e.g. 1.15)
+    pub multiplier: f64,
+}
+
+/// An implant or hardwiring plugged into the character's clone
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Implant {
+    pub implant_id: i64,
+    pub implant_name: String,
+    /// Implant slot (1-10, SDE `implantness`)
+    pub slot: i32,
+    /// Bonuses the implant applies
+    #[serde(default)]
+    pub effects: Vec<Modifier>,
+    /// Set the implant belongs to, if any
+    #[serde(default)]
+    pub set_bonus: Option<ImplantSetBonus>,
+}
+
+/// A possible penalty from taking a booster
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BoosterSideEffect {
+    pub name: String,
+    /// Chance the side effect occurs (0.0-1.0)
+    pub chance: f64,
+    pub modifier: Modifier,
+}
+
+/// An active combat booster
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Booster {
+    pub booster_id: i64,
+    pub booster_name: String,
+    /// Booster slot (SDE `boosterness`)
+    pub slot: i32,
+    /// Bonuses the booster applies
+    #[serde(default)]
+    pub effects: Vec<Modifier>,
+    #[serde(default)]
+    pub side_effects: Vec<BoosterSideEffect>,
+}
+
+/// Modifiers from the plugged-in implants, with set bonuses applied
+///
+/// If several implants claim the same slot only the first one counts.
+pub fn implant_modifiers(implants: &[Implant]) -> Vec<Modifier> {
+    let mut plugged: Vec<&Implant> = vec![];
+    for implant in implants {
+        if !plugged.iter().any(|p| p.slot == implant.slot) {
+            plugged.push(implant);
+        }
+    }
+    let mut set_multipliers: HashMap<&str, f64> = HashMap::new();
+    for set in plugged.iter().filter_map(|i| i.set_bonus.as_ref()) {
+        *set_multipliers.entry(set.set_name.as_str()).or_insert(1.0) *= set.multiplier;
+    }
+    plugged
+        .iter()
+        .flat_map(|implant| {
+            let factor = implant
+                .set_bonus
+                .as_ref()
+                .map(|set| set_multipliers[set.set_name.as_str()])
+                .unwrap_or(1.0);
+            implant.effects.iter().map(move |m| m.scaled(factor))
+        })
+        .collect()
+}
+
+/// Modifiers from active boosters, optionally including every side effect
+pub fn booster_modifiers(boosters: &[Booster], include_side_effects: bool) -> Vec<Modifier> {
+    let mut modifiers = vec![];
+    for booster in boosters {
+        modifiers.extend(booster.effects.iter().cloned());
+        if include_side_effects {
+            modifiers.extend(booster.side_effects.iter().map(|s| s.modifier.clone()));
+        }
+    }
+    modifiers
+}
+
+/// Resolve the implant type IDs of a clone (as returned by ESI) against known implants
+///
+/// Unknown type IDs are skipped.
+pub fn implants_from_type_ids(type_ids: &[i64], known_implants: &[Implant]) -> Vec<Implant> {
+    type_ids
+        .iter()
+        .filter_map(|id| known_implants.iter().find(|i| i.implant_id == *id))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fitting::{calculate_fit_stats_with, CalcContext, Modifier, ModifierTarget};
+    use crate::test_support::{attrs, blaster_fit};
+
+    fn slave(slot: i32) -> Implant {
+        Implant {
+            implant_id: 20498 + slot as i64,
+            implant_name: format!("High-grade Slave {}", slot),
+            slot,
+            effects: vec![Modifier::percent(ModifierTarget::Ship, "armorHP", 5.0)],
+            set_bonus: Some(ImplantSetBonus {
+                set_name: "Slave".to_string(),
+                multiplier: 1.1,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_implant_set_bonus() {
+        let mut fit = blaster_fit();
+        fit.ship.attributes.insert("armorHP".to_string(), 1_000.0);
+        let ctx = CalcContext {
+            implants: vec![slave(1), slave(2)],
+            ..Default::default()
+        };
+        // Each implant's 5% is multiplied by 1.1 * 1.1 for the two-piece set
+        let expected = 1_000.0 * (1.0 + 0.05 * 1.21) * (1.0 + 0.05 * 1.21);
+        let stats = calculate_fit_stats_with(&fit, &ctx);
+        assert!((stats.armor_hp - expected).abs() < 1e-6);
+        // Two implants in the same slot only count once
+        let ctx = CalcContext {
+            implants: vec![slave(1), slave(1)],
+            ..Default::default()
+        };
+        let stats = calculate_fit_stats_with(&fit, &ctx);
+        assert!((stats.armor_hp - 1_000.0 * (1.0 + 0.05 * 1.1)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_booster_side_effects_are_opt_in() {
+        let mut fit = blaster_fit();
+        fit.ship.attributes.extend(attrs(&[
+            ("shieldCapacity", 1_000.0),
+            ("maxVelocity", 200.0),
+        ]));
+        let booster = Booster {
+            booster_id: 28672,
+            booster_name: "Standard Blue Pill Booster".to_string(),
+            slot: 1,
+            effects: vec![Modifier::percent(
+                ModifierTarget::Ship,
+                "shieldCapacity",
+                10.0,
+            )],
+            side_effects: vec![BoosterSideEffect {
+                name: "Velocity penalty".to_string(),
+                chance: 0.3,
+                modifier: Modifier::percent(ModifierTarget::Ship, "maxVelocity", -10.0),
+            }],
+        };
+        let mut ctx = CalcContext {
+            boosters: vec![booster],
+            ..Default::default()
+        };
+        let stats = calculate_fit_stats_with(&fit, &ctx);
+        assert!((stats.shield_hp - 1_100.0).abs() < 1e-6);
+        assert!((stats.max_velocity - 200.0).abs() < 1e-6);
+        ctx.booster_side_effects = true;
+        let stats = calculate_fit_stats_with(&fit, &ctx);
+        assert!((stats.max_velocity - 180.0).abs() < 1e-6);
+    }
+}
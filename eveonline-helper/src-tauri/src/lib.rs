@@ -4,13 +4,68 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+mod activity;
+mod alternatives;
+mod assets;
+mod capacitor;
+mod charges;
+mod compare;
+mod db;
+mod doctrine;
+mod eft;
+mod environment;
 mod esi;
+mod ewar;
+mod exploration;
+mod fitting;
+mod fleet;
+mod heat;
+mod implants;
+mod logistics;
+mod mining;
+mod npc;
+mod optimizer;
+mod prices;
+mod ranking;
+mod rationale;
+mod recommend;
+mod safety;
+mod saved_fits;
+mod t3;
+#[cfg(test)]
+mod test_support;
+mod training;
+mod what_if;
+use activity::Activity;
+use assets::AssetCache;
+use capacitor::CapSimulation;
+use compare::{FitComparison, FitSource};
+use doctrine::{CharacterReadiness, CharacterSkills, Doctrine};
+use eft::{CargoItem, EftImport, EftTypes};
+use environment::{EnvironmentProfile, SdeType};
 use esi::{
-    generate_fit_variants, handle_local_callback, match_skills_to_ships_and_modules,
-    open_browser_for_login, suggest_alternative_fits, validate_fit, EveSsoConfig, FitVariant,
-    Module, Ship, Skill,
+    handle_local_callback, open_browser_for_login, Charge, EveSsoConfig, FitVariant, Ship, Skill,
 };
-use tauri::Window;
+use ewar::{EwarSummary, SensorProfile};
+use exploration::ExplorationStats;
+use fitting::{calculate_fit_stats_with, CalcContext, FitStats};
+use fleet::{BoostPreset, FleetBoost, FleetBooster};
+use heat::HeatSimulation;
+use implants::{implants_from_type_ids, Implant};
+use logistics::LogisticsStats;
+use mining::{MiningYield, OreType};
+use npc::{NpcCorporation, NpcFaction};
+use prices::{PriceFormat, PriceTable};
+use ranking::{RankingWeights, ShipRanking};
+use recommend::{
+    FitSearch, FitSearches, RecommendationRequest, SearchSummary, FIT_RECOMMENDATIONS_EVENT,
+};
+use safety::SafetyReport;
+use saved_fits::SavedFit;
+use sqlx::SqlitePool;
+use std::time::Instant;
+use tauri::{Emitter, State, Window};
+use what_if::{SkillScenario, SkillSimulation};
 
 /// Tauri command to start the EVE SSO login flow and open the system browser
 #[tauri::command]
@@ -24,33 +79,86 @@ pub fn process_eve_sso_callback(window: Window, callback_url: String) -> Result<
     handle_local_callback(&window, &callback_url)
 }
 
-/// Tauri command to get ship fit recommendations for a user
+/// Tauri command to search fit recommendations for a user
+///
+/// Ships are optimized in parallel; each ship's fits are emitted as a
+/// `fit-recommendations` event (`ShipRecommendations`) as soon as they are ready.
+///
+/// # Arguments
+/// * `search_id` - ID chosen by the frontend, sent with every event and used to cancel
+/// * `request` - Skills, ships, modules, charges, activity and search options
+///
+/// # Returns
+/// - How many ships were done and whether the search was cancelled or timed out
 #[tauri::command]
 pub async fn get_fit_recommendations(
-    user_skills: Vec<Skill>,
-    all_ships: Vec<Ship>,
-    all_modules: Vec<Module>,
-    activity: String,
-) -> Vec<FitVariant> {
-    // 1. Match user skills to eligible ships and modules
-    let (eligible_ships, eligible_modules) =
-        match_skills_to_ships_and_modules(&user_skills, &all_ships, &all_modules);
-    // 2. For each eligible ship, generate fit variants
-    let mut recommendations = vec![];
-    for ship in eligible_ships {
-        let fits = generate_fit_variants(&ship, &activity, &eligible_modules);
-        // 3. Validate each fit and suggest alternatives if needed
-        for fit in fits {
-            let validation = validate_fit(&fit, &user_skills);
-            if validation.is_valid {
-                recommendations.push(fit);
-            } else {
-                let alternatives = suggest_alternative_fits(&fit, &user_skills, &eligible_modules);
-                recommendations.extend(alternatives);
-            }
+    window: Window,
+    searches: State<'_, FitSearches>,
+    db: State<'_, SqlitePool>,
+    search_id: u64,
+    request: RecommendationRequest,
+) -> Result<SearchSummary, String> {
+    let deadline = Instant::now() + request.deadline();
+    let prices = prices::load_prices(&db).await?;
+    // "Use what I have": only modules the character owns (in the chosen station); a search
+    // the assets cannot be loaded for fails rather than using every module
+    let owned = match request.owned_assets {
+        Some(selection) => {
+            let (access_token, _) = esi::get_tokens(&selection.character_id.to_string())?;
+            let owned = assets::load_owned_items(selection, &access_token)
+                .await
+                .map_err(|e| format!("Could not load the character's assets: {}", e))?;
+            Some(owned)
         }
-    }
-    recommendations
+        None => None,
+    };
+    let cancel = searches.start(search_id);
+    let search = FitSearch::new(request, prices, owned);
+    let worker_cancel = cancel.clone();
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        search.run(search_id, deadline, &worker_cancel, &|ship| {
+            if let Err(e) = window.emit(FIT_RECOMMENDATIONS_EVENT, ship) {
+                println!("[Recommendations] Failed to emit event: {}", e);
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("Fit search failed: {}", e))?;
+    searches.finish(search_id, &cancel);
+    Ok(summary)
+}
+
+/// Tauri command to rank fits of any hulls for an activity
+///
+/// # Arguments
+/// * `fits` - Fits to rank (e.g. the fits a search emitted, or saved fits)
+/// * `activity` - The activity the fits are for
+/// * `context` - The character's skills and other context
+/// * `weights` - How much effectiveness, safety, cost and skill readiness count
+/// * `top_n` - Number of ships to return
+///
+/// # Returns
+/// - The best ships with their fits, each with a score broken down into its components
+#[tauri::command]
+pub fn rank_fits_cmd(
+    fits: Vec<FitVariant>,
+    activity: Activity,
+    context: CalcContext,
+    weights: Option<RankingWeights>,
+    top_n: usize,
+) -> Vec<ShipRanking> {
+    ranking::rank_ships(
+        fits,
+        &activity,
+        &context,
+        &weights.unwrap_or_default(),
+        top_n,
+    )
+}
+
+#[tauri::command]
+pub fn cancel_fit_search(searches: State<'_, FitSearches>, search_id: u64) -> bool {
+    searches.cancel(search_id)
 }
 
 /// Tauri command to generate a prioritized skill plan for a recommended fit
@@ -81,6 +189,419 @@ pub fn get_skill_plan_for_fit(
     plan
 }
 
+/// Tauri command to calculate a fit's stats with implants, boosters and skills
+///
+/// # Arguments
+/// * `fit` - The fit to evaluate
+/// * `context` - Skills, implants, boosters and whether to apply booster side effects
+///
+/// # Returns
+/// - Calculated FitStats
+#[tauri::command]
+pub fn get_fit_stats(fit: FitVariant, context: CalcContext) -> FitStats {
+    calculate_fit_stats_with(&fit, &context)
+}
+
+/// Tauri command to evaluate how safe a fit is for an activity
+///
+/// # Arguments
+/// * `fit` - The fit to evaluate
+/// * `activity` - The activity, with its safety thresholds and expected incoming DPS
+/// * `context` - Skills, implants, boosters, fleet boosts, ...
+///
+/// # Returns
+/// - The safety rating and score, tank, capacitor and align numbers, and warnings
+#[tauri::command]
+pub fn evaluate_fit_safety_cmd(
+    fit: FitVariant,
+    activity: Activity,
+    context: CalcContext,
+) -> SafetyReport {
+    let stats = calculate_fit_stats_with(&fit, &context);
+    safety::evaluate_safety(&fit, &stats, &activity, &context)
+}
+
+/// Tauri command to calculate what hypothetical skills would change on a fit
+///
+/// # Arguments
+/// * `fit` - The fit to evaluate
+/// * `context` - The character's current skills, implants, boosters, ...
+/// * `scenario` - The hypothetical skills: current, after a skill plan, all at one
+///   level, or per-skill level overrides
+///
+/// # Returns
+/// - Stats with both sets of skills, their delta and the skill levels that changed
+#[tauri::command]
+pub fn simulate_skills_cmd(
+    fit: FitVariant,
+    context: CalcContext,
+    scenario: SkillScenario,
+) -> SkillSimulation {
+    what_if::simulate_skills(&fit, &context, &scenario)
+}
+
+/// Tauri command to calculate a fit's mining yield for an ore type
+///
+/// # Arguments
+/// * `fit` - The mining fit (modules, crystals and drones)
+/// * `context` - Skills, implants and fleet boosts (e.g. Mining Foreman bursts)
+/// * `ore` - The ore being mined
+///
+/// # Returns
+/// - Yield in m3/s and m3/hour, residue and ore hold fill time
+#[tauri::command]
+pub fn get_mining_yield(fit: FitVariant, context: CalcContext, ore: OreType) -> MiningYield {
+    mining::calculate_mining_yield(&fit, &context, &ore)
+}
+
+#[tauri::command]
+pub fn rank_mining_fits(
+    fits: Vec<FitVariant>,
+    context: CalcContext,
+    ore: OreType,
+) -> Vec<MiningYield> {
+    mining::rank_fits_by_yield(&fits, &context, &ore)
+}
+
+/// Tauri command to calculate probe scan strength and analyzer stats for a fit
+///
+/// # Arguments
+/// * `fit` - The exploration fit
+/// * `context` - Skills, implants and other context
+/// * `probes` - Core and combat probe types carried with the fit
+///
+/// # Returns
+/// - Probe strengths, virus coherence/strength and align time
+#[tauri::command]
+pub fn get_exploration_stats(
+    fit: FitVariant,
+    context: CalcContext,
+    probes: Vec<Charge>,
+) -> ExplorationStats {
+    exploration::calculate_exploration_stats(&fit, &context, &probes)
+}
+
+/// Tauri command to summarize a fit's tackle and EWAR
+///
+/// # Arguments
+/// * `fit` - The fit to evaluate
+/// * `context` - Skills, implants and other context
+/// * `target_sensors` - Sensor type and strength of the ship to jam
+///
+/// # Returns
+/// - Web strength, warp disruption, tackle range, ECM jam chance and tackle score
+#[tauri::command]
+pub fn get_ewar_summary(
+    fit: FitVariant,
+    context: CalcContext,
+    target_sensors: SensorProfile,
+) -> EwarSummary {
+    let stats = calculate_fit_stats_with(&fit, &context);
+    ewar::summarize_ewar(&stats, &target_sensors)
+}
+
+/// Tauri command to simulate a fit's capacitor
+///
+/// # Arguments
+/// * `fit` - The fit to simulate
+/// * `context` - Skills, implants and other context
+/// * `incoming_per_second` - Capacitor received from other ships in GJ/s (e.g. a cap chain)
+///
+/// # Returns
+/// - Whether the fit is cap stable, and the stable level or time until empty
+#[tauri::command]
+pub fn get_capacitor_simulation(
+    fit: FitVariant,
+    context: CalcContext,
+    incoming_per_second: Option<f64>,
+) -> CapSimulation {
+    capacitor::simulate_capacitor(&fit, &context, incoming_per_second.unwrap_or(0.0))
+}
+
+/// Tauri command to calculate remote repair throughput for a logistics fit
+///
+/// # Arguments
+/// * `fit` - The logistics fit
+/// * `context` - Skills, implants and other context
+///
+/// # Returns
+/// - Shield/armor HP/s, capacitor GJ/s, ranges and capacitor with and without a cap chain
+#[tauri::command]
+pub fn get_logistics_stats(fit: FitVariant, context: CalcContext) -> LogisticsStats {
+    logistics::calculate_logistics_stats(&fit, &context)
+}
+
+#[tauri::command]
+pub fn list_activities() -> Result<Vec<Activity>, String> {
+    activity::load_activities()
+}
+
+#[tauri::command]
+pub fn save_activity_cmd(activity: Activity) -> Result<(), String> {
+    activity::save_activity(activity)
+}
+
+#[tauri::command]
+pub fn delete_activity_cmd(name: String) -> Result<(), String> {
+    activity::delete_activity(&name)
+}
+
+#[tauri::command]
+pub fn get_npc_factions() -> Vec<NpcFaction> {
+    npc::npc_factions()
+}
+
+/// Tauri command to fill in the enemy of a mission activity
+///
+/// # Arguments
+/// * `activity` - A mission activity with an enemy faction or agent corporation
+/// * `npc_corporations` - SDE NPC corporations with their faction IDs
+///
+/// # Returns
+/// - The activity with the enemy's damage types, resists and expected mission DPS
+#[tauri::command]
+pub fn resolve_mission_enemy_cmd(
+    activity: Activity,
+    npc_corporations: Vec<NpcCorporation>,
+) -> Activity {
+    npc::resolve_mission_enemy(&activity, &npc_corporations)
+}
+
+/// Tauri command to compare two or more fits side by side
+///
+/// # Arguments
+/// * `fits` - Fits to compare, each sent in full or as a saved fit ID
+/// * `context` - The character's skills, implants and other context
+///
+/// # Returns
+/// - DPS, EHP, capacitor, speed, targeting, cost (from the local price table) and
+///   training time per fit, and the modules that differ between the fits
+#[tauri::command]
+pub async fn compare_fits_cmd(
+    db: State<'_, SqlitePool>,
+    fits: Vec<FitSource>,
+    context: CalcContext,
+) -> Result<FitComparison, String> {
+    let fits = fits
+        .into_iter()
+        .map(FitSource::resolve)
+        .collect::<Result<Vec<_>, _>>()?;
+    compare::compare_fits(&fits, &context, &prices::load_prices(&db).await?)
+}
+
+#[tauri::command]
+pub async fn get_price_table(db: State<'_, SqlitePool>) -> Result<PriceTable, String> {
+    prices::load_prices(&db).await
+}
+
+/// Tauri command to import a price snapshot into the local price table
+///
+/// # Arguments
+/// * `contents` - The snapshot: `type_id,price` lines (CSV), or a type ID to price map or
+///   ESI market prices (JSON)
+/// * `format` - "csv" or "json"
+///
+/// # Returns
+/// - The number of type IDs now priced
+#[tauri::command]
+pub async fn import_prices_cmd(
+    db: State<'_, SqlitePool>,
+    contents: String,
+    format: PriceFormat,
+) -> Result<usize, String> {
+    prices::import_prices(&db, &contents, format).await
+}
+
+/// Tauri command to update the local price table from the ESI market
+///
+/// # Returns
+/// - The number of type IDs now priced
+#[tauri::command]
+pub async fn fetch_market_prices_cmd(db: State<'_, SqlitePool>) -> Result<usize, String> {
+    let prices = prices::fetch_market_prices().await?;
+    prices::store_prices(&db, prices).await
+}
+
+#[tauri::command]
+pub fn list_saved_fits() -> Result<Vec<SavedFit>, String> {
+    saved_fits::load_saved_fits()
+}
+
+#[tauri::command]
+pub fn save_fit_cmd(fit: FitVariant) -> Result<u64, String> {
+    saved_fits::save_fit(fit)
+}
+
+#[tauri::command]
+pub fn delete_saved_fit_cmd(id: u64) -> Result<(), String> {
+    saved_fits::delete_saved_fit(id)
+}
+
+#[tauri::command]
+pub async fn list_doctrines(db: State<'_, SqlitePool>) -> Result<Vec<Doctrine>, String> {
+    doctrine::load_doctrines(&db).await
+}
+
+#[tauri::command]
+pub async fn save_doctrine_cmd(
+    db: State<'_, SqlitePool>,
+    doctrine: Doctrine,
+) -> Result<(), String> {
+    doctrine::save_doctrine(&db, &doctrine).await
+}
+
+#[tauri::command]
+pub async fn delete_doctrine_cmd(db: State<'_, SqlitePool>, name: String) -> Result<(), String> {
+    doctrine::delete_doctrine(&db, &name).await
+}
+
+/// Tauri command to import a doctrine from EFT fits and store it
+///
+/// # Arguments
+/// * `name` - Name of the doctrine (replaces a stored doctrine of the same name)
+/// * `eft` - The doctrine's fits in EFT format, one per role (named after the role)
+/// * `types` - SDE ships, modules, charges, drones and modes the items are resolved against
+///
+/// # Returns
+/// - The stored doctrine
+#[tauri::command]
+pub async fn import_doctrine_cmd(
+    db: State<'_, SqlitePool>,
+    name: String,
+    eft: String,
+    types: EftTypes,
+) -> Result<Doctrine, String> {
+    let doctrine = doctrine::doctrine_from_eft(&name, &eft, &types)?;
+    doctrine::save_doctrine(&db, &doctrine).await?;
+    Ok(doctrine)
+}
+
+/// Tauri command to read a fit from EFT text
+///
+/// # Arguments
+/// * `eft` - The fit in EFT format
+/// * `types` - SDE ships, modules, charges, drones and modes the items are resolved against
+///
+/// # Returns
+/// - The fit, its cargo and the lines that could not be resolved
+#[tauri::command]
+pub fn import_eft_cmd(eft: String, types: EftTypes) -> Result<EftImport, String> {
+    eft::parse_eft(&eft, &types)
+}
+
+#[tauri::command]
+pub fn export_eft_cmd(fit: FitVariant, cargo: Option<Vec<CargoItem>>) -> String {
+    eft::export_eft(&fit, &cargo.unwrap_or_default())
+}
+
+/// Tauri command to check which stored doctrine fits each character can fly
+///
+/// # Arguments
+/// * `characters` - The known characters with their skills
+///
+/// # Returns
+/// - Per character, every doctrine fit with whether it can be flown now, and otherwise
+///   the missing skills and the time to train them
+#[tauri::command]
+pub async fn get_doctrine_readiness(
+    db: State<'_, SqlitePool>,
+    characters: Vec<CharacterSkills>,
+) -> Result<Vec<CharacterReadiness>, String> {
+    Ok(doctrine::doctrine_readiness(
+        &doctrine::load_doctrines(&db).await?,
+        &characters,
+    ))
+}
+
+/// Tauri command to compute the burst effects a fleet booster provides
+///
+/// # Arguments
+/// * `booster` - The booster character's skills, ship bonus, mindlinks and bursts
+///
+/// # Returns
+/// - Burst effects with their strength, ready to put in a CalcContext
+#[tauri::command]
+pub fn get_fleet_boosts(booster: FleetBooster) -> Vec<FleetBoost> {
+    booster.boosts()
+}
+
+#[tauri::command]
+pub fn list_boost_presets() -> Result<Vec<BoostPreset>, String> {
+    fleet::load_boost_presets()
+}
+
+#[tauri::command]
+pub fn save_boost_preset_cmd(preset: BoostPreset) -> Result<(), String> {
+    fleet::save_boost_preset(preset)
+}
+
+#[tauri::command]
+pub fn delete_boost_preset_cmd(name: String) -> Result<(), String> {
+    fleet::delete_boost_preset(&name)
+}
+
+/// Tauri command to build environment profiles from SDE beacon and weather types
+///
+/// # Arguments
+/// * `sde_types` - Effect beacon and abyssal weather types with their dogma attributes
+///
+/// # Returns
+/// - One profile per wormhole effect class, abyssal weather type and incursion effect
+#[tauri::command]
+pub fn get_environment_profiles(sde_types: Vec<SdeType>) -> Vec<EnvironmentProfile> {
+    environment::load_environment_profiles(&sde_types)
+}
+
+/// Tauri command to load the implants of a character's active clone from ESI
+///
+/// # Arguments
+/// * `character_id` - The character whose stored ESI token is used
+/// * `known_implants` - Implant definitions from the SDE
+///
+/// # Returns
+/// - The implants plugged into the active clone
+#[tauri::command]
+pub async fn load_active_clone_implants(
+    character_id: i64,
+    known_implants: Vec<Implant>,
+) -> Result<Vec<Implant>, String> {
+    let (access_token, _) = esi::get_tokens(&character_id.to_string())?;
+    let type_ids = esi::fetch_character_implants(&access_token, character_id).await?;
+    Ok(implants_from_type_ids(&type_ids, &known_implants))
+}
+
+/// Tauri command to fetch a character's assets from ESI and cache them
+///
+/// # Arguments
+/// * `character_id` - The character whose stored ESI token is used
+///
+/// # Returns
+/// - The cached assets with the time they were fetched and when ESI has newer data
+#[tauri::command]
+pub async fn refresh_assets_cmd(character_id: i64) -> Result<AssetCache, String> {
+    let (access_token, _) = esi::get_tokens(&character_id.to_string())?;
+    let assets = assets::fetch_character_assets(&access_token, character_id).await?;
+    assets::cache_assets(character_id, assets)
+}
+
+#[tauri::command]
+pub fn get_cached_assets(character_id: i64) -> Result<Option<AssetCache>, String> {
+    assets::load_cached_assets(character_id)
+}
+
+/// Tauri command to estimate how long overheated modules survive
+///
+/// # Arguments
+/// * `fit` - The fit with modules set to the `Overheated` state
+/// * `user_skills` - The user's current skills
+///
+/// # Returns
+/// - Cycles and seconds to burnout for each overheated module
+#[tauri::command]
+pub fn get_heat_simulation(fit: FitVariant, user_skills: Vec<Skill>) -> Vec<HeatSimulation> {
+    heat::simulate_heat(&fit, &user_skills)
+}
+
 /// Tauri command to suggest the next ship progression for the user
 ///
 /// # Arguments
@@ -137,6 +658,14 @@ pub fn sde_auto_update_occurred() -> bool {
     unsafe { SDE_AUTO_UPDATED }
 }
 
+/// Why the encrypted database could not be opened, if it could not (Tauri managed state)
+pub struct DatabaseError(Option<String>);
+
+#[tauri::command]
+pub fn get_database_error(error: State<'_, DatabaseError>) -> Option<String> {
+    error.0.clone()
+}
+
 #[tauri::command]
 pub fn get_eula_accepted() -> bool {
     esi::eula_accepted()
@@ -159,20 +688,79 @@ pub fn run() {
             let _ = esi::refresh_sde();
         });
     }
-    tauri::Builder::default()
+    // Without the encrypted database the app runs on an in-memory one, so nothing is
+    // saved; the frontend shows why (`get_database_error`)
+    let (pool, database_error) = match tauri::async_runtime::block_on(db::open_db()) {
+        Ok(pool) => (Some(pool), None),
+        Err(e) => {
+            println!("[DB] {}; nothing will be saved this session", e);
+            let pool = tauri::async_runtime::block_on(db::open_memory_db())
+                .inspect_err(|e| println!("[DB] {}", e))
+                .ok();
+            (pool, Some(e))
+        }
+    };
+    let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(FitSearches::default())
+        .manage(DatabaseError(database_error));
+    // Commands that need the database fail with an error if no database could be opened
+    if let Some(pool) = pool {
+        builder = builder.manage(pool);
+    }
+    builder
         .invoke_handler(tauri::generate_handler![
             greet,
             start_eve_sso_login,
             process_eve_sso_callback,
             get_fit_recommendations,
+            cancel_fit_search,
+            rank_fits_cmd,
             get_skill_plan_for_fit,
+            get_heat_simulation,
+            get_fit_stats,
+            simulate_skills_cmd,
+            evaluate_fit_safety_cmd,
+            get_mining_yield,
+            rank_mining_fits,
+            get_exploration_stats,
+            get_ewar_summary,
+            get_capacitor_simulation,
+            get_logistics_stats,
+            list_activities,
+            save_activity_cmd,
+            delete_activity_cmd,
+            get_npc_factions,
+            resolve_mission_enemy_cmd,
+            compare_fits_cmd,
+            get_price_table,
+            import_prices_cmd,
+            fetch_market_prices_cmd,
+            list_saved_fits,
+            save_fit_cmd,
+            delete_saved_fit_cmd,
+            list_doctrines,
+            save_doctrine_cmd,
+            delete_doctrine_cmd,
+            import_doctrine_cmd,
+            get_doctrine_readiness,
+            import_eft_cmd,
+            export_eft_cmd,
+            get_environment_profiles,
+            load_active_clone_implants,
+            refresh_assets_cmd,
+            get_cached_assets,
+            get_fleet_boosts,
+            list_boost_presets,
+            save_boost_preset_cmd,
+            delete_boost_preset_cmd,
             get_next_ship_progression,
             export_skill_plan_evemon_cmd,
             refresh_sde_cmd,
             sde_auto_update_occurred,
             get_eula_accepted,
-            set_eula_accepted_cmd
+            set_eula_accepted_cmd,
+            get_database_error
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,322 @@
+//! Remote repair and logistics for EveOnline Helper
+//
+// Throughput of running remote shield boosters (`shieldBonus`), remote armor repairers
+// (`armorDamageAmount`) and remote capacitor transmitters (`powerTransferAmount`) per
+// second, with their optimal (`maxRange`) and falloff (`falloffEffectiveness`).
+//
+// In a cap chain every logistics ship passes capacitor to the next one in the chain and
+// receives the same amount from the previous one, so the fit's own transfer rate comes
+// back to it as incoming capacitor.
+
+use crate::capacitor::{simulate_capacitor, CapSimulation};
+use crate::esi::{FitVariant, Module, Ship, SlotType};
+use crate::fitting::{
+    attr, calculate_attributes_with, calculate_fit_stats_with, cycle_time, is_running, CalcContext,
+};
+use serde::{Deserialize, Serialize};
+
+/// SDE group of remote shield boosters
+pub const REMOTE_SHIELD_BOOSTER_GROUP: i64 = 41;
+/// SDE group of remote armor repairers
+pub const REMOTE_ARMOR_REPAIRER_GROUP: i64 = 325;
+/// SDE group of remote capacitor transmitters
+pub const REMOTE_CAPACITOR_GROUP: i64 = 67;
+/// Number of logistics fits returned per ship
+const MAX_FITS: usize = 5;
+
+/// What a remote assistance module transfers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RemoteRepairKind {
+    Shield,
+    Armor,
+    Capacitor,
+}
+
+impl RemoteRepairKind {
+    fn of(module: &Module) -> Option<Self> {
+        match module.group_id {
+            REMOTE_SHIELD_BOOSTER_GROUP => Some(RemoteRepairKind::Shield),
+            REMOTE_ARMOR_REPAIRER_GROUP => Some(RemoteRepairKind::Armor),
+            REMOTE_CAPACITOR_GROUP => Some(RemoteRepairKind::Capacitor),
+            _ => None,
+        }
+    }
+
+    /// Attribute holding the amount transferred per cycle
+    fn amount_attribute(self) -> &'static str {
+        match self {
+            RemoteRepairKind::Shield => attr::SHIELD_BONUS,
+            RemoteRepairKind::Armor => attr::ARMOR_REPAIR,
+            RemoteRepairKind::Capacitor => attr::POWER_TRANSFER_AMOUNT,
+        }
+    }
+}
+
+/// Throughput and range of one running remote assistance module
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RemoteRepairStats {
+    /// Index of the module in `FitVariant::modules`
+    pub module_index: usize,
+    pub module_name: String,
+    pub kind: RemoteRepairKind,
+    /// HP/s for shield and armor, GJ/s for capacitor
+    pub amount_per_second: f64,
+    pub optimal_range: f64,
+    pub falloff: f64,
+}
+
+/// Logistics output of a fit
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LogisticsStats {
+    pub fit_name: String,
+    pub modules: Vec<RemoteRepairStats>,
+    /// Remote shield repair in HP/s
+    pub shield_per_second: f64,
+    /// Remote armor repair in HP/s
+    pub armor_per_second: f64,
+    /// Capacitor transferred in GJ/s
+    pub capacitor_per_second: f64,
+    /// Shortest optimal range of the fit's repair modules (the range the fit repairs at)
+    pub repair_range: f64,
+    /// Capacitor with no outside help
+    pub capacitor: CapSimulation,
+    /// Capacitor while in a cap chain
+    pub capacitor_chained: CapSimulation,
+}
+
+/// Calculate remote repair throughput, range and capacitor for a logistics fit
+///
+/// # Arguments
+/// * `fit` - The logistics fit
+/// * `ctx` - Skills, implants, fleet boosts and environment
+///
+/// # Returns
+/// - LogisticsStats, with capacitor simulated both alone and in a cap chain
+pub fn calculate_logistics_stats(fit: &FitVariant, ctx: &CalcContext) -> LogisticsStats {
+    let calc = calculate_attributes_with(fit, ctx);
+    let mut stats = LogisticsStats {
+        fit_name: fit.fit_name.clone(),
+        ..Default::default()
+    };
+    for (index, module) in fit.modules.iter().enumerate() {
+        let Some(kind) = RemoteRepairKind::of(module) else {
+            continue;
+        };
+        let cycle = cycle_time(&calc.modules[index]);
+        if !is_running(module) || cycle <= 0.0 {
+            continue;
+        }
+        let amount_per_second = calc.module_attr(index, kind.amount_attribute()) / cycle;
+        match kind {
+            RemoteRepairKind::Shield => stats.shield_per_second += amount_per_second,
+            RemoteRepairKind::Armor => stats.armor_per_second += amount_per_second,
+            RemoteRepairKind::Capacitor => stats.capacitor_per_second += amount_per_second,
+        }
+        stats.modules.push(RemoteRepairStats {
+            module_index: index,
+            module_name: module.module_name.clone(),
+            kind,
+            amount_per_second,
+            optimal_range: calc.module_attr(index, attr::MAX_RANGE),
+            falloff: calc.module_attr(index, attr::FALLOFF_EFFECTIVENESS),
+        });
+    }
+    stats.repair_range = stats
+        .modules
+        .iter()
+        .filter(|m| m.kind != RemoteRepairKind::Capacitor)
+        .map(|m| m.optimal_range)
+        .reduce(f64::min)
+        .unwrap_or(0.0);
+    stats.capacitor = simulate_capacitor(fit, ctx, 0.0);
+    stats.capacitor_chained = simulate_capacitor(fit, ctx, stats.capacitor_per_second);
+    stats
+}
+
+/// Best module of a remote assistance kind by amount per second
+fn best_remote_module(modules: &[Module], kind: RemoteRepairKind) -> Option<&Module> {
+    let rate = |m: &Module| {
+        let get = |name: &str| m.attributes.get(name).copied().unwrap_or(0.0);
+        let cycle = cycle_time(&m.attributes);
+        if cycle > 0.0 {
+            get(kind.amount_attribute()) / cycle
+        } else {
+            0.0
+        }
+    };
+    modules
+        .iter()
+        .filter(|m| RemoteRepairKind::of(m) == Some(kind) && m.slot == Some(SlotType::High))
+        .max_by(|a, b| rate(a).total_cmp(&rate(b)))
+}
+
+/// Build logistics fits for a hull from the modules the user can fit
+///
+/// For shield and armor logistics, the high slots are filled with the best remote repair
+/// module, once with a capacitor transmitter for a cap chain and once without. Repairers
+/// are removed until the fit is within CPU and powergrid. Fits are ranked by repair
+/// throughput, preferring fits that are cap stable in a chain.
+///
+/// # Returns
+/// - Up to five fits, best first
+pub fn generate_logistics_fits(
+    ship: &Ship,
+    eligible_modules: &[Module],
+    ctx: &CalcContext,
+) -> Vec<FitVariant> {
+    let base = FitVariant {
+        ship: ship.clone(),
+        ..Default::default()
+    };
+    let highs = calculate_attributes_with(&base, ctx).slot_count(SlotType::High);
+    let transmitter = best_remote_module(eligible_modules, RemoteRepairKind::Capacitor);
+    let mut fits = vec![];
+    for (kind, label) in [
+        (RemoteRepairKind::Shield, "Shield"),
+        (RemoteRepairKind::Armor, "Armor"),
+    ] {
+        let Some(repairer) = best_remote_module(eligible_modules, kind) else {
+            continue;
+        };
+        for chain in [true, false] {
+            let mut fit = base.clone();
+            fit.fit_name = format!("{} {} Logistics", ship.ship_name, label);
+            if chain {
+                let Some(transmitter) = transmitter else {
+                    continue;
+                };
+                fit.fit_name.push_str(" (Cap Chain)");
+                fit.modules.push(transmitter.clone());
+            }
+            while fit.modules.len() < highs {
+                fit.modules.push(repairer.clone());
+            }
+            loop {
+                let stats = calculate_fit_stats_with(&fit, ctx);
+                let fits_resources = stats.cpu_used <= stats.cpu_output
+                    && stats.powergrid_used <= stats.powergrid_output;
+                if fits_resources {
+                    break;
+                }
+                match fit
+                    .modules
+                    .iter()
+                    .rposition(|m| m.group_id == repairer.group_id)
+                {
+                    Some(index) => {
+                        fit.modules.remove(index);
+                    }
+                    None => break,
+                }
+            }
+            if fit.modules.iter().any(|m| m.group_id == repairer.group_id) {
+                fits.push(fit);
+            }
+        }
+    }
+
+    let mut ranked: Vec<(bool, f64, FitVariant)> =
+        fits.into_iter()
+            .map(|mut fit| {
+                let stats = calculate_logistics_stats(&fit, ctx);
+                let repair = stats.shield_per_second + stats.armor_per_second;
+                fit.rationale =
+                    format!(
+                "{:.0} HP/s remote repair at {:.0} km; capacitor {} alone, {} in a cap chain",
+                repair,
+                stats.repair_range / 1000.0,
+                if stats.capacitor.stable { "stable" } else { "unstable" },
+                if stats.capacitor_chained.stable {
+                    "stable"
+                } else {
+                    "unstable"
+                }
+            );
+                (stats.capacitor_chained.stable, repair, fit)
+            })
+            .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)));
+    ranked
+        .into_iter()
+        .take(MAX_FITS)
+        .map(|(_, _, fit)| fit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{generate_fit_variants, FitVariant, Module, Ship, SlotType};
+    use crate::fitting::CalcContext;
+    use crate::optimizer::SearchBudget;
+    use crate::test_support::{activity, attrs, remote_shield_booster};
+    use std::collections::HashMap;
+
+    fn logi_ship() -> Ship {
+        Ship {
+            ship_name: "Scimitar".to_string(),
+            attributes: attrs(&[
+                ("hiSlots", 4.0),
+                ("cpuOutput", 400.0),
+                ("powerOutput", 1_000.0),
+                ("capacitorCapacity", 2_000.0),
+                ("rechargeRate", 400_000.0),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    fn cap_transmitter() -> Module {
+        Module {
+            module_name: "Medium Remote Capacitor Transmitter II".to_string(),
+            group_id: 67,
+            slot: Some(SlotType::High),
+            attributes: attrs(&[
+                ("powerTransferAmount", 195.0),
+                ("duration", 5_000.0),
+                ("maxRange", 24_000.0),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_logistics_and_cap_chain() {
+        let fit = FitVariant {
+            ship: logi_ship(),
+            modules: vec![
+                cap_transmitter(),
+                remote_shield_booster(),
+                remote_shield_booster(),
+                remote_shield_booster(),
+            ],
+            ..Default::default()
+        };
+        let stats = calculate_logistics_stats(&fit, &CalcContext::default());
+        assert!((stats.shield_per_second - 3.0 * 400.0 / 5.0).abs() < 1e-9);
+        assert!((stats.capacitor_per_second - 39.0).abs() < 1e-9);
+        assert_eq!(stats.repair_range, 40_000.0);
+        // 36 GJ/s of reps against 12.5 GJ/s peak recharge: dry alone, stable in a chain
+        assert!(!stats.capacitor.stable);
+        assert!(stats.capacitor.seconds_to_empty.unwrap() > 0.0);
+        assert!(stats.capacitor_chained.stable);
+
+        let mut modules = vec![cap_transmitter(), remote_shield_booster()];
+        modules[1].attributes.insert("cpu".to_string(), 150.0);
+        let fits = generate_fit_variants(
+            &logi_ship(),
+            &activity("Fleet Logistics"),
+            &CalcContext::default(),
+            &modules,
+            &[],
+            &HashMap::new(),
+            &[],
+            SearchBudget::default(),
+        )
+        .unwrap();
+        assert_eq!(fits.len(), 2);
+        // The chained fit keeps the transmitter and as many boosters as CPU allows
+        assert!(fits[0].fit_name.contains("Cap Chain"));
+        assert_eq!(fits[0].modules.len(), 3);
+    }
+}
@@ -0,0 +1,202 @@
+//! Mining yield calculation for EveOnline Helper
+//
+// Yield is reported in m3 of ore per second and per hour:
+// - mining lasers/strip miners: `miningAmount` per cycle, multiplied by the loaded crystal's
+//   `specialisationAsteroidYieldMultiplier` when the crystal matches the ore's asteroid group
+// - crystals also destroy extra ore as residue: each cycle has a `miningWasteProbability`
+//   (percent) chance to waste `miningWastedVolumeMultiplier` times the cycle's yield
+// - mining drones: `miningAmount` per cycle for each active drone; drone travel time is
+//   not modelled
+// Mining Foreman bursts come in through the fleet boosts of the CalcContext.
+
+use crate::esi::{FitVariant, Skill};
+use crate::fitting::{
+    attr, calculate_attributes_with, cycle_time, is_running, CalcContext, MINING_GROUPS,
+};
+use serde::{Deserialize, Serialize};
+
+/// SDE group of mining drones
+pub const MINING_DRONE_GROUP: i64 = 101;
+/// Drones skill ID; each level allows one more active drone
+const DRONES: i64 = 3436;
+
+/// The ore being mined
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OreType {
+    pub name: String,
+    /// SDE asteroid group (e.g. 462 for Veldspar), matched against crystal specialisation
+    pub group_id: i64,
+    /// Volume of one unit of ore in m3
+    pub volume: f64,
+}
+
+/// Mining output of a fit for one ore type
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MiningYield {
+    pub fit_name: String,
+    /// m3/s from mining lasers and strip miners
+    pub module_yield: f64,
+    /// m3/s from mining drones
+    pub drone_yield: f64,
+    /// Total m3/s
+    pub m3_per_second: f64,
+    pub m3_per_hour: f64,
+    pub units_per_hour: f64,
+    /// Expected ore lost to residue in m3/hour
+    pub residue_per_hour: f64,
+    /// Seconds until the ore hold (or cargo hold) is full, None if nothing is mined
+    pub hold_fill_time: Option<f64>,
+}
+
+/// Number of drones that can be active at once with the character's skills
+fn max_active_drones(user_skills: &[Skill]) -> u32 {
+    user_skills
+        .iter()
+        .find(|s| s.skill_id == DRONES)
+        .map(|s| s.active_level.clamp(0, 5) as u32)
+        .unwrap_or(0)
+}
+
+/// Calculate the mining yield of a fit for an ore type
+///
+/// # Arguments
+/// * `fit` - The fit with mining modules, crystals and drones
+/// * `ctx` - Skills, implants, fleet boosts (Mining Foreman bursts) and environment
+/// * `ore` - The ore being mined
+///
+/// # Returns
+/// - MiningYield with m3/s, m3/hour, units/hour, residue and hold fill time
+pub fn calculate_mining_yield(fit: &FitVariant, ctx: &CalcContext, ore: &OreType) -> MiningYield {
+    let calc = calculate_attributes_with(fit, ctx);
+    let mut result = MiningYield {
+        fit_name: fit.fit_name.clone(),
+        ..Default::default()
+    };
+    let mut residue = 0.0;
+    for (index, module) in fit.modules.iter().enumerate() {
+        if !MINING_GROUPS.contains(&module.group_id) || !is_running(module) {
+            continue;
+        }
+        let cycle = cycle_time(&calc.modules[index]);
+        if cycle <= 0.0 {
+            continue;
+        }
+        let mut per_cycle = calc.module_attr(index, attr::MINING_AMOUNT);
+        if let Some(crystal) = &calc.charges[index] {
+            let get = |name: &str| crystal.get(name).copied().unwrap_or(0.0);
+            if get(attr::SPECIALISATION_GROUP) as i64 == ore.group_id {
+                per_cycle *= get(attr::SPECIALISATION_YIELD).max(1.0);
+            }
+            residue += per_cycle * get(attr::RESIDUE_CHANCE) / 100.0
+                * get(attr::RESIDUE_VOLUME_MULTIPLIER)
+                / cycle;
+        }
+        result.module_yield += per_cycle / cycle;
+    }
+
+    let mut drones_left = max_active_drones(&ctx.skills);
+    let mut bandwidth_left = calc.ship_attr(attr::DRONE_BANDWIDTH);
+    for (index, drone) in fit.drones.iter().enumerate() {
+        if drone.group_id != MINING_DRONE_GROUP {
+            continue;
+        }
+        let bandwidth = calc.drone_attr(index, attr::DRONE_BANDWIDTH_USED);
+        let by_bandwidth = if bandwidth > 0.0 {
+            (bandwidth_left / bandwidth).floor() as u32
+        } else {
+            drone.quantity
+        };
+        let active = drone.quantity.min(drones_left).min(by_bandwidth);
+        drones_left -= active;
+        bandwidth_left -= bandwidth * active as f64;
+        let cycle = cycle_time(&calc.drones[index]);
+        if cycle > 0.0 {
+            result.drone_yield +=
+                active as f64 * calc.drone_attr(index, attr::MINING_AMOUNT) / cycle;
+        }
+    }
+
+    result.m3_per_second = result.module_yield + result.drone_yield;
+    result.m3_per_hour = result.m3_per_second * 3600.0;
+    if ore.volume > 0.0 {
+        result.units_per_hour = result.m3_per_hour / ore.volume;
+    }
+    result.residue_per_hour = residue * 3600.0;
+    let hold = match calc.ship_attr(attr::ORE_HOLD_CAPACITY) {
+        ore_hold if ore_hold > 0.0 => ore_hold,
+        _ => calc.ship_attr(attr::CAPACITY),
+    };
+    if result.m3_per_second > 0.0 {
+        result.hold_fill_time = Some(hold / result.m3_per_second);
+    }
+    result
+}
+
+/// Rank fits by mining yield for an ore type, best first
+pub fn rank_fits_by_yield(
+    fits: &[FitVariant],
+    ctx: &CalcContext,
+    ore: &OreType,
+) -> Vec<MiningYield> {
+    let mut yields: Vec<MiningYield> = fits
+        .iter()
+        .map(|fit| calculate_mining_yield(fit, ctx, ore))
+        .collect();
+    // Stable sort keeps the input order for equal yields
+    yields.sort_by(|a, b| b.m3_per_second.total_cmp(&a.m3_per_second));
+    yields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::Skill;
+    use crate::fitting::CalcContext;
+    use crate::fleet::{BurstCharge, FleetBoost};
+    use crate::test_support::mining_fit;
+
+    #[test]
+    fn test_mining_yield() {
+        let veldspar = OreType {
+            name: "Veldspar".to_string(),
+            group_id: 462,
+            volume: 0.1,
+        };
+        let ctx = CalcContext::from_skills(&[Skill {
+            skill_id: 3436,
+            skill_name: Some("Drones".to_string()),
+            active_level: 3,
+        }]);
+        let result = calculate_mining_yield(&mining_fit(true), &ctx, &veldspar);
+        assert!((result.module_yield - 900.0 * 1.75 / 180.0).abs() < 1e-9);
+        // Drones skill 3 limits the eight drones in the bay to three active
+        assert!((result.drone_yield - 3.0 * 25.0 / 60.0).abs() < 1e-9);
+        assert!((result.m3_per_hour - result.m3_per_second * 3600.0).abs() < 1e-6);
+        assert!((result.units_per_hour - result.m3_per_hour / 0.1).abs() < 1e-6);
+        assert!((result.residue_per_hour - 900.0 * 1.75 * 0.1 / 180.0 * 3600.0).abs() < 1e-6);
+        assert!((result.hold_fill_time.unwrap() - 16_000.0 / result.m3_per_second).abs() < 1e-6);
+
+        // The crystal does nothing for other ores
+        let scordite = OreType {
+            name: "Scordite".to_string(),
+            group_id: 460,
+            volume: 0.15,
+        };
+        let result = calculate_mining_yield(&mining_fit(true), &ctx, &scordite);
+        assert!((result.module_yield - 5.0).abs() < 1e-9);
+
+        let ranked = rank_fits_by_yield(&[mining_fit(false), mining_fit(true)], &ctx, &veldspar);
+        assert_eq!(ranked[0].fit_name, "Crystal");
+
+        // Mining Foreman bursts shorten the strip miner cycle
+        let boosted = CalcContext {
+            fleet_boosts: vec![FleetBoost {
+                charge: BurstCharge::MiningLaserOptimization,
+                strength: 10.0,
+            }],
+            ..ctx.clone()
+        };
+        let result = calculate_mining_yield(&mining_fit(false), &boosted, &veldspar);
+        assert!((result.module_yield - 900.0 / 162.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,196 @@
+//! NPC factions for EveOnline Helper
+//
+// A local data set (`resources/npc_factions.json`) records, per SDE NPC faction:
+// - the damage types its ships deal (fractions of their total damage)
+// - its ships' average resists, i.e. which damage types they are weak to
+// - the expected incoming DPS of its missions by agent level (1-5)
+// - for empire factions, the pirate factions their agents' missions usually send
+//   players against
+//
+// Choosing an agent's corporation (an SDE NPC corporation, which belongs to a faction)
+// fills in the enemy of a mission activity: its incoming damage decides which hardeners
+// the optimizer picks, its resists decide which ammo is loaded.
+
+use crate::activity::{Activity, ActivityKind};
+use crate::fitting::DamageProfile;
+use serde::{Deserialize, Serialize};
+
+const NPC_FACTIONS: &str = include_str!("../resources/npc_factions.json");
+
+/// An NPC faction and how its ships fight
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NpcFaction {
+    /// SDE faction ID (e.g. 500010 for the Guristas)
+    pub faction_id: i64,
+    pub name: String,
+    /// Damage types dealt, as fractions of the total
+    pub damage_dealt: DamageProfile,
+    /// Average resist fractions of the faction's ships
+    pub resists: DamageProfile,
+    /// Expected incoming DPS in missions, by agent level (index 0 = level 1)
+    pub mission_dps: Vec<f64>,
+    /// Factions fought in this faction's missions (empty: the faction itself)
+    #[serde(default)]
+    pub mission_enemies: Vec<i64>,
+}
+
+/// An SDE NPC corporation (e.g. an agent's corporation)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NpcCorporation {
+    pub corporation_id: i64,
+    pub corporation_name: String,
+    pub faction_id: i64,
+}
+
+/// All NPC factions in the local data set
+pub fn npc_factions() -> Vec<NpcFaction> {
+    serde_json::from_str(NPC_FACTIONS).expect("NPC faction data is valid")
+}
+
+/// Factions fought in missions for a faction
+pub fn mission_enemies(faction_id: i64) -> Vec<NpcFaction> {
+    let factions = npc_factions();
+    let Some(faction) = factions.iter().find(|f| f.faction_id == faction_id) else {
+        return vec![];
+    };
+    if faction.mission_enemies.is_empty() {
+        return vec![faction.clone()];
+    }
+    faction
+        .mission_enemies
+        .iter()
+        .filter_map(|id| factions.iter().find(|f| f.faction_id == *id).cloned())
+        .collect()
+}
+
+/// Fill in a mission activity's enemy
+///
+/// The enemy is the mission's faction if set, otherwise the factions usually fought for
+/// the agent's corporation. With several enemies, damage and resists are averaged and
+/// the highest expected DPS is used.
+///
+/// # Arguments
+/// * `activity` - A mission activity
+/// * `corporations` - SDE NPC corporations, to find the agent corporation's faction
+///
+/// # Returns
+/// - The activity with incoming damage, target resists and expected DPS of the enemy,
+///   or unchanged if it is not a mission or the enemy is unknown
+pub fn resolve_mission_enemy(activity: &Activity, corporations: &[NpcCorporation]) -> Activity {
+    let mut resolved = activity.clone();
+    let ActivityKind::Mission {
+        level,
+        faction_id,
+        agent_corporation_id,
+    } = &activity.kind
+    else {
+        return resolved;
+    };
+    let enemies = match (faction_id, agent_corporation_id) {
+        (Some(faction_id), _) => npc_factions()
+            .into_iter()
+            .filter(|f| f.faction_id == *faction_id)
+            .collect(),
+        (None, Some(corporation_id)) => corporations
+            .iter()
+            .find(|c| c.corporation_id == *corporation_id)
+            .map(|c| mission_enemies(c.faction_id))
+            .unwrap_or_default(),
+        (None, None) => vec![],
+    };
+    if enemies.is_empty() {
+        return resolved;
+    }
+
+    let share = 1.0 / enemies.len() as f64;
+    let average = |profile: fn(&NpcFaction) -> DamageProfile| {
+        enemies.iter().fold(DamageProfile::default(), |sum, f| {
+            sum.add(&profile(f).scaled(share))
+        })
+    };
+    resolved.incoming_damage = average(|f| f.damage_dealt);
+    resolved.target.resists = average(|f| f.resists);
+    let level_index = (*level as usize).clamp(1, 5) - 1;
+    // Without enemy numbers, the profile's own estimate stays
+    if let Some(dps) = enemies
+        .iter()
+        .filter_map(|f| f.mission_dps.get(level_index).copied())
+        .reduce(f64::max)
+    {
+        resolved.safety.incoming_dps = dps;
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::ActivityKind;
+    use crate::charges::select_best_charge;
+    use crate::esi::{Charge, FitVariant, Ship};
+    use crate::fitting::CalcContext;
+    use crate::optimizer::{optimize_fit, Objective, SearchBudget};
+    use crate::test_support::{activity, attrs, blaster_fit, em_hardener};
+
+    #[test]
+    fn test_mission_enemy_selects_tank_and_ammo() {
+        let mut mission = activity("Level 4 Missions");
+        mission.kind = ActivityKind::Mission {
+            level: 4,
+            faction_id: None,
+            agent_corporation_id: Some(1000035),
+        };
+        let corporations = vec![NpcCorporation {
+            corporation_id: 1000035,
+            corporation_name: "Caldari Navy".to_string(),
+            faction_id: 500001,
+        }];
+        // Caldari agents send players against the Guristas and Serpentis
+        let mission = resolve_mission_enemy(&mission, &corporations);
+        assert!(mission.incoming_damage.kinetic > mission.incoming_damage.thermal);
+        assert_eq!(mission.incoming_damage.em, 0.0);
+        assert!(mission.target.resists.kinetic < mission.target.resists.em);
+        assert_eq!(mission.safety.incoming_dps, 800.0);
+
+        let mut kinetic_hardener = em_hardener();
+        kinetic_hardener.module_name = "Kinetic Shield Hardener II".to_string();
+        kinetic_hardener.effects[0].attribute = "shieldKineticDamageResonance".to_string();
+        let base = FitVariant {
+            ship: Ship {
+                ship_name: "Drake".to_string(),
+                attributes: attrs(&[
+                    ("medSlots", 1.0),
+                    ("cpuOutput", 100.0),
+                    ("shieldCapacity", 5_000.0),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tank = optimize_fit(
+            &base,
+            &[em_hardener(), kinetic_hardener],
+            &[],
+            Objective::Tank,
+            &mission,
+            &CalcContext::default(),
+            SearchBudget::default(),
+        )
+        .unwrap()
+        .fit;
+        assert_eq!(tank.modules[0].module_name, "Kinetic Shield Hardener II");
+
+        let charge = |name: &str, damage_type: &str| Charge {
+            charge_name: name.to_string(),
+            group_id: 85,
+            attributes: attrs(&[("chargeSize", 3.0), ("volume", 0.025), (damage_type, 10.0)]),
+            ..Default::default()
+        };
+        let charges = vec![
+            charge("EM Test Charge", "emDamage"),
+            charge("Kinetic Test Charge", "kineticDamage"),
+        ];
+        let best = select_best_charge(&blaster_fit(), 0, &charges, &mission.target, &[]).unwrap();
+        assert_eq!(best.charge_name, "Kinetic Test Charge");
+    }
+}
@@ -0,0 +1,853 @@
+//! Fit optimizer for EveOnline Helper
+//
+// Picks modules from the modules a character can use to maximize an objective:
+// - greedy construction: repeatedly adds the module that improves the score most
+// - local search: swaps each added module for other candidates of the same slot while
+//   that improves the score (e.g. one CPU-hungry module for a cheaper one that frees
+//   room for another), then tries adding modules again
+// Every candidate fit is scored with the full attribute calculation, so stacking
+// penalties, skills and the environment count exactly as they do in the fit stats.
+// Fits stay within CPU, powergrid, calibration, slots, hardpoints and `maxGroupFitted`,
+// and within an ISK budget if one is set (modules and charges without a price are then
+// left out).
+//
+// A partial fit can be completed: its modules (e.g. locked by the player) stay fitted
+// and only the remaining slots are searched. `locked_base` builds such a fit and
+// reports why it is infeasible if the locked modules alone break a constraint;
+// `locked_positions` finds the locked modules again in a finished fit.
+//
+// The search is deterministic: candidates are tried in input order, ties keep the
+// earlier candidate and the search stops after a fixed number of steps (adding a module,
+// or trying the swaps of one fitted module), so the same inputs give the same fit on any
+// machine. A time limit backs the step cap up on slow machines and huge module lists,
+// and a caller can stop a run early with a deadline or a cancel flag (checked before
+// every fit is scored). A search stopped by any of them is reported as truncated: its
+// fit is the best found so far, not a local optimum.
+
+use crate::activity::Activity;
+use crate::charges::{check_charge_compatibility, select_best_charge};
+use crate::esi::{Charge, FitVariant, Module, Ship, SlotType};
+use crate::fitting::{
+    attr, calculate_attributes_with, calculate_fit_stats_with, is_launcher, is_turret, CalcContext,
+    FitStats, StatDelta,
+};
+use crate::prices::CostLimit;
+use crate::rationale::{explain_fit, trade_off};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Slots the optimizer fills (subsystems are part of the hull choice, not the search)
+const FITTED_SLOTS: [SlotType; 4] = [SlotType::High, SlotType::Mid, SlotType::Low, SlotType::Rig];
+/// Smallest score gain that counts as an improvement
+const MIN_IMPROVEMENT: f64 = 1e-9;
+
+/// What the optimizer maximizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Objective {
+    /// Applied DPS against the activity's target
+    Dps,
+    /// Effective HP
+    Tank,
+    /// Applied DPS and EHP together (their product, so neither is neglected)
+    Balanced,
+    /// Balanced, limited to fits that are cap stable
+    CapStable,
+    /// The activity's weighted score (`Activity::score`)
+    Activity,
+}
+
+impl Objective {
+    pub const ALL: [Objective; 5] = [
+        Objective::Dps,
+        Objective::Tank,
+        Objective::Balanced,
+        Objective::CapStable,
+        Objective::Activity,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Objective::Dps => "Max DPS",
+            Objective::Tank => "Max Tank",
+            Objective::Balanced => "Balanced",
+            Objective::CapStable => "Cap-Stable",
+            Objective::Activity => "Activity-Optimized",
+        }
+    }
+
+    /// What the objective's fit gives up modules of the balanced fit for
+    fn trade_off_reason(self) -> Option<&'static str> {
+        match self {
+            Objective::Dps => Some("for more damage"),
+            Objective::Tank => Some("for more tank"),
+            Objective::Balanced => None,
+            Objective::CapStable => Some("for cap stability"),
+            Objective::Activity => Some("for the activity's priorities"),
+        }
+    }
+}
+
+/// Limits of one optimizer run
+#[derive(Debug, Clone, Copy)]
+pub struct SearchBudget<'a> {
+    /// Maximum number of search steps: adding a module, or trying the swaps of one
+    /// fitted module (each step scores every candidate once)
+    pub max_steps: usize,
+    /// Longest the run may take
+    pub time_limit: Duration,
+    /// The run stops when this passes (e.g. the deadline of the whole recommendation search)
+    pub deadline: Option<Instant>,
+    /// The run stops when this is set (e.g. the recommendation search was cancelled)
+    pub cancel: Option<&'a AtomicBool>,
+    /// Maximum ISK cost of the fit
+    pub cost_limit: Option<CostLimit<'a>>,
+}
+
+impl Default for SearchBudget<'_> {
+    fn default() -> Self {
+        SearchBudget {
+            max_steps: 500,
+            time_limit: Duration::from_secs(10),
+            deadline: None,
+            cancel: None,
+            cost_limit: None,
+        }
+    }
+}
+
+/// Result of one optimizer run
+#[derive(Debug, Clone)]
+pub struct OptimizedFit {
+    pub fit: FitVariant,
+    /// A limit, the deadline or the cancel flag stopped the search before no module could
+    /// be added or swapped for a better score
+    pub truncated: bool,
+}
+
+/// A module the player wants in the fit; the optimizer fills the other slots
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LockedModule {
+    pub module: Module,
+    pub slot: SlotType,
+    /// Charge to load (the best one for the activity's target if None)
+    #[serde(default)]
+    pub charge: Option<Charge>,
+}
+
+/// Slots and hardpoints of a hull
+struct Layout {
+    slots: Vec<(SlotType, usize)>,
+    turret_hardpoints: usize,
+    launcher_hardpoints: usize,
+}
+
+impl Layout {
+    fn of(fit: &FitVariant, ctx: &CalcContext) -> Self {
+        let calc = calculate_attributes_with(fit, ctx);
+        Layout {
+            slots: FITTED_SLOTS
+                .iter()
+                .map(|slot| (*slot, calc.slot_count(*slot)))
+                .collect(),
+            turret_hardpoints: calc.ship_attr(attr::TURRET_SLOTS) as usize,
+            launcher_hardpoints: calc.ship_attr(attr::LAUNCHER_SLOTS) as usize,
+        }
+    }
+
+    /// Why the fit's modules do not fit the slots, hardpoints and group limits, if so
+    fn violation(&self, fit: &FitVariant) -> Option<String> {
+        let count = |f: &dyn Fn(&Module) -> bool| fit.modules.iter().filter(|m| f(m)).count();
+        for (slot, available) in &self.slots {
+            let used = count(&|m| m.slot == Some(*slot));
+            if used > *available {
+                return Some(format!(
+                    "{} {:?} slot modules, {} slots",
+                    used, slot, available
+                ));
+            }
+        }
+        let turrets = count(&is_turret);
+        if turrets > self.turret_hardpoints {
+            return Some(format!(
+                "{} turrets, {} turret hardpoints",
+                turrets, self.turret_hardpoints
+            ));
+        }
+        let launchers = count(&is_launcher);
+        if launchers > self.launcher_hardpoints {
+            return Some(format!(
+                "{} launchers, {} launcher hardpoints",
+                launchers, self.launcher_hardpoints
+            ));
+        }
+        fit.modules.iter().find_map(|module| {
+            let max = *module.attributes.get(attr::MAX_GROUP_FITTED)? as usize;
+            (count(&|m| m.group_id == module.group_id) > max).then(|| {
+                format!(
+                    "at most {} of {}'s group can be fitted",
+                    max, module.module_name
+                )
+            })
+        })
+    }
+}
+
+/// Score of a fit for an objective (higher is better)
+///
+/// DPS is applied against the activity's target and EHP taken against its incoming damage.
+pub fn objective_score(objective: Objective, stats: &FitStats, activity: &Activity) -> f64 {
+    let applied_dps: f64 = stats
+        .weapons
+        .iter()
+        .map(|w| w.applied_dps(&activity.target))
+        .sum();
+    let ehp = stats.ehp_against(&activity.incoming_damage);
+    match objective {
+        Objective::Dps => applied_dps,
+        Objective::Tank => ehp,
+        Objective::Balanced | Objective::CapStable => (applied_dps + 1.0) * ehp,
+        Objective::Activity => activity.score(stats),
+    }
+}
+
+/// State of one optimizer run
+struct Search<'a> {
+    ctx: &'a CalcContext,
+    objective: Objective,
+    activity: &'a Activity,
+    layout: Layout,
+    steps_left: usize,
+    deadline: Instant,
+    cancel: Option<&'a AtomicBool>,
+    cost_limit: Option<CostLimit<'a>>,
+    truncated: bool,
+}
+
+impl Search<'_> {
+    /// Whether a limit stopped the search (checks the clock and the cancel flag)
+    fn stopped(&mut self) -> bool {
+        let cancelled = self
+            .cancel
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+        if cancelled || Instant::now() >= self.deadline {
+            self.truncated = true;
+        }
+        self.truncated
+    }
+
+    /// Take one search step; false once the steps are used up or the search was stopped
+    fn step(&mut self) -> bool {
+        if self.steps_left == 0 {
+            self.truncated = true;
+        }
+        if self.stopped() {
+            return false;
+        }
+        self.steps_left -= 1;
+        true
+    }
+
+    /// Score of a fit, or None if it breaks a constraint or the search was stopped
+    fn evaluate(&mut self, fit: &FitVariant) -> Option<f64> {
+        if self.stopped()
+            || self.layout.violation(fit).is_some()
+            || self.cost_limit.is_some_and(|limit| !limit.allows(fit))
+        {
+            return None;
+        }
+        let stats = calculate_fit_stats_with(fit, self.ctx);
+        let within_resources = stats.cpu_used <= stats.cpu_output
+            && stats.powergrid_used <= stats.powergrid_output
+            && stats.calibration_used <= stats.calibration_output;
+        if !within_resources || (self.objective == Objective::CapStable && !stats.capacitor_stable)
+        {
+            return None;
+        }
+        Some(objective_score(self.objective, &stats, self.activity))
+    }
+
+    /// Add the best improving candidate until none improves the score
+    fn construct(&mut self, fit: &mut FitVariant, score: &mut f64, candidates: &[Module]) {
+        while self.step() {
+            let mut best: Option<(f64, &Module)> = None;
+            for candidate in candidates {
+                let mut trial = fit.clone();
+                trial.modules.push(candidate.clone());
+                let Some(trial_score) = self.evaluate(&trial) else {
+                    continue;
+                };
+                let threshold = best.map(|(s, _)| s).unwrap_or(*score + MIN_IMPROVEMENT);
+                if trial_score > threshold {
+                    best = Some((trial_score, candidate));
+                }
+            }
+            match best {
+                Some((best_score, module)) => {
+                    fit.modules.push(module.clone());
+                    *score = best_score;
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Swap added modules (from `first_free` on) for better candidates of the same slot
+    ///
+    /// Returns whether any swap was made.
+    fn improve(
+        &mut self,
+        fit: &mut FitVariant,
+        score: &mut f64,
+        candidates: &[Module],
+        first_free: usize,
+    ) -> bool {
+        let mut improved = false;
+        for index in first_free..fit.modules.len() {
+            if !self.step() {
+                break;
+            }
+            for candidate in candidates {
+                let current = &fit.modules[index];
+                if candidate.slot != current.slot || candidate.module_name == current.module_name {
+                    continue;
+                }
+                let mut trial = fit.clone();
+                trial.modules[index] = candidate.clone();
+                if let Some(trial_score) = self.evaluate(&trial) {
+                    if trial_score > *score + MIN_IMPROVEMENT {
+                        *fit = trial;
+                        *score = trial_score;
+                        improved = true;
+                    }
+                }
+            }
+        }
+        improved
+    }
+}
+
+/// Search for the modules that maximize an objective
+///
+/// # Arguments
+/// * `base` - Starting fit: the hull and any modules that must stay fitted
+/// * `candidates` - Modules the character can use
+/// * `charges` - Charges available for weapons (each weapon gets the best one for the target)
+/// * `objective` - What to maximize
+/// * `activity` - The activity, for its target, incoming damage and weights
+/// * `ctx` - Skills, implants, boosts and environment used to score fits
+/// * `budget` - Step, time and ISK limits, and the caller's deadline and cancel flag
+///
+/// # Returns
+/// - The best fit found and whether a limit cut the search short, or None if the
+///   starting fit already breaks a constraint
+pub fn optimize_fit(
+    base: &FitVariant,
+    candidates: &[Module],
+    charges: &[Charge],
+    objective: Objective,
+    activity: &Activity,
+    ctx: &CalcContext,
+    budget: SearchBudget,
+) -> Option<OptimizedFit> {
+    let priced = |type_id: i64| {
+        budget
+            .cost_limit
+            .is_none_or(|limit| limit.prices.contains_key(&type_id))
+    };
+    let time_limit = Instant::now() + budget.time_limit;
+    let mut search = Search {
+        ctx,
+        objective,
+        activity,
+        layout: Layout::of(base, ctx),
+        steps_left: budget.max_steps,
+        deadline: budget
+            .deadline
+            .map_or(time_limit, |deadline| deadline.min(time_limit)),
+        cancel: budget.cancel,
+        cost_limit: budget.cost_limit,
+        truncated: false,
+    };
+    let charges: Vec<Charge> = charges
+        .iter()
+        .filter(|c| priced(c.charge_id))
+        .cloned()
+        .collect();
+
+    let candidates: Vec<Module> = candidates
+        .iter()
+        .filter(|m| m.slot.is_some_and(|slot| FITTED_SLOTS.contains(&slot)) && priced(m.module_id))
+        .map(|module| {
+            let mut module = module.clone();
+            if is_turret(&module) || is_launcher(&module) {
+                let mut trial = base.clone();
+                trial.modules.push(module.clone());
+                let index = trial.modules.len() - 1;
+                module.charge =
+                    select_best_charge(&trial, index, &charges, &activity.target, &ctx.skills)
+                        .or(module.charge);
+            }
+            module
+        })
+        .collect();
+
+    let mut fit = base.clone();
+    let mut score = search.evaluate(&fit)?;
+    loop {
+        search.construct(&mut fit, &mut score, &candidates);
+        if search.truncated
+            || !search.improve(&mut fit, &mut score, &candidates, base.modules.len())
+        {
+            break;
+        }
+    }
+    Some(OptimizedFit {
+        fit,
+        truncated: search.truncated,
+    })
+}
+
+/// Build the partial fit a search starts from: the hull with the locked modules
+///
+/// # Arguments
+/// * `ship` - The hull
+/// * `locked` - Modules that must be fitted, with their slot and optional charge
+/// * `charges` - Charges for locked weapons without one (the best for the target)
+/// * `activity` - The activity, for its target
+/// * `ctx` - Skills, implants, boosts and environment
+///
+/// # Returns
+/// - The partial fit, or why the locked modules cannot be fitted together
+pub fn locked_base(
+    ship: &Ship,
+    locked: &[LockedModule],
+    charges: &[Charge],
+    activity: &Activity,
+    ctx: &CalcContext,
+) -> Result<FitVariant, String> {
+    let mut base = FitVariant {
+        ship: ship.clone(),
+        ..Default::default()
+    };
+    for locked_module in locked {
+        let mut module = locked_module.module.clone();
+        if module.slot.is_some_and(|slot| slot != locked_module.slot) {
+            return Err(format!(
+                "{} does not fit a {:?} slot",
+                module.module_name, locked_module.slot
+            ));
+        }
+        module.slot = Some(locked_module.slot);
+        if let Some(charge) = &locked_module.charge {
+            check_charge_compatibility(&module, charge)?;
+            module.charge = Some(charge.clone());
+        }
+        base.modules.push(module);
+    }
+    for index in 0..base.modules.len() {
+        let module = &base.modules[index];
+        if module.charge.is_none() && (is_turret(module) || is_launcher(module)) {
+            base.modules[index].charge =
+                select_best_charge(&base, index, charges, &activity.target, &ctx.skills);
+        }
+    }
+
+    let infeasible = |reason: String| {
+        Err(format!(
+            "The locked modules do not fit the {}: {}",
+            ship.ship_name, reason
+        ))
+    };
+    if let Some(violation) = Layout::of(&base, ctx).violation(&base) {
+        return infeasible(violation);
+    }
+    let stats = calculate_fit_stats_with(&base, ctx);
+    for (resource, used, output) in [
+        ("CPU", stats.cpu_used, stats.cpu_output),
+        ("powergrid", stats.powergrid_used, stats.powergrid_output),
+        (
+            "calibration",
+            stats.calibration_used,
+            stats.calibration_output,
+        ),
+    ] {
+        if used > output {
+            return infeasible(format!(
+                "{:.1} {} used, {:.1} available",
+                used, resource, output
+            ));
+        }
+    }
+    Ok(base)
+}
+
+/// Where each locked module is in a fit, matched by type ID and slot
+///
+/// Each fitted module matches at most one locked module, so locking two of the same
+/// module finds two positions.
+///
+/// # Returns
+/// - One entry per locked module, in order: its index in `fit.modules`, or None if the
+///   fit does not have it
+pub fn locked_positions(fit: &FitVariant, locked: &[LockedModule]) -> Vec<Option<usize>> {
+    let mut taken = vec![false; fit.modules.len()];
+    locked
+        .iter()
+        .map(|locked_module| {
+            let index = fit.modules.iter().enumerate().position(|(index, m)| {
+                !taken[index]
+                    && m.module_id == locked_module.module.module_id
+                    && m.slot == Some(locked_module.slot)
+            })?;
+            taken[index] = true;
+            Some(index)
+        })
+        .collect()
+}
+
+/// Build one optimized fit per objective, completing a partial fit
+///
+/// Modules of `base` are kept; only the remaining slots are filled. Objectives that end
+/// up with the same modules as an earlier one are skipped. With a cost limit, each fit
+/// records what the limit cost it against the objective's fit without one. Every search
+/// runs within `budget`; fits whose search was cut short say so in their rationale.
+///
+/// # Returns
+/// - Up to one fit per `Objective`, named after the objective, with a rationale that
+///   includes what it gave up against the balanced fit
+pub fn generate_optimized_fits(
+    base: &FitVariant,
+    eligible_modules: &[Module],
+    charges: &[Charge],
+    activity: &Activity,
+    ctx: &CalcContext,
+    budget: SearchBudget,
+) -> Vec<FitVariant> {
+    let ship = &base.ship;
+    let module_names = |fit: &FitVariant| {
+        let mut names: Vec<&str> = fit.modules.iter().map(|m| m.module_name.as_str()).collect();
+        names.sort_unstable();
+        names.join(",")
+    };
+    let mut fits: Vec<FitVariant> = vec![];
+    let optimize = |objective, cost_limit| {
+        let budget = SearchBudget {
+            cost_limit,
+            ..budget
+        };
+        optimize_fit(
+            base,
+            eligible_modules,
+            charges,
+            objective,
+            activity,
+            ctx,
+            budget,
+        )
+    };
+    let optimized: Vec<(Objective, OptimizedFit)> = Objective::ALL
+        .iter()
+        .filter_map(|objective| {
+            optimize(*objective, budget.cost_limit).map(|fit| (*objective, fit))
+        })
+        .collect();
+    let balanced = optimized
+        .iter()
+        .find(|(objective, _)| *objective == Objective::Balanced)
+        .map(|(_, optimized)| &optimized.fit);
+    for (objective, optimized) in &optimized {
+        let fit = &optimized.fit;
+        if fit.modules.is_empty() || fits.iter().any(|f| module_names(f) == module_names(fit)) {
+            continue;
+        }
+        let mut fit = fit.clone();
+        let stats = calculate_fit_stats_with(&fit, ctx);
+        let mut trade_offs = vec![];
+        if let (Some(reason), Some(balanced)) = (objective.trade_off_reason(), balanced) {
+            trade_offs.extend(trade_off(balanced, &fit, reason, ctx));
+        }
+        if budget.cost_limit.is_some() {
+            if let Some(OptimizedFit { fit: unlimited, .. }) = optimize(*objective, None) {
+                fit.budget_delta = Some(StatDelta::between(
+                    &calculate_fit_stats_with(&unlimited, ctx),
+                    &stats,
+                ));
+                trade_offs.extend(trade_off(
+                    &unlimited,
+                    &fit,
+                    "to stay within the ISK budget",
+                    ctx,
+                ));
+            }
+        }
+        fit.fit_name = format!("{} {}", ship.ship_name, objective.label());
+        let mut summary = format!(
+            "Optimized for {}: {:.0} DPS, {:.0} EHP, capacitor {}",
+            objective.label().to_lowercase(),
+            stats.dps,
+            stats.ehp,
+            if stats.capacitor_stable {
+                "stable"
+            } else {
+                "unstable"
+            }
+        );
+        if optimized.truncated {
+            summary.push_str(" (the search was cut short: this is the best fit found so far)");
+        }
+        let rationale = explain_fit(&fit, summary, trade_offs, activity, ctx);
+        fit.rationale = rationale.render();
+        fit.explanation = Some(rationale);
+        fits.push(fit);
+    }
+    fits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{
+        generate_fit_variants, validate_fit, Charge, FitVariant, Module, Ship, Skill, SlotType,
+    };
+    use crate::fitting::CalcContext;
+    use crate::test_support::{
+        activity, armor_plate, attrs, hybrid_charge, large_blaster, magnetic_field_stabilizer,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_fit_optimizer() {
+        let megathron = Ship {
+            ship_id: 641,
+            ship_name: "Megathron".to_string(),
+            attributes: attrs(&[
+                ("hiSlots", 2.0),
+                ("turretSlotsLeft", 2.0),
+                ("lowSlots", 2.0),
+                ("cpuOutput", 130.0),
+                ("powerOutput", 20_000.0),
+                ("armorHP", 5_000.0),
+            ]),
+            ..Default::default()
+        };
+        let modules = vec![large_blaster(), magnetic_field_stabilizer(), armor_plate()];
+        let charges = vec![hybrid_charge("Antimatter Charge L", 10.0, 1.0)];
+        let base = FitVariant {
+            ship: megathron.clone(),
+            ..Default::default()
+        };
+        let optimize = |objective| {
+            optimize_fit(
+                &base,
+                &modules,
+                &charges,
+                objective,
+                &activity("Level 4 Missions"),
+                &CalcContext::default(),
+                SearchBudget::default(),
+            )
+            .unwrap()
+            .fit
+        };
+        let names = |fit: &FitVariant| {
+            let mut names: Vec<String> =
+                fit.modules.iter().map(|m| m.module_name.clone()).collect();
+            names.sort();
+            names
+        };
+
+        // Two blasters use 100 of the 130 tf, leaving room for one damage mod
+        let dps = optimize(Objective::Dps);
+        assert_eq!(
+            names(&dps),
+            vec![
+                "Magnetic Field Stabilizer II",
+                "Neutron Blaster Cannon II",
+                "Neutron Blaster Cannon II"
+            ]
+        );
+        assert!(dps
+            .modules
+            .iter()
+            .all(|m| m.group_id != 74 || m.charge.is_some()));
+        let tank = optimize(Objective::Tank);
+        assert_eq!(
+            names(&tank),
+            vec!["1600mm Steel Plates II", "1600mm Steel Plates II"]
+        );
+        // Identical inputs give identical fits
+        assert_eq!(
+            names(&optimize(Objective::Balanced)),
+            names(&optimize(Objective::Balanced))
+        );
+
+        // A search out of steps stops early and says so
+        let short = optimize_fit(
+            &base,
+            &modules,
+            &charges,
+            Objective::Dps,
+            &activity("Level 4 Missions"),
+            &CalcContext::default(),
+            SearchBudget {
+                max_steps: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(short.truncated);
+        assert_eq!(short.fit.modules.len(), 1);
+        // So does a cancelled one, before adding anything
+        let cancel = AtomicBool::new(true);
+        let cancelled = optimize_fit(
+            &base,
+            &modules,
+            &charges,
+            Objective::Dps,
+            &activity("Level 4 Missions"),
+            &CalcContext::default(),
+            SearchBudget {
+                cancel: Some(&cancel),
+                ..Default::default()
+            },
+        );
+        assert!(cancelled
+            .is_none_or(|optimized| optimized.truncated && optimized.fit.modules.is_empty()));
+
+        let fits = generate_fit_variants(
+            &megathron,
+            &activity("Level 4 Missions"),
+            &CalcContext::default(),
+            &modules,
+            &charges,
+            &HashMap::new(),
+            &[],
+            SearchBudget::default(),
+        )
+        .unwrap();
+        assert!(fits.iter().any(|f| f.fit_name == "Megathron Max DPS"));
+        for fit in &fits {
+            assert!(validate_fit(fit, &[])
+                .missing_requirements
+                .iter()
+                .all(|r| !r.contains("overloaded")));
+        }
+    }
+
+    #[test]
+    fn test_locked_modules() {
+        let megathron = Ship {
+            ship_id: 641,
+            ship_name: "Megathron".to_string(),
+            attributes: attrs(&[
+                ("hiSlots", 2.0),
+                ("turretSlotsLeft", 2.0),
+                ("lowSlots", 2.0),
+                ("cpuOutput", 130.0),
+                ("powerOutput", 20_000.0),
+                ("armorHP", 5_000.0),
+            ]),
+            ..Default::default()
+        };
+        let modules = vec![large_blaster(), magnetic_field_stabilizer(), armor_plate()];
+        let charges = vec![
+            hybrid_charge("Antimatter Charge L", 10.0, 1.0),
+            hybrid_charge("Iron Charge L", 4.0, 1.6),
+        ];
+        let locked = |module: Module, slot, charge: Option<Charge>| LockedModule {
+            module,
+            slot,
+            charge,
+        };
+        let generate = |locked: &[LockedModule]| {
+            generate_fit_variants(
+                &megathron,
+                &activity("Level 4 Missions"),
+                &CalcContext::default(),
+                &modules,
+                &charges,
+                &HashMap::new(),
+                locked,
+                SearchBudget::default(),
+            )
+        };
+
+        // A locked plate stays in every fit; the DPS fit adds blasters around it and has
+        // no CPU left for the damage mod
+        let fits = generate(&[locked(armor_plate(), SlotType::Low, None)]).unwrap();
+        assert!(!fits.is_empty());
+        assert!(fits
+            .iter()
+            .all(|f| f.modules[0].module_name == "1600mm Steel Plates II"));
+        let dps = fits
+            .iter()
+            .find(|f| f.fit_name == "Megathron Max DPS")
+            .unwrap();
+        let names: Vec<&str> = dps.modules.iter().map(|m| m.module_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "1600mm Steel Plates II",
+                "Neutron Blaster Cannon II",
+                "Neutron Blaster Cannon II"
+            ]
+        );
+
+        // A locked weapon keeps its charge; one without gets the best for the target (Iron
+        // reaches the 30 km mission targets)
+        let charge_of = |fit: &FitVariant| fit.modules[0].charge.clone().unwrap().charge_name;
+        let antimatter = charges[0].clone();
+        let fits = generate(&[locked(large_blaster(), SlotType::High, Some(antimatter))]).unwrap();
+        assert!(fits.iter().all(|f| charge_of(f) == "Antimatter Charge L"));
+        let fits = generate(&[locked(large_blaster(), SlotType::High, None)]).unwrap();
+        assert!(fits.iter().all(|f| charge_of(f) == "Iron Charge L"));
+
+        // Locked sets that cannot work
+        let blasters = vec![locked(large_blaster(), SlotType::High, None); 3];
+        assert_eq!(
+            generate(&blasters).unwrap_err(),
+            "The locked modules do not fit the Megathron: 3 High slot modules, 2 slots"
+        );
+        let too_much_cpu = vec![
+            locked(large_blaster(), SlotType::High, None),
+            locked(large_blaster(), SlotType::High, None),
+            locked(magnetic_field_stabilizer(), SlotType::Low, None),
+            locked(armor_plate(), SlotType::Low, None),
+        ];
+        assert_eq!(
+            generate(&too_much_cpu).unwrap_err(),
+            "The locked modules do not fit the Megathron: 150.0 CPU used, 130.0 available"
+        );
+        // The same set fits a character with CPU Management V (+25% CPU: 162.5 tf)
+        let skilled = CalcContext::from_skills(&[Skill {
+            skill_id: 3426,
+            skill_name: Some("CPU Management".to_string()),
+            active_level: 5,
+        }]);
+        let fits = generate_fit_variants(
+            &megathron,
+            &activity("Level 4 Missions"),
+            &skilled,
+            &modules,
+            &charges,
+            &HashMap::new(),
+            &too_much_cpu,
+            SearchBudget::default(),
+        )
+        .unwrap();
+        assert!(!fits.is_empty());
+        for fit in &fits {
+            assert!(locked_positions(fit, &too_much_cpu)
+                .iter()
+                .all(|position| position.is_some()));
+        }
+        assert!(generate(&[locked(armor_plate(), SlotType::High, None)]).is_err());
+        assert!(generate(&[locked(
+            armor_plate(),
+            SlotType::Low,
+            Some(charges[0].clone())
+        )])
+        .is_err());
+    }
+}
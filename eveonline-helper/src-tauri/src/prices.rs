@@ -0,0 +1,295 @@
+//! Item prices for EveOnline Helper
+//
+// Prices are ISK per unit keyed by SDE type ID. A fit's cost is its hull, modules,
+// loaded charges (one per module), drones and the implants it relies on.
+//
+// The local price table is kept in the encrypted database (`db.rs`). It is filled by:
+// - importing a CSV (`type_id,price` per line) or JSON snapshot (a type ID to price map,
+//   or the ESI market prices format)
+// - fetching the ESI market prices when online (average price, or the adjusted price
+//   for items that have none)
+// Imported prices replace stored prices of the same type IDs and keep the others.
+
+use crate::db;
+use crate::esi::FitVariant;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+const ESI_MARKET_PRICES_URL: &str =
+    "https://esi.evetech.net/latest/markets/prices/?datasource=tranquility";
+
+/// ISK price per unit, keyed by SDE type ID
+pub type PriceTable = HashMap<i64, f64>;
+
+/// Format of a price snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceFormat {
+    Csv,
+    Json,
+}
+
+/// One entry of the ESI `/markets/prices/` response
+#[derive(Debug, Clone, Deserialize)]
+struct MarketPrice {
+    type_id: i64,
+    average_price: Option<f64>,
+    adjusted_price: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PriceSnapshot {
+    Table(HashMap<String, f64>),
+    Market(Vec<MarketPrice>),
+}
+
+/// Maximum ISK a fit may cost
+#[derive(Debug, Clone, Copy)]
+pub struct CostLimit<'a> {
+    pub max_cost: f64,
+    pub prices: &'a PriceTable,
+}
+
+impl CostLimit<'_> {
+    /// Whether a fit is within the limit (items without a price are not affordable)
+    pub fn allows(&self, fit: &FitVariant) -> bool {
+        fit_cost(fit, self.prices).is_some_and(|cost| cost <= self.max_cost)
+    }
+}
+
+/// Type IDs, names and quantities of everything in a fit
+pub fn fit_items(fit: &FitVariant) -> Vec<(i64, &str, u32)> {
+    let mut items = vec![(fit.ship.ship_id, fit.ship.ship_name.as_str(), 1)];
+    for module in &fit.modules {
+        items.push((module.module_id, module.module_name.as_str(), 1));
+        if let Some(charge) = &module.charge {
+            items.push((charge.charge_id, charge.charge_name.as_str(), 1));
+        }
+    }
+    for drone in &fit.drones {
+        items.push((drone.drone_id, drone.drone_name.as_str(), drone.quantity));
+    }
+    for implant in &fit.implants {
+        items.push((implant.implant_id, implant.implant_name.as_str(), 1));
+    }
+    items
+}
+
+/// Total ISK cost of a fit, or None if any item has no price
+pub fn fit_cost(fit: &FitVariant, prices: &PriceTable) -> Option<f64> {
+    fit_items(fit)
+        .iter()
+        .map(|(type_id, _, quantity)| prices.get(type_id).map(|price| price * *quantity as f64))
+        .sum()
+}
+
+fn market_price_table(entries: Vec<MarketPrice>) -> PriceTable {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let price = entry.average_price.or(entry.adjusted_price)?;
+            Some((entry.type_id, price))
+        })
+        .collect()
+}
+
+/// Parse a CSV price snapshot: `type_id,price` per line, an optional header line and any
+/// further columns are ignored
+pub fn parse_price_csv(contents: &str) -> Result<PriceTable, String> {
+    let mut prices = PriceTable::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut columns = line.split(',').map(str::trim);
+        let (type_id, price) = (columns.next().unwrap_or(""), columns.next().unwrap_or(""));
+        let Ok(type_id) = type_id.parse::<i64>() else {
+            if index == 0 {
+                continue; // header
+            }
+            return Err(format!("Invalid type ID on line {}: {}", index + 1, line));
+        };
+        let price = price
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid price on line {}: {}", index + 1, line))?;
+        prices.insert(type_id, price);
+    }
+    Ok(prices)
+}
+
+/// Parse a JSON price snapshot: a type ID to price map, or the ESI market prices format
+pub fn parse_price_json(contents: &str) -> Result<PriceTable, String> {
+    let snapshot: PriceSnapshot = serde_json::from_str(contents)
+        .map_err(|e| format!("Failed to parse price snapshot: {}", e))?;
+    match snapshot {
+        PriceSnapshot::Table(table) => table
+            .into_iter()
+            .map(|(type_id, price)| {
+                type_id
+                    .parse::<i64>()
+                    .map(|type_id| (type_id, price))
+                    .map_err(|_| format!("Invalid type ID in price snapshot: {}", type_id))
+            })
+            .collect(),
+        PriceSnapshot::Market(entries) => Ok(market_price_table(entries)),
+    }
+}
+
+/// Fetch current prices from the ESI market (no login needed)
+pub async fn fetch_market_prices() -> Result<PriceTable, String> {
+    let resp = Client::new()
+        .get(ESI_MARKET_PRICES_URL)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch market prices: {}", e))?;
+    let entries = resp
+        .json::<Vec<MarketPrice>>()
+        .await
+        .map_err(|e| format!("Failed to parse market prices: {}", e))?;
+    Ok(market_price_table(entries))
+}
+
+/// Load the local price table (empty if no prices were stored yet)
+pub async fn load_prices(pool: &SqlitePool) -> Result<PriceTable, String> {
+    let rows = db::load_prices(pool)
+        .await
+        .map_err(|e| format!("Failed to load prices: {}", e))?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Merge prices into the local price table
+///
+/// # Returns
+/// - The number of type IDs now priced
+pub async fn store_prices(pool: &SqlitePool, prices: PriceTable) -> Result<usize, String> {
+    let rows: Vec<(i64, f64)> = prices.into_iter().collect();
+    db::upsert_prices(pool, &rows)
+        .await
+        .map_err(|e| format!("Failed to store prices: {}", e))?;
+    Ok(load_prices(pool).await?.len())
+}
+
+/// Import a CSV or JSON price snapshot into the local price table
+pub async fn import_prices(
+    pool: &SqlitePool,
+    contents: &str,
+    format: PriceFormat,
+) -> Result<usize, String> {
+    let prices = match format {
+        PriceFormat::Csv => parse_price_csv(contents)?,
+        PriceFormat::Json => parse_price_json(contents)?,
+    };
+    store_prices(pool, prices).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{generate_fit_variants, Ship};
+    use crate::fitting::CalcContext;
+    use crate::optimizer::SearchBudget;
+    use crate::test_support::{
+        activity, armor_plate, attrs, hybrid_charge, large_blaster, magnetic_field_stabilizer,
+    };
+
+    #[test]
+    fn test_isk_budget_fits() {
+        let prices = parse_price_csv("type_id,price\n641,200000000\n3186, 3000000\n").unwrap();
+        assert_eq!(prices.get(&3186), Some(&3_000_000.0));
+        assert!(parse_price_csv("641,200000000\nabc,1\n").is_err());
+        let market = parse_price_json(
+            r#"[{"type_id": 10190, "average_price": 1000000.0, "adjusted_price": 900000.0},
+                {"type_id": 3170, "adjusted_price": 1000000.0},
+                {"type_id": 20353}]"#,
+        )
+        .unwrap();
+        assert_eq!(market.get(&10190), Some(&1_000_000.0));
+        assert_eq!(market.get(&3170), Some(&1_000_000.0));
+        assert!(!market.contains_key(&20353));
+        let mut prices: PriceTable = prices.into_iter().chain(market).collect();
+        prices.extend(parse_price_json(r#"{"0": 100.0}"#).unwrap());
+
+        let megathron = Ship {
+            ship_id: 641,
+            ship_name: "Megathron".to_string(),
+            attributes: attrs(&[
+                ("hiSlots", 2.0),
+                ("turretSlotsLeft", 2.0),
+                ("lowSlots", 2.0),
+                ("cpuOutput", 130.0),
+                ("powerOutput", 20_000.0),
+                ("armorHP", 5_000.0),
+            ]),
+            ..Default::default()
+        };
+        let mut t1_blaster = large_blaster();
+        t1_blaster.module_id = 3170;
+        t1_blaster.module_name = "Neutron Blaster Cannon I".to_string();
+        t1_blaster
+            .attributes
+            .insert("damageMultiplier".to_string(), 3.0);
+        // The armor plate has no price, so it cannot be part of a budget fit
+        let modules = vec![
+            large_blaster(),
+            t1_blaster,
+            magnetic_field_stabilizer(),
+            armor_plate(),
+        ];
+        let charges = vec![hybrid_charge("Antimatter Charge L", 10.0, 1.0)];
+        let max_cost = 204_000_000.0;
+        let fits = generate_fit_variants(
+            &megathron,
+            &activity("Level 4 Missions"),
+            &CalcContext::default(),
+            &modules,
+            &charges,
+            &prices,
+            &[],
+            SearchBudget {
+                cost_limit: Some(CostLimit {
+                    max_cost,
+                    prices: &prices,
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(!fits.is_empty());
+        for fit in &fits {
+            assert!(fit.cost.is_some_and(|cost| cost <= max_cost));
+            assert_eq!(fit_cost(fit, &prices), fit.cost);
+            assert!(fit.modules.iter().all(|m| m.module_id != 20353));
+            assert!(fit.budget_delta.is_some());
+        }
+        // Two T2 blasters are out of budget, so the DPS fit loses damage
+        let dps = fits
+            .iter()
+            .find(|f| f.fit_name == "Megathron Max DPS")
+            .unwrap();
+        assert!(dps.budget_delta.as_ref().unwrap().dps < 0.0);
+        assert!(dps.modules.iter().filter(|m| m.module_id == 3186).count() < 2);
+
+        // Without a budget, fits still report their cost when everything is priced
+        let unlimited = generate_fit_variants(
+            &megathron,
+            &activity("Level 4 Missions"),
+            &CalcContext::default(),
+            &modules,
+            &charges,
+            &prices,
+            &[],
+            SearchBudget::default(),
+        )
+        .unwrap();
+        assert!(unlimited.iter().all(|f| f.budget_delta.is_none()));
+        let unlimited_dps = unlimited
+            .iter()
+            .find(|f| f.fit_name == "Megathron Max DPS")
+            .unwrap();
+        assert_eq!(unlimited_dps.cost, Some(207_000_200.0));
+    }
+}
@@ -0,0 +1,301 @@
+//! Cross-ship fit ranking for EveOnline Helper
+//
+// Scores every fit for an activity on the same scale, whatever its hull, from four
+// components between 0 and 1:
+// - effectiveness: the activity score (`Activity::score`) relative to the best fit
+//   ranked, as the ratio of their weighted stat products (the best fit gets 1)
+// - safety: the safety score from `safety::evaluate_safety` (EHP and sustained tank
+//   against the incoming DPS, cap stability, align time)
+// - cost: the cheapest priced fit's cost divided by this fit's (0.5 if unpriced)
+// - skill readiness: 1 / (1 + days of training the fit still needs)
+// The total is their weighted sum on a 0-100 scale. Ships are ranked by their best fit.
+
+use crate::activity::Activity;
+use crate::esi::{generate_skill_plan_for_fit, FitVariant};
+use crate::fitting::{calculate_fit_stats_with, CalcContext, FitStats};
+use crate::safety::evaluate_safety;
+use crate::training::{fit_skill_ranks, plan_training_seconds, DEFAULT_SP_PER_MINUTE};
+use serde::{Deserialize, Serialize};
+
+/// Cost component of a fit without a price
+const UNKNOWN_COST_SCORE: f64 = 0.5;
+
+/// How much each score component counts
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RankingWeights {
+    pub effectiveness: f64,
+    pub safety: f64,
+    pub cost: f64,
+    pub readiness: f64,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        RankingWeights {
+            effectiveness: 0.55,
+            safety: 0.2,
+            cost: 0.1,
+            readiness: 0.15,
+        }
+    }
+}
+
+/// A fit's score with its components (each 0-1)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ScoreBreakdown {
+    pub effectiveness: f64,
+    pub safety: f64,
+    pub cost: f64,
+    pub readiness: f64,
+    /// Weighted sum of the components, 0-100
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScoredFit {
+    pub fit: FitVariant,
+    pub score: ScoreBreakdown,
+}
+
+/// A ship's place in the ranking, with its fits best first
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShipRanking {
+    /// 1 for the best ship
+    pub rank: usize,
+    pub ship_id: i64,
+    pub ship_name: String,
+    /// Score of the ship's best fit
+    pub score: ScoreBreakdown,
+    pub fits: Vec<ScoredFit>,
+}
+
+/// Score fits for an activity, on one scale across hulls
+///
+/// # Arguments
+/// * `fits` - Fits of any hulls (with stats, or they are calculated with `ctx`)
+/// * `activity` - The activity the fits are for
+/// * `ctx` - The character's skills and other context
+/// * `weights` - How much each component counts
+///
+/// # Returns
+/// - One score per fit, in the same order
+pub fn score_fits(
+    fits: &[FitVariant],
+    activity: &Activity,
+    ctx: &CalcContext,
+    weights: &RankingWeights,
+) -> Vec<ScoreBreakdown> {
+    let stats: Vec<FitStats> = fits
+        .iter()
+        .map(|fit| {
+            fit.stats
+                .clone()
+                .unwrap_or_else(|| calculate_fit_stats_with(fit, ctx))
+        })
+        .collect();
+    let activity_scores: Vec<f64> = stats.iter().map(|s| activity.score(s)).collect();
+    let best_activity_score = activity_scores
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let cheapest = fits
+        .iter()
+        .filter_map(|fit| fit.cost)
+        .filter(|cost| *cost > 0.0)
+        .fold(f64::INFINITY, f64::min);
+
+    fits.iter()
+        .zip(&stats)
+        .zip(&activity_scores)
+        .map(|((fit, stats), activity_score)| {
+            let effectiveness = (activity_score - best_activity_score).exp();
+            let safety = match &fit.safety {
+                Some(report) => report.score,
+                None => evaluate_safety(fit, stats, activity, ctx).score,
+            };
+            let cost = match fit.cost {
+                Some(cost) if cost > 0.0 => cheapest / cost,
+                _ => UNKNOWN_COST_SCORE,
+            };
+            let plan = generate_skill_plan_for_fit(fit, &ctx.skills);
+            let training_days =
+                plan_training_seconds(&plan, &fit_skill_ranks(fit), DEFAULT_SP_PER_MINUTE)
+                    / 86_400.0;
+            let readiness = 1.0 / (1.0 + training_days);
+            let weight_sum =
+                weights.effectiveness + weights.safety + weights.cost + weights.readiness;
+            let total = if weight_sum > 0.0 {
+                100.0
+                    * (weights.effectiveness * effectiveness
+                        + weights.safety * safety
+                        + weights.cost * cost
+                        + weights.readiness * readiness)
+                    / weight_sum
+            } else {
+                0.0
+            };
+            ScoreBreakdown {
+                effectiveness,
+                safety,
+                cost,
+                readiness,
+                total,
+            }
+        })
+        .collect()
+}
+
+/// Rank ships by their best fit for an activity
+///
+/// # Arguments
+/// * `fits` - Fits of any hulls
+/// * `activity` - The activity the fits are for
+/// * `ctx` - The character's skills and other context
+/// * `weights` - How much each score component counts
+/// * `top_n` - Number of ships to return
+///
+/// # Returns
+/// - The best `top_n` ships, each with all its fits and their scores, best first
+pub fn rank_ships(
+    fits: Vec<FitVariant>,
+    activity: &Activity,
+    ctx: &CalcContext,
+    weights: &RankingWeights,
+    top_n: usize,
+) -> Vec<ShipRanking> {
+    let scores = score_fits(&fits, activity, ctx, weights);
+    let mut ships: Vec<ShipRanking> = vec![];
+    for (fit, score) in fits.into_iter().zip(scores) {
+        let scored = ScoredFit { fit, score };
+        match ships
+            .iter_mut()
+            .find(|s| s.ship_id == scored.fit.ship.ship_id)
+        {
+            Some(ship) => ship.fits.push(scored),
+            None => ships.push(ShipRanking {
+                rank: 0,
+                ship_id: scored.fit.ship.ship_id,
+                ship_name: scored.fit.ship.ship_name.clone(),
+                score: ScoreBreakdown::default(),
+                fits: vec![scored],
+            }),
+        }
+    }
+    for ship in &mut ships {
+        ship.fits
+            .sort_by(|a, b| b.score.total.total_cmp(&a.score.total));
+        ship.score = ship.fits[0].score.clone();
+    }
+    ships.sort_by(|a, b| b.score.total.total_cmp(&a.score.total));
+    ships.truncate(top_n);
+    for (index, ship) in ships.iter_mut().enumerate() {
+        ship.rank = index + 1;
+    }
+    ships
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{FitVariant, Ship, Skill};
+    use crate::fitting::CalcContext;
+    use crate::test_support::{
+        activity, attrs, hybrid_charge, large_blaster, magnetic_field_stabilizer,
+    };
+
+    #[test]
+    fn test_cross_ship_ranking() {
+        let hull = |ship_id, name: &str| Ship {
+            ship_id,
+            ship_name: name.to_string(),
+            attributes: attrs(&[("armorHP", 5_000.0)]),
+            ..Default::default()
+        };
+        let mut blaster = large_blaster();
+        blaster.charge = Some(hybrid_charge("Antimatter Charge L", 10.0, 1.0));
+        let drake = FitVariant {
+            fit_name: "Drake Max DPS".to_string(),
+            ship: hull(24698, "Drake"),
+            modules: vec![
+                blaster.clone(),
+                blaster.clone(),
+                magnetic_field_stabilizer(),
+            ],
+            cost: Some(40_000_000.0),
+            ..Default::default()
+        };
+        let ferox = FitVariant {
+            fit_name: "Ferox Max DPS".to_string(),
+            ship: hull(16227, "Ferox"),
+            modules: vec![blaster.clone(), blaster.clone()],
+            cost: Some(30_000_000.0),
+            ..Default::default()
+        };
+        // Same hull as the Drake fit, but needs training and has no price
+        let mut untrained_blaster = blaster.clone();
+        untrained_blaster
+            .required_skills
+            .push((20448, "Capital Hybrid Turret".to_string(), 5));
+        let drake_untrained = FitVariant {
+            fit_name: "Drake Untrained".to_string(),
+            modules: vec![untrained_blaster],
+            cost: None,
+            ..drake.clone()
+        };
+        let activity = activity("Level 4 Missions");
+        let ctx = CalcContext {
+            skills: vec![Skill {
+                skill_id: 3307,
+                skill_name: Some("Large Hybrid Turret".to_string()),
+                active_level: 5,
+            }],
+            ..Default::default()
+        };
+        let weights = RankingWeights::default();
+
+        let ranking = rank_ships(
+            vec![ferox, drake_untrained, drake],
+            &activity,
+            &ctx,
+            &weights,
+            5,
+        );
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(
+            (ranking[0].rank, ranking[0].ship_name.as_str()),
+            (1, "Drake")
+        );
+        assert_eq!(ranking[1].ship_name, "Ferox");
+
+        // The Drake fit does the most damage, the Ferox fit is cheaper
+        let drake_score = &ranking[0].score;
+        let ferox_score = &ranking[1].score;
+        assert_eq!(ranking[0].fits[0].fit.fit_name, "Drake Max DPS");
+        assert!((drake_score.effectiveness - 1.0).abs() < 1e-9);
+        assert!(ferox_score.effectiveness < 1.0);
+        assert!((drake_score.cost - 0.75).abs() < 1e-9);
+        assert!((ferox_score.cost - 1.0).abs() < 1e-9);
+        let expected_total = 100.0
+            * (0.55 * drake_score.effectiveness
+                + 0.2 * drake_score.safety
+                + 0.1 * drake_score.cost
+                + 0.15 * drake_score.readiness);
+        assert!((drake_score.total - expected_total).abs() < 1e-9);
+        let untrained = &ranking[0].fits[1].score;
+        assert!(untrained.readiness < drake_score.readiness);
+        assert_eq!(untrained.cost, 0.5);
+
+        let top = rank_ships(
+            ranking
+                .iter()
+                .flat_map(|s| s.fits.iter().map(|f| f.fit.clone()))
+                .collect(),
+            &activity,
+            &ctx,
+            &weights,
+            1,
+        );
+        assert_eq!(top.len(), 1);
+    }
+}
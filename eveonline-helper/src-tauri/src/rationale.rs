@@ -0,0 +1,506 @@
+//! Fit rationale for EveOnline Helper
+//
+// Explains a recommended fit from its calculated stats:
+// - hull: what each hull bonus adds, measured by calculating the fit without it, and
+//   whether that stat counts for the activity
+// - modules: what each fitted module type adds, measured by removing all copies of it
+// - trade-offs: modules given up against another fit (e.g. the balanced fit, or the fit
+//   without an ISK budget) or changed to make the fit flyable, with their stat cost
+// A contribution is the share of the final stat the bonus or module provides (e.g. a
+// damage mod giving 15% of the fit's DPS); the stat with the largest share decides
+// what the bonus or module is "for".
+//
+// The typed sections are kept on the fit for the frontend; `Rationale::render` turns
+// them into the English text stored in `FitVariant::rationale`.
+
+use crate::activity::Activity;
+use crate::esi::{FitChange, FitVariant, Module};
+use crate::ewar::tackle_score;
+use crate::fitting::{calculate_fit_stats_with, CalcContext, FitStats, Operation, StatDelta};
+use serde::{Deserialize, Serialize};
+
+/// Smallest share of a stat that counts as a contribution
+const MIN_SHARE: f64 = 0.01;
+
+/// What a hull bonus or module improves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Contribution {
+    Damage,
+    Tank,
+    Capacitor,
+    Mobility,
+    Targeting,
+    Mining,
+    Tackle,
+    Cargo,
+    Fitting,
+}
+
+impl Contribution {
+    pub fn label(self) -> &'static str {
+        match self {
+            Contribution::Damage => "damage",
+            Contribution::Tank => "tank",
+            Contribution::Capacitor => "capacitor",
+            Contribution::Mobility => "mobility",
+            Contribution::Targeting => "targeting",
+            Contribution::Mining => "mining yield",
+            Contribution::Tackle => "tackle",
+            Contribution::Cargo => "cargo space",
+            Contribution::Fitting => "fitting room",
+        }
+    }
+
+    /// Whether the activity weights the stat (capacitor, targeting and fitting only
+    /// support the others)
+    fn counts_for(self, activity: &Activity) -> bool {
+        let w = &activity.weights;
+        let weight = match self {
+            Contribution::Damage => w.dps,
+            Contribution::Tank => w.tank,
+            Contribution::Mobility => w.agility,
+            Contribution::Mining => w.mining,
+            Contribution::Tackle => w.tackle,
+            Contribution::Cargo => w.cargo,
+            Contribution::Capacitor | Contribution::Targeting | Contribution::Fitting => 0.0,
+        };
+        weight > 0.0
+    }
+}
+
+/// Why the hull suits the activity: one of its bonuses
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BonusReason {
+    /// SDE attribute the bonus changes
+    pub attribute: String,
+    pub operation: Operation,
+    /// Bonus value (per skill level unless it is a role bonus)
+    pub value: f64,
+    pub per_level: bool,
+    pub contribution: Contribution,
+    /// Share of the fit's stat the bonus provides (0-1)
+    pub share: f64,
+    /// Whether the activity values the stat
+    pub counts_for_activity: bool,
+}
+
+/// Why a module was chosen
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModuleReason {
+    pub module_name: String,
+    /// Number of copies fitted
+    pub count: usize,
+    pub contribution: Contribution,
+    /// Share of the fit's stat all copies provide (0-1)
+    pub share: f64,
+    pub counts_for_activity: bool,
+}
+
+/// Structured explanation of a fit
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Rationale {
+    /// What the fit was built for and its headline stats
+    pub summary: String,
+    pub ship_name: String,
+    pub hull: Vec<BonusReason>,
+    pub modules: Vec<ModuleReason>,
+    /// Modules given up or changed, with their stat cost
+    pub trade_offs: Vec<FitChange>,
+}
+
+/// Share of a stat (higher is better) that `with` has over `without`
+fn share(with: f64, without: f64) -> f64 {
+    if with > 0.0 {
+        (with - without) / with
+    } else {
+        0.0
+    }
+}
+
+/// The stat a change improves most, and by what share
+fn main_contribution(with: &FitStats, without: &FitStats) -> Option<(Contribution, f64)> {
+    let reps = |s: &FitStats| s.shield_boost_rate + s.armor_repair_rate + s.passive_shield_regen;
+    let align_share = if without.align_time > 0.0 {
+        (without.align_time - with.align_time) / without.align_time
+    } else {
+        0.0
+    };
+    [
+        (Contribution::Damage, share(with.dps, without.dps)),
+        (
+            Contribution::Tank,
+            share(with.ehp, without.ehp).max(share(reps(with), reps(without))),
+        ),
+        (
+            Contribution::Capacitor,
+            share(with.capacitor_recharge, without.capacitor_recharge)
+                .max(share(with.capacitor_capacity, without.capacitor_capacity)),
+        ),
+        (
+            Contribution::Mobility,
+            share(with.max_velocity, without.max_velocity).max(align_share),
+        ),
+        (
+            Contribution::Targeting,
+            share(with.max_target_range, without.max_target_range)
+                .max(share(with.scan_resolution, without.scan_resolution)),
+        ),
+        (
+            Contribution::Mining,
+            share(with.mining_yield, without.mining_yield),
+        ),
+        (
+            Contribution::Tackle,
+            share(tackle_score(with), tackle_score(without)),
+        ),
+        (
+            Contribution::Cargo,
+            share(with.cargo_capacity, without.cargo_capacity),
+        ),
+        (
+            Contribution::Fitting,
+            share(with.cpu_output, without.cpu_output)
+                .max(share(with.powergrid_output, without.powergrid_output)),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, share)| *share >= MIN_SHARE)
+    .reduce(|best, c| if c.1 > best.1 { c } else { best })
+}
+
+/// Names of modules in `from` that are not matched by a module in `to`, with counts
+fn unmatched_modules(from: &[Module], to: &[Module]) -> Vec<(String, usize)> {
+    let mut remaining: Vec<&str> = to.iter().map(|m| m.module_name.as_str()).collect();
+    let mut unmatched: Vec<(String, usize)> = vec![];
+    for module in from {
+        if let Some(index) = remaining.iter().position(|n| *n == module.module_name) {
+            remaining.remove(index);
+            continue;
+        }
+        match unmatched.iter_mut().find(|(n, _)| *n == module.module_name) {
+            Some((_, count)) => *count += 1,
+            None => unmatched.push((module.module_name.clone(), 1)),
+        }
+    }
+    unmatched
+}
+
+/// "A, B and 2x C"
+fn module_list(modules: &[(String, usize)]) -> String {
+    let mut names: Vec<String> = modules
+        .iter()
+        .map(|(name, count)| match count {
+            1 => name.clone(),
+            _ => format!("{}x {}", count, name),
+        })
+        .collect();
+    match names.pop() {
+        Some(last) if !names.is_empty() => format!("{} and {}", names.join(", "), last),
+        Some(last) => last,
+        None => String::new(),
+    }
+}
+
+fn percent(share: f64) -> String {
+    format!("{:.0}%", share * 100.0)
+}
+
+/// The modules a fit gave up against another fit for the same hull
+///
+/// # Arguments
+/// * `reference` - The fit compared against (e.g. the balanced fit)
+/// * `fit` - The fit being explained
+/// * `reason` - Why, completing "Dropped X ..." (e.g. "for cap stability")
+/// * `ctx` - Context the stats are calculated with
+///
+/// # Returns
+/// - The trade-off with its stat cost, or None if both fits have the same modules
+pub fn trade_off(
+    reference: &FitVariant,
+    fit: &FitVariant,
+    reason: &str,
+    ctx: &CalcContext,
+) -> Option<FitChange> {
+    let dropped = unmatched_modules(&reference.modules, &fit.modules);
+    let added = unmatched_modules(&fit.modules, &reference.modules);
+    let description = match (dropped.is_empty(), added.is_empty()) {
+        (true, true) => return None,
+        (false, true) => format!("Dropped {} {}", module_list(&dropped), reason),
+        (true, false) => format!("Added {} {}", module_list(&added), reason),
+        (false, false) => format!(
+            "Swapped {} for {} {}",
+            module_list(&dropped),
+            module_list(&added),
+            reason
+        ),
+    };
+    Some(FitChange {
+        description,
+        stat_delta: StatDelta::between(
+            &calculate_fit_stats_with(reference, ctx),
+            &calculate_fit_stats_with(fit, ctx),
+        ),
+    })
+}
+
+/// Explain a fit from its calculated stats
+///
+/// # Arguments
+/// * `fit` - The fit to explain
+/// * `summary` - What the fit was built for (first sentence of the text)
+/// * `trade_offs` - Modules given up or changed, e.g. from `trade_off`
+/// * `activity` - The activity, to tell which stats count
+/// * `ctx` - Context the stats are calculated with
+///
+/// # Returns
+/// - Hull bonus and module reasons (those that contribute to a stat) and the trade-offs
+pub fn explain_fit(
+    fit: &FitVariant,
+    summary: String,
+    trade_offs: Vec<FitChange>,
+    activity: &Activity,
+    ctx: &CalcContext,
+) -> Rationale {
+    let stats = calculate_fit_stats_with(fit, ctx);
+
+    let mut hull = vec![];
+    for (index, bonus) in fit.ship.bonuses.iter().enumerate() {
+        let mut without = fit.clone();
+        without.ship.bonuses.remove(index);
+        let Some((contribution, share)) =
+            main_contribution(&stats, &calculate_fit_stats_with(&without, ctx))
+        else {
+            continue;
+        };
+        hull.push(BonusReason {
+            attribute: bonus.modifier.attribute.clone(),
+            operation: bonus.modifier.operation,
+            value: bonus.modifier.value,
+            per_level: bonus.skill_id.is_some(),
+            contribution,
+            share,
+            counts_for_activity: contribution.counts_for(activity),
+        });
+    }
+
+    let mut modules: Vec<ModuleReason> = vec![];
+    for module in &fit.modules {
+        if modules.iter().any(|m| m.module_name == module.module_name) {
+            continue;
+        }
+        let mut without = fit.clone();
+        without
+            .modules
+            .retain(|m| m.module_name != module.module_name);
+        let Some((contribution, share)) =
+            main_contribution(&stats, &calculate_fit_stats_with(&without, ctx))
+        else {
+            continue;
+        };
+        modules.push(ModuleReason {
+            module_name: module.module_name.clone(),
+            count: fit.modules.len() - without.modules.len(),
+            contribution,
+            share,
+            counts_for_activity: contribution.counts_for(activity),
+        });
+    }
+
+    Rationale {
+        summary,
+        ship_name: fit.ship.ship_name.clone(),
+        hull,
+        modules,
+        trade_offs,
+    }
+}
+
+impl Rationale {
+    /// The rationale as English text
+    pub fn render(&self) -> String {
+        let mut sentences = vec![];
+        if !self.summary.is_empty() {
+            sentences.push(self.summary.clone());
+        }
+        if !self.hull.is_empty() {
+            let bonuses: Vec<String> = self
+                .hull
+                .iter()
+                .map(|b| {
+                    let value = match b.operation {
+                        Operation::PostPercent => format!("{:+}% {}", b.value, b.attribute),
+                        Operation::PostMul => format!("x{} {}", b.value, b.attribute),
+                        Operation::ModAdd => format!("{:+} {}", b.value, b.attribute),
+                        Operation::PostAssign => format!("{} = {}", b.attribute, b.value),
+                    };
+                    let scaling = if b.per_level {
+                        "per level"
+                    } else {
+                        "role bonus"
+                    };
+                    format!(
+                        "{} of its {} ({} {})",
+                        percent(b.share),
+                        b.contribution.label(),
+                        value,
+                        scaling
+                    )
+                })
+                .collect();
+            let suits = self.hull.iter().any(|b| b.counts_for_activity);
+            sentences.push(format!(
+                "The {} {} {}",
+                self.ship_name,
+                if suits {
+                    "suits the activity: its bonuses provide"
+                } else {
+                    "bonuses provide"
+                },
+                bonuses.join(", ")
+            ));
+        }
+        if !self.modules.is_empty() {
+            let reasons: Vec<String> = self
+                .modules
+                .iter()
+                .map(|m| {
+                    let name = match m.count {
+                        1 => m.module_name.clone(),
+                        n => format!("{}x {}", n, m.module_name),
+                    };
+                    format!(
+                        "{} ({} of {})",
+                        name,
+                        percent(m.share),
+                        m.contribution.label()
+                    )
+                })
+                .collect();
+            sentences.push(format!("Key modules: {}", reasons.join(", ")));
+        }
+        for trade_off in &self.trade_offs {
+            sentences.push(trade_off.description.clone());
+        }
+        sentences
+            .iter()
+            .map(|s| format!("{}.", s))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{generate_fit_variants, FitVariant, HullBonus, Ship};
+    use crate::fitting::{CalcContext, Modifier, ModifierTarget};
+    use crate::optimizer::SearchBudget;
+    use crate::test_support::{
+        activity, armor_plate, attrs, hybrid_charge, large_blaster, magnetic_field_stabilizer,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_fit_rationale() {
+        let megathron = Ship {
+            ship_id: 641,
+            ship_name: "Megathron".to_string(),
+            attributes: attrs(&[
+                ("hiSlots", 2.0),
+                ("turretSlotsLeft", 2.0),
+                ("lowSlots", 2.0),
+                ("cpuOutput", 130.0),
+                ("powerOutput", 20_000.0),
+                ("armorHP", 5_000.0),
+            ]),
+            bonuses: vec![
+                // Role bonus to blaster damage, and one that needs a skill the character lacks
+                HullBonus {
+                    skill_id: None,
+                    modifier: Modifier::percent(
+                        ModifierTarget::ModuleGroup(74),
+                        "damageMultiplier",
+                        25.0,
+                    ),
+                },
+                HullBonus {
+                    skill_id: Some(3335),
+                    modifier: Modifier::percent(ModifierTarget::Ship, "armorHP", 5.0),
+                },
+            ],
+            ..Default::default()
+        };
+        let modules = vec![large_blaster(), magnetic_field_stabilizer(), armor_plate()];
+        let charges = vec![hybrid_charge("Antimatter Charge L", 10.0, 1.0)];
+        let fits = generate_fit_variants(
+            &megathron,
+            &activity("Level 4 Missions"),
+            &CalcContext::default(),
+            &modules,
+            &charges,
+            &HashMap::new(),
+            &[],
+            SearchBudget::default(),
+        )
+        .unwrap();
+        let dps = fits
+            .iter()
+            .find(|f| f.fit_name == "Megathron Max DPS")
+            .unwrap();
+        let rationale = dps.explanation.as_ref().unwrap();
+        assert_eq!(rationale.hull.len(), 1);
+        let bonus = &rationale.hull[0];
+        assert_eq!(bonus.contribution, Contribution::Damage);
+        assert!(bonus.counts_for_activity && !bonus.per_level);
+        assert!((bonus.share - 0.2).abs() < 1e-6);
+        let blasters = rationale
+            .modules
+            .iter()
+            .find(|m| m.module_name == "Neutron Blaster Cannon II")
+            .unwrap();
+        assert_eq!(blasters.count, 2);
+        assert_eq!(blasters.contribution, Contribution::Damage);
+        assert!((blasters.share - 1.0).abs() < 1e-9);
+        assert_eq!(dps.rationale, rationale.render());
+        assert!(dps
+            .rationale
+            .contains("Key modules: 2x Neutron Blaster Cannon II (100% of damage)"));
+        assert!(dps.rationale.contains("The Megathron suits the activity"));
+
+        // Giving up the damage mod shows up as a trade-off with its stat cost
+        let mut blaster = large_blaster();
+        blaster.charge = Some(charges[0].clone());
+        let balanced = FitVariant {
+            ship: megathron.clone(),
+            modules: vec![
+                blaster.clone(),
+                blaster.clone(),
+                magnetic_field_stabilizer(),
+            ],
+            ..Default::default()
+        };
+        let cap_stable = FitVariant {
+            modules: vec![blaster.clone(), blaster, armor_plate()],
+            ..balanced.clone()
+        };
+        let ctx = CalcContext::default();
+        let change = trade_off(&balanced, &cap_stable, "for cap stability", &ctx).unwrap();
+        assert_eq!(
+            change.description,
+            "Swapped Magnetic Field Stabilizer II for 1600mm Steel Plates II for cap stability"
+        );
+        assert!(change.stat_delta.dps < 0.0 && change.stat_delta.ehp > 0.0);
+        assert!(trade_off(&balanced, &balanced, "for cap stability", &ctx).is_none());
+        let rationale = explain_fit(
+            &cap_stable,
+            "Cap stable".to_string(),
+            vec![change],
+            &activity("Level 4 Missions"),
+            &ctx,
+        );
+        assert!(rationale.render().ends_with(
+            "Swapped Magnetic Field Stabilizer II for 1600mm Steel Plates II for cap stability."
+        ));
+    }
+}
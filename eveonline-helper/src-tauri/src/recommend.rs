@@ -0,0 +1,522 @@
+//! Fit recommendation search for EveOnline Helper
+//
+// Recommendations are built ship by ship: fit variants for the activity (around the
+// player's locked modules, on hulls they fit), the best ammo, validation, alternatives
+// for fits the character cannot fly (locked modules stay), then stats, cost and owned
+// items, and the safety report (fits below the requested rating are dropped).
+// Ships are handed out to a pool of worker threads (one per CPU) and each
+// ship's fits are reported as soon as they are done, so the frontend can show results
+// while the search goes on (the Tauri command emits them as events).
+//
+// When every ship is done, the fits are ranked across hulls (`ranking::rank_ships`) and
+// the top ships are returned with the search summary.
+//
+// A search stops handing out ships when its deadline passes or it is cancelled (e.g.
+// the user picked another activity). The optimizer runs of ships already being fitted
+// get the same deadline and cancel flag and stop at their next step; fits cut short by
+// the deadline are reported with a note in their rationale, and after a cancellation
+// they are not reported at all.
+
+use crate::activity::{Activity, ActivityKind, PvpRole};
+use crate::assets::{self, AssetSelection, OwnedItems};
+use crate::charges::load_best_charges;
+use crate::esi::{
+    generate_fit_variants, match_skills_to_ships_and_modules, suggest_alternative_fits,
+    validate_fit_with, Charge, FitVariant, Module, Ship, Skill,
+};
+use crate::ewar::tackle_score;
+use crate::fitting::{calculate_fit_stats_with, CalcContext};
+use crate::fleet::FleetBoost;
+use crate::implants::{Booster, Implant};
+use crate::npc::{resolve_mission_enemy, NpcCorporation};
+use crate::optimizer::{locked_positions, LockedModule, SearchBudget};
+use crate::prices::{fit_cost, CostLimit, PriceTable};
+use crate::ranking::{rank_ships, RankingWeights, ShipRanking};
+use crate::rationale::explain_fit;
+use crate::safety::{evaluate_safety, SafetyRating};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Event each ship's recommendations are emitted as
+pub const FIT_RECOMMENDATIONS_EVENT: &str = "fit-recommendations";
+/// Deadline of a search that does not set one
+const DEFAULT_DEADLINE: Duration = Duration::from_secs(120);
+/// Ships ranked when the request does not say how many
+const DEFAULT_TOP_N: usize = 10;
+
+/// What to recommend fits for
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecommendationRequest {
+    pub user_skills: Vec<Skill>,
+    pub all_ships: Vec<Ship>,
+    pub all_modules: Vec<Module>,
+    pub all_charges: Vec<Charge>,
+    pub activity: Activity,
+    /// Implants in the character's active clone
+    #[serde(default)]
+    pub implants: Vec<Implant>,
+    /// Combat boosters the character will have active
+    #[serde(default)]
+    pub boosters: Vec<Booster>,
+    #[serde(default)]
+    pub fleet_boosts: Vec<FleetBoost>,
+    /// SDE NPC corporations, to find a mission agent's enemies
+    #[serde(default)]
+    pub npc_corporations: Vec<NpcCorporation>,
+    /// Implants that may be plugged in to make a fit work
+    #[serde(default)]
+    pub fitting_implants: Vec<Implant>,
+    /// Maximum ISK cost of a fit
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    /// Only use modules the character owns ("use what I have")
+    #[serde(default)]
+    pub owned_assets: Option<AssetSelection>,
+    /// Seconds the whole search may take
+    #[serde(default)]
+    pub deadline_secs: Option<u64>,
+    /// Number of ships to rank
+    #[serde(default)]
+    pub top_n: Option<usize>,
+    #[serde(default)]
+    pub ranking_weights: RankingWeights,
+    /// Lowest safety rating of a recommended fit
+    #[serde(default)]
+    pub min_safety: Option<SafetyRating>,
+    /// Modules every fit must have (e.g. a MWD and a chosen tank)
+    #[serde(default)]
+    pub locked_modules: Vec<LockedModule>,
+}
+
+impl RecommendationRequest {
+    pub fn deadline(&self) -> Duration {
+        self.deadline_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_DEADLINE)
+    }
+}
+
+/// The recommended fits of one ship
+#[derive(Debug, Clone, Serialize)]
+pub struct ShipRecommendations {
+    pub search_id: u64,
+    pub ship_id: i64,
+    pub ship_name: String,
+    pub fits: Vec<FitVariant>,
+}
+
+/// How a search ended
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchSummary {
+    pub search_id: u64,
+    pub ships_total: usize,
+    /// Ships whose recommendations were reported
+    pub ships_done: usize,
+    pub cancelled: bool,
+    pub timed_out: bool,
+    /// Best ships across all reported fits (empty if cancelled)
+    pub ranking: Vec<ShipRanking>,
+}
+
+/// Shared flag to stop a running search
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// The flag itself, for the optimizer to poll
+    pub fn flag(&self) -> &AtomicBool {
+        &self.0
+    }
+}
+
+/// Searches in progress, by the ID the frontend gave them (Tauri managed state)
+#[derive(Debug, Default)]
+pub struct FitSearches(Mutex<HashMap<u64, CancelHandle>>);
+
+impl FitSearches {
+    /// Register a search; a running search with the same ID is cancelled
+    pub fn start(&self, search_id: u64) -> CancelHandle {
+        let handle = CancelHandle::default();
+        let mut searches = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = searches.insert(search_id, handle.clone()) {
+            previous.cancel();
+        }
+        handle
+    }
+
+    /// Cancel a search; false if no search with that ID is running
+    pub fn cancel(&self, search_id: u64) -> bool {
+        let searches = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match searches.get(&search_id) {
+            Some(handle) => {
+                handle.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget a finished search (unless another one took its ID)
+    pub fn finish(&self, search_id: u64, handle: &CancelHandle) {
+        let mut searches = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if searches
+            .get(&search_id)
+            .is_some_and(|h| Arc::ptr_eq(&h.0, &handle.0))
+        {
+            searches.remove(&search_id);
+        }
+    }
+}
+
+/// Inputs of a search, prepared once and shared by the workers
+pub struct FitSearch {
+    pub activity: Activity,
+    /// Ships to recommend fits for
+    pub ships: Vec<Ship>,
+    /// Modules the fits are built from
+    pub modules: Vec<Module>,
+    pub charges: Vec<Charge>,
+    pub user_skills: Vec<Skill>,
+    pub fitting_implants: Vec<Implant>,
+    pub prices: PriceTable,
+    pub max_cost: Option<f64>,
+    /// Items the character owns, when fitting from owned assets
+    pub owned: Option<OwnedItems>,
+    /// Context the fits are built and their stats calculated with
+    pub context: CalcContext,
+    pub top_n: usize,
+    pub ranking_weights: RankingWeights,
+    pub min_safety: Option<SafetyRating>,
+    pub locked_modules: Vec<LockedModule>,
+}
+
+impl FitSearch {
+    /// Prepare a search: resolve the mission enemy and the ships and modules to use
+    ///
+    /// # Arguments
+    /// * `request` - What to recommend fits for
+    /// * `prices` - The local price table
+    /// * `owned` - Items the character owns, if only those may be used
+    pub fn new(
+        request: RecommendationRequest,
+        prices: PriceTable,
+        owned: Option<OwnedItems>,
+    ) -> Self {
+        // Mission activities fight the agent's usual enemies: tank and ammo follow them
+        let activity = resolve_mission_enemy(&request.activity, &request.npc_corporations);
+        let (ships, mut modules) = match_skills_to_ships_and_modules(
+            &request.user_skills,
+            &request.all_ships,
+            &request.all_modules,
+        );
+        if let Some(owned) = &owned {
+            modules = assets::owned_modules(&modules, owned);
+        }
+        // Fits are built for the character's clone, boosters and fleet, in the activity's
+        // environment
+        let context = CalcContext {
+            implants: request.implants,
+            boosters: request.boosters,
+            fleet_boosts: request.fleet_boosts,
+            environment: activity.environment.clone(),
+            ..CalcContext::from_skills(&request.user_skills)
+        };
+        FitSearch {
+            activity,
+            ships,
+            modules,
+            charges: request.all_charges,
+            user_skills: request.user_skills,
+            fitting_implants: request.fitting_implants,
+            prices,
+            max_cost: request.max_cost,
+            owned,
+            context,
+            top_n: request.top_n.unwrap_or(DEFAULT_TOP_N),
+            ranking_weights: request.ranking_weights,
+            min_safety: request.min_safety,
+            locked_modules: request.locked_modules,
+        }
+    }
+
+    /// Recommended fits for one ship; its optimizer runs stop at `deadline` or on `cancel`
+    pub fn recommend_for_ship(
+        &self,
+        ship: &Ship,
+        deadline: Instant,
+        cancel: &CancelHandle,
+    ) -> Vec<FitVariant> {
+        let activity = &self.activity;
+        let cost_limit = self.max_cost.map(|max_cost| CostLimit {
+            max_cost,
+            prices: &self.prices,
+        });
+        let budget = SearchBudget {
+            deadline: Some(deadline),
+            cancel: Some(cancel.flag()),
+            cost_limit,
+            ..Default::default()
+        };
+        let within_budget = |fit: &FitVariant| cost_limit.is_none_or(|limit| limit.allows(fit));
+        // Hulls the locked modules do not fit get no fits
+        let Ok(fits) = generate_fit_variants(
+            ship,
+            activity,
+            &self.context,
+            &self.modules,
+            &self.charges,
+            &self.prices,
+            &self.locked_modules,
+            budget,
+        ) else {
+            return vec![];
+        };
+        // Load the best ammo for the activity's targets (unless it breaks the budget),
+        // then validate each fit and suggest alternatives if needed
+        let mut recommendations = vec![];
+        for fit in fits {
+            let mut loaded =
+                load_best_charges(&fit, &self.charges, &activity.target, &self.user_skills);
+            // Locked modules keep the charge they were locked with
+            let positions = locked_positions(&loaded, &self.locked_modules);
+            for (locked, position) in self.locked_modules.iter().zip(positions) {
+                if let (Some(index), Some(charge)) = (position, &locked.charge) {
+                    loaded.modules[index].charge = Some(charge.clone());
+                }
+            }
+            let fit = if within_budget(&loaded) { loaded } else { fit };
+            if validate_fit_with(&fit, &self.context).is_valid {
+                recommendations.push(fit);
+            } else {
+                let alternatives = suggest_alternative_fits(
+                    &fit,
+                    &self.context,
+                    &self.modules,
+                    &self.charges,
+                    &activity.target,
+                    &self.fitting_implants,
+                    &self.locked_modules,
+                );
+                recommendations.extend(alternatives.into_iter().filter(within_budget));
+            }
+        }
+        // Attach stats, cost, owned items and safety so the frontend can show them; alternatives
+        // are explained again with the changes that made them flyable
+        for fit in &mut recommendations {
+            if !fit.changes.is_empty() {
+                let previous = fit.explanation.take().unwrap_or_default();
+                let mut trade_offs = previous.trade_offs;
+                trade_offs.extend(fit.changes.iter().cloned());
+                let rationale =
+                    explain_fit(fit, previous.summary, trade_offs, activity, &self.context);
+                fit.rationale = rationale.render();
+                fit.explanation = Some(rationale);
+            }
+            let stats = calculate_fit_stats_with(fit, &self.context);
+            fit.safety = Some(evaluate_safety(fit, &stats, activity, &self.context));
+            fit.stats = Some(stats);
+            fit.cost = fit_cost(fit, &self.prices);
+            if let Some(owned) = &self.owned {
+                fit.availability = assets::fit_availability(fit, owned);
+            }
+        }
+        if let Some(min_safety) = self.min_safety {
+            recommendations
+                .retain(|fit| fit.safety.as_ref().is_some_and(|s| s.rating >= min_safety));
+        }
+        // Tackle fits are ordered by how well they hold targets down
+        if matches!(
+            activity.kind,
+            ActivityKind::Pvp {
+                role: PvpRole::Tackle
+            }
+        ) {
+            let score = |fit: &FitVariant| fit.stats.as_ref().map(tackle_score).unwrap_or(0.0);
+            recommendations.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+        recommendations
+    }
+
+    /// Recommend fits for every ship on a worker pool
+    ///
+    /// # Arguments
+    /// * `search_id` - ID reported with each ship's fits
+    /// * `deadline` - No ship is started after this
+    /// * `cancel` - Stops the search; fits finished afterwards are not reported
+    /// * `on_ship` - Called from the workers with each ship's fits as they finish
+    ///
+    /// # Returns
+    /// - How many ships were done, whether the search was cut short and the ranking
+    pub fn run(
+        &self,
+        search_id: u64,
+        deadline: Instant,
+        cancel: &CancelHandle,
+        on_ship: &(dyn Fn(ShipRecommendations) + Sync),
+    ) -> SearchSummary {
+        let next_ship = AtomicUsize::new(0);
+        let ships_done = AtomicUsize::new(0);
+        let all_fits = Mutex::new(vec![]);
+        let timed_out = AtomicBool::new(false);
+        let workers = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(self.ships.len());
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    if Instant::now() >= deadline {
+                        timed_out.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                    let Some(ship) = self.ships.get(next_ship.fetch_add(1, Ordering::Relaxed))
+                    else {
+                        return;
+                    };
+                    let fits = self.recommend_for_ship(ship, deadline, cancel);
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    all_fits
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .extend(fits.iter().cloned());
+                    on_ship(ShipRecommendations {
+                        search_id,
+                        ship_id: ship.ship_id,
+                        ship_name: ship.ship_name.clone(),
+                        fits,
+                    });
+                    ships_done.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        });
+        let ships_done = ships_done.into_inner();
+        let cancelled = cancel.is_cancelled();
+        let ranking = if cancelled {
+            vec![]
+        } else {
+            rank_ships(
+                all_fits.into_inner().unwrap_or_else(|e| e.into_inner()),
+                &self.activity,
+                &self.context,
+                &self.ranking_weights,
+                self.top_n,
+            )
+        };
+        SearchSummary {
+            search_id,
+            ships_total: self.ships.len(),
+            ships_done,
+            cancelled,
+            timed_out: timed_out.into_inner() && ships_done < self.ships.len(),
+            ranking,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::Ship;
+    use crate::ranking::RankingWeights;
+    use crate::test_support::{
+        activity, armor_plate, attrs, hybrid_charge, large_blaster, magnetic_field_stabilizer,
+    };
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_parallel_fit_search() {
+        let request = RecommendationRequest {
+            user_skills: vec![],
+            all_ships: vec![],
+            all_modules: vec![],
+            all_charges: vec![hybrid_charge("Antimatter Charge L", 10.0, 1.0)],
+            activity: activity("Level 4 Missions"),
+            implants: vec![],
+            boosters: vec![],
+            fleet_boosts: vec![],
+            npc_corporations: vec![],
+            fitting_implants: vec![],
+            max_cost: None,
+            owned_assets: None,
+            deadline_secs: None,
+            top_n: Some(3),
+            ranking_weights: RankingWeights::default(),
+            min_safety: None,
+            locked_modules: vec![],
+        };
+        let mut search = FitSearch::new(request, HashMap::new(), None);
+        search.ships = (0..4)
+            .map(|i| Ship {
+                ship_id: 641 + i,
+                ship_name: format!("Megathron {}", i),
+                attributes: attrs(&[
+                    ("hiSlots", 2.0),
+                    ("turretSlotsLeft", 2.0),
+                    ("lowSlots", 2.0),
+                    ("cpuOutput", 130.0),
+                    ("powerOutput", 20_000.0),
+                    ("armorHP", 5_000.0),
+                ]),
+                ..Default::default()
+            })
+            .collect();
+        search.modules = vec![large_blaster(), magnetic_field_stabilizer(), armor_plate()];
+
+        let received = Mutex::new(vec![]);
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let summary = search.run(7, deadline, &CancelHandle::default(), &|ship| {
+            received.lock().unwrap().push(ship)
+        });
+        assert_eq!((summary.ships_done, summary.ships_total), (4, 4));
+        assert!(!summary.cancelled && !summary.timed_out);
+        assert_eq!(summary.ranking.len(), 3);
+        assert_eq!(summary.ranking[0].rank, 1);
+        let received = received.into_inner().unwrap();
+        let mut ship_ids: Vec<i64> = received.iter().map(|r| r.ship_id).collect();
+        ship_ids.sort();
+        assert_eq!(ship_ids, vec![641, 642, 643, 644]);
+        assert!(received.iter().all(|r| r.search_id == 7
+            && !r.fits.is_empty()
+            && r.fits.iter().all(|f| f.stats.is_some())));
+
+        // A cancelled search or one past its deadline reports nothing
+        let cancel = CancelHandle::default();
+        cancel.cancel();
+        let summary = search.run(8, deadline, &cancel, &|_| panic!("cancelled"));
+        assert!(summary.cancelled);
+        assert_eq!(summary.ships_done, 0);
+        let summary = search.run(9, Instant::now(), &CancelHandle::default(), &|_| {
+            panic!("timed out")
+        });
+        assert!(summary.timed_out);
+        assert_eq!(summary.ships_done, 0);
+
+        // Starting a search again under the same ID cancels the running one
+        let searches = FitSearches::default();
+        let first = searches.start(1);
+        let second = searches.start(1);
+        assert!(first.is_cancelled() && !second.is_cancelled());
+        assert!(searches.cancel(1) && second.is_cancelled());
+        searches.finish(1, &second);
+        assert!(!searches.cancel(1));
+    }
+}
@@ -0,0 +1,408 @@
+//! Fit safety evaluation for EveOnline Helper
+//
+// Measures the risk of losing a fit in an activity against the activity's safety
+// thresholds:
+// - tank: EHP against the minimum, and sustained tank (boosters, repairers and passive
+//   shield regeneration, through the fit's resists) against the expected incoming DPS.
+//   A fit that cannot tank the incoming DPS should at least survive `HOLD_TIME`.
+// - capacitor: stability where the activity requires it, and the recharge margin
+// - align time against the longest acceptable one
+// Each check gives a score between 0 and 1; the safety score is their average. The rating
+// follows from the score and is lowered by the most severe warning (e.g. a fit without
+// any tank is at best risky). When the activity sets no thresholds there is nothing to
+// measure against: the rating is unknown (score 0) unless a warning lowers it.
+
+use crate::activity::Activity;
+use crate::esi::FitVariant;
+use crate::fitting::{calculate_fit_stats_with, CalcContext, FitStats, PROPULSION_GROUP};
+use serde::{Deserialize, Serialize};
+
+/// Seconds a fit that cannot tank the incoming DPS should survive (to finish the
+/// target or warp out)
+const HOLD_TIME: f64 = 180.0;
+/// Smallest EHP gain that counts as tank
+const MIN_TANK_EHP: f64 = 1.0;
+
+/// How safe a fit is, worst first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafetyRating {
+    /// No safety check applies; below every known rating so minimum ratings exclude it
+    Unknown,
+    Unsafe,
+    Risky,
+    Acceptable,
+    Safe,
+}
+
+impl SafetyRating {
+    fn from_score(score: f64) -> Self {
+        if score >= 0.9 {
+            SafetyRating::Safe
+        } else if score >= 0.7 {
+            SafetyRating::Acceptable
+        } else if score >= 0.4 {
+            SafetyRating::Risky
+        } else {
+            SafetyRating::Unsafe
+        }
+    }
+}
+
+/// How much a warning lowers the rating, least first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Worth knowing, the rating is not lowered
+    Notice,
+    /// At best acceptable
+    Minor,
+    /// At best risky
+    Major,
+    /// Unsafe
+    Critical,
+}
+
+impl Severity {
+    fn max_rating(self) -> SafetyRating {
+        match self {
+            Severity::Notice => SafetyRating::Safe,
+            Severity::Minor => SafetyRating::Acceptable,
+            Severity::Major => SafetyRating::Risky,
+            Severity::Critical => SafetyRating::Unsafe,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// No module adds EHP or repairs
+    NoTank,
+    LowEhp,
+    /// The incoming DPS breaks the tank before `HOLD_TIME`
+    TankBroken,
+    NotCapStable,
+    SlowAlign,
+    NoPropulsion,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SafetyWarning {
+    pub kind: WarningKind,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Safety of a fit in an activity
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SafetyReport {
+    pub rating: SafetyRating,
+    /// Average of the check scores (0-1; 0 when no check applies)
+    pub score: f64,
+    /// EHP against the activity's incoming damage
+    pub ehp: f64,
+    /// Incoming DPS the fit repairs indefinitely (its best layer)
+    pub sustained_tank: f64,
+    pub incoming_dps: f64,
+    /// Seconds until the incoming DPS destroys the fit (None if the tank holds or the
+    /// incoming DPS is unknown)
+    pub time_to_die: Option<f64>,
+    /// Capacitor recharge minus usage, in GJ/s
+    pub capacitor_margin: f64,
+    pub align_time: f64,
+    pub warnings: Vec<SafetyWarning>,
+}
+
+/// Incoming DPS each layer's repairs cancel out, through its resists
+fn sustained_tank(stats: &FitStats, activity: &Activity) -> f64 {
+    let incoming = activity.incoming_damage.normalized();
+    let through = |reps: f64, resists| {
+        let taken = incoming.applied_against(resists);
+        if taken > 0.0 {
+            reps / taken
+        } else {
+            reps
+        }
+    };
+    // Only the layer being shot at repairs, so the best one counts
+    through(
+        stats.shield_boost_rate + stats.passive_shield_regen,
+        &stats.shield_resists,
+    )
+    .max(through(stats.armor_repair_rate, &stats.armor_resists))
+}
+
+/// Evaluate how safe a fit is for an activity
+///
+/// # Arguments
+/// * `fit` - The fit to evaluate
+/// * `stats` - The fit's stats, calculated with `ctx`
+/// * `activity` - The activity, with its safety thresholds and incoming damage
+/// * `ctx` - Context the stats are calculated with (for the fit without modules)
+///
+/// # Returns
+/// - The safety rating and score, the numbers they are based on and warnings
+pub fn evaluate_safety(
+    fit: &FitVariant,
+    stats: &FitStats,
+    activity: &Activity,
+    ctx: &CalcContext,
+) -> SafetyReport {
+    let thresholds = &activity.safety;
+    let ehp = stats.ehp_against(&activity.incoming_damage);
+    let sustained = sustained_tank(stats, activity);
+    let incoming_dps = thresholds.incoming_dps.max(0.0);
+    let capacitor_margin = stats.capacitor_recharge - stats.capacitor_usage;
+    let mut checks = vec![];
+    let mut warnings = vec![];
+    let mut warn = |kind, severity, message: String| {
+        warnings.push(SafetyWarning {
+            kind,
+            severity,
+            message,
+        })
+    };
+
+    let hull_only = FitVariant {
+        modules: vec![],
+        ..fit.clone()
+    };
+    let hull_ehp = calculate_fit_stats_with(&hull_only, ctx).ehp_against(&activity.incoming_damage);
+    if ehp - hull_ehp < MIN_TANK_EHP && stats.shield_boost_rate + stats.armor_repair_rate <= 0.0 {
+        warn(
+            WarningKind::NoTank,
+            Severity::Major,
+            "No module adds EHP or repairs".to_string(),
+        );
+    }
+
+    if thresholds.min_ehp > 0.0 {
+        let ratio = (ehp / thresholds.min_ehp).min(1.0);
+        checks.push(ratio);
+        if ratio < 1.0 {
+            warn(
+                WarningKind::LowEhp,
+                if ratio < 0.5 {
+                    Severity::Major
+                } else {
+                    Severity::Minor
+                },
+                format!(
+                    "EHP of {:.0} is below the {:.0} the activity needs",
+                    ehp, thresholds.min_ehp
+                ),
+            );
+        }
+    }
+
+    let mut time_to_die = None;
+    if incoming_dps > 0.0 {
+        if sustained >= incoming_dps {
+            checks.push(1.0);
+        } else {
+            let seconds = ehp / (incoming_dps - sustained);
+            time_to_die = Some(seconds);
+            checks.push((seconds / HOLD_TIME).min(1.0));
+            if seconds < HOLD_TIME {
+                warn(
+                    WarningKind::TankBroken,
+                    Severity::Critical,
+                    format!(
+                        "{:.0} incoming DPS breaks the tank ({:.0} DPS sustained) in {:.0} s",
+                        incoming_dps, sustained, seconds
+                    ),
+                );
+            }
+        }
+    }
+
+    if thresholds.require_cap_stable {
+        checks.push(if stats.capacitor_stable {
+            1.0
+        } else if stats.capacitor_usage > 0.0 {
+            0.5 * (stats.capacitor_recharge / stats.capacitor_usage).min(1.0)
+        } else {
+            0.0
+        });
+    }
+    if !stats.capacitor_stable {
+        warn(
+            WarningKind::NotCapStable,
+            if thresholds.require_cap_stable {
+                Severity::Major
+            } else {
+                Severity::Notice
+            },
+            format!(
+                "Not cap stable ({:+.1} GJ/s with all modules running)",
+                capacitor_margin
+            ),
+        );
+    }
+
+    if let Some(max_align) = thresholds.max_align_time {
+        if stats.align_time <= max_align {
+            checks.push(1.0);
+        } else {
+            checks.push(max_align / stats.align_time);
+            warn(
+                WarningKind::SlowAlign,
+                Severity::Minor,
+                format!(
+                    "Aligns in {:.1} s, slower than {:.1} s",
+                    stats.align_time, max_align
+                ),
+            );
+        }
+    }
+
+    if !fit.modules.iter().any(|m| m.group_id == PROPULSION_GROUP) {
+        // Only a problem where speed matters
+        let severity = if activity.weights.agility > 0.0 || activity.weights.tackle > 0.0 {
+            Severity::Minor
+        } else {
+            Severity::Notice
+        };
+        warn(
+            WarningKind::NoPropulsion,
+            severity,
+            "No propulsion module".to_string(),
+        );
+    }
+
+    let caps = warnings.iter().map(|w| w.severity.max_rating());
+    let (score, rating) = if checks.is_empty() {
+        let rating = caps
+            .filter(|r| *r < SafetyRating::Safe)
+            .min()
+            .unwrap_or(SafetyRating::Unknown);
+        (0.0, rating)
+    } else {
+        let score = checks.iter().sum::<f64>() / checks.len() as f64;
+        (
+            score,
+            caps.fold(SafetyRating::from_score(score), SafetyRating::min),
+        )
+    };
+    SafetyReport {
+        rating,
+        score,
+        ehp,
+        sustained_tank: sustained,
+        incoming_dps,
+        time_to_die,
+        capacitor_margin,
+        align_time: stats.align_time,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::SafetyThresholds;
+    use crate::esi::{FitVariant, Module, Ship, SlotType};
+    use crate::fitting::{calculate_fit_stats_with, CalcContext};
+    use crate::test_support::{activity, armor_plate, attrs, hybrid_charge, large_blaster};
+
+    #[test]
+    fn test_fit_safety() {
+        let mut activity = activity("Level 4 Missions");
+        activity.safety.incoming_dps = 300.0;
+        let ctx = CalcContext::default();
+        let mut blaster = large_blaster();
+        blaster.charge = Some(hybrid_charge("Antimatter Charge L", 10.0, 1.0));
+        let evaluate = |fit: &FitVariant| {
+            evaluate_safety(fit, &calculate_fit_stats_with(fit, &ctx), &activity, &ctx)
+        };
+
+        // 5000 EHP and nothing else: dead in 17 s
+        let bare = FitVariant {
+            fit_name: "Bare".to_string(),
+            ship: Ship {
+                ship_id: 641,
+                ship_name: "Megathron".to_string(),
+                attributes: attrs(&[("armorHP", 5_000.0)]),
+                ..Default::default()
+            },
+            modules: vec![blaster.clone()],
+            ..Default::default()
+        };
+        let report = evaluate(&bare);
+        assert_eq!(report.rating, SafetyRating::Unsafe);
+        let kinds: Vec<WarningKind> = report.warnings.iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                WarningKind::NoTank,
+                WarningKind::LowEhp,
+                WarningKind::TankBroken,
+                WarningKind::NoPropulsion
+            ]
+        );
+        assert!((report.time_to_die.unwrap() - 5_000.0 / 300.0).abs() < 1e-9);
+        // Missions do not weight speed: a missing prop mod is only a notice
+        assert_eq!(report.warnings[3].severity, Severity::Notice);
+
+        // Plates and a repairer outrepairing the incoming DPS, cap stable
+        let repairer = Module {
+            module_id: 3540,
+            module_name: "Large Armor Repairer II".to_string(),
+            group_id: 62,
+            slot: Some(SlotType::Low),
+            attributes: attrs(&[
+                ("armorDamageAmount", 1_200.0),
+                ("duration", 3_000.0),
+                ("capacitorNeed", 120.0),
+            ]),
+            ..Default::default()
+        };
+        let mut tanked = bare.clone();
+        tanked.ship.attributes = attrs(&[
+            ("armorHP", 45_000.0),
+            ("capacitorCapacity", 5_000.0),
+            ("rechargeRate", 100_000.0),
+        ]);
+        tanked.modules = vec![blaster, armor_plate(), armor_plate(), repairer];
+        let report = evaluate(&tanked);
+        assert_eq!(report.rating, SafetyRating::Safe);
+        assert_eq!(report.score, 1.0);
+        assert!((report.sustained_tank - 400.0).abs() < 1e-9);
+        assert!(report.time_to_die.is_none());
+        assert!((report.capacitor_margin - (125.0 - 40.0)).abs() < 1e-9);
+        assert!(report
+            .warnings
+            .iter()
+            .all(|w| w.severity == Severity::Notice));
+
+        // The activity requires cap stability: a fit that runs dry is at best risky
+        tanked
+            .ship
+            .attributes
+            .insert("rechargeRate".to_string(), 1_000_000.0);
+        let report = evaluate(&tanked);
+        assert!(report.score >= 0.7);
+        assert_eq!(report.rating, SafetyRating::Risky);
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::NotCapStable && w.severity == Severity::Major));
+
+        // Without thresholds nothing is measured: unknown, unless a warning lowers it
+        let mut unmeasured = activity.clone();
+        unmeasured.safety = SafetyThresholds::default();
+        let evaluate = |fit: &FitVariant| {
+            evaluate_safety(fit, &calculate_fit_stats_with(fit, &ctx), &unmeasured, &ctx)
+        };
+        tanked
+            .ship
+            .attributes
+            .insert("rechargeRate".to_string(), 100_000.0);
+        let report = evaluate(&tanked);
+        assert_eq!(report.rating, SafetyRating::Unknown);
+        assert_eq!(report.score, 0.0);
+        assert!(SafetyRating::Unknown < SafetyRating::Unsafe);
+        assert_eq!(evaluate(&bare).rating, SafetyRating::Risky);
+    }
+}
@@ -0,0 +1,58 @@
+//! Saved fits for EveOnline Helper
+//
+// Fits the user keeps are stored as JSON under `data/`, next to the boost presets.
+// Each saved fit gets a numeric ID that the frontend uses to refer to it.
+
+use crate::esi::FitVariant;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const SAVED_FITS_PATH: &str = "data/saved_fits.json";
+
+/// A fit saved by the user
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SavedFit {
+    pub id: u64,
+    pub fit: FitVariant,
+}
+
+/// Load all saved fits (empty if none were saved yet)
+pub fn load_saved_fits() -> Result<Vec<SavedFit>, String> {
+    match fs::read_to_string(SAVED_FITS_PATH) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse saved fits: {}", e)),
+        Err(_) => Ok(vec![]),
+    }
+}
+
+fn write_saved_fits(fits: &[SavedFit]) -> Result<(), String> {
+    fs::create_dir_all("data").map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(fits)
+        .map_err(|e| format!("Failed to serialize saved fits: {}", e))?;
+    fs::write(SAVED_FITS_PATH, json).map_err(|e| format!("Failed to write saved fits: {}", e))
+}
+
+/// Save a fit and return its new ID
+pub fn save_fit(fit: FitVariant) -> Result<u64, String> {
+    let mut fits = load_saved_fits()?;
+    let id = fits.iter().map(|f| f.id).max().unwrap_or(0) + 1;
+    fits.push(SavedFit { id, fit });
+    write_saved_fits(&fits)?;
+    Ok(id)
+}
+
+/// Delete the saved fit with the given ID
+pub fn delete_saved_fit(id: u64) -> Result<(), String> {
+    let mut fits = load_saved_fits()?;
+    fits.retain(|f| f.id != id);
+    write_saved_fits(&fits)
+}
+
+/// Look up a saved fit by ID
+pub fn get_saved_fit(id: u64) -> Result<FitVariant, String> {
+    load_saved_fits()?
+        .into_iter()
+        .find(|f| f.id == id)
+        .map(|f| f.fit)
+        .ok_or_else(|| format!("No saved fit with ID {}", id))
+}
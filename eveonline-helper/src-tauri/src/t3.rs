@@ -0,0 +1,220 @@
+//! Strategic cruiser subsystems and tactical destroyer modes for EveOnline Helper
+//
+// T3 cruisers have no fixed layout: each of their four subsystems (defensive, offensive,
+// propulsion, core) adds slots and hardpoints through `hiSlotModifier`-style attributes
+// and carries bonuses that scale with the matching subsystem skill (`Module::bonuses`).
+//
+// T3 destroyers switch between three modes (Defense, Propulsion, Sharpshooter); the
+// active mode's effects apply to the ship like a hull bonus.
+
+use crate::esi::{FitVariant, Module, SlotType};
+use crate::fitting::{attr, Modifier, ModifierTarget};
+
+/// SDE group of strategic (T3) cruisers
+pub const STRATEGIC_CRUISER_GROUP: i64 = 963;
+/// SDE group of tactical (T3) destroyers
+pub const TACTICAL_DESTROYER_GROUP: i64 = 1305;
+/// SDE groups of the four subsystem types every strategic cruiser needs
+pub const SUBSYSTEM_GROUPS: &[(i64, &str)] = &[
+    (954, "Defensive"),
+    (956, "Offensive"),
+    (957, "Propulsion"),
+    (958, "Core"),
+];
+
+/// Subsystem attributes that add to a hull attribute
+const SLOT_MODIFIERS: &[(&str, &str)] = &[
+    ("hiSlotModifier", attr::HI_SLOTS),
+    ("medSlotModifier", attr::MED_SLOTS),
+    ("lowSlotModifier", attr::LOW_SLOTS),
+    ("turretHardPointModifier", attr::TURRET_SLOTS),
+    ("launcherHardPointModifier", attr::LAUNCHER_SLOTS),
+];
+
+/// Hull modifiers for the slots and hardpoints a subsystem adds
+pub fn subsystem_slot_modifiers(module: &Module) -> Vec<Modifier> {
+    if module.slot != Some(SlotType::Subsystem) {
+        return vec![];
+    }
+    SLOT_MODIFIERS
+        .iter()
+        .filter_map(|(source, target)| {
+            let value = module.attributes.get(*source).copied()?;
+            Some(Modifier::add(ModifierTarget::Ship, target, value))
+        })
+        .collect()
+}
+
+/// Subsystem and tactical mode problems of a fit, as validation messages
+pub fn validate_t3(fit: &FitVariant) -> Vec<String> {
+    let mut problems = vec![];
+    let ship = &fit.ship;
+    let subsystems: Vec<&Module> = fit
+        .modules
+        .iter()
+        .filter(|m| m.slot == Some(SlotType::Subsystem))
+        .collect();
+
+    if ship.group_id == STRATEGIC_CRUISER_GROUP {
+        for (group_id, label) in SUBSYSTEM_GROUPS {
+            match subsystems
+                .iter()
+                .filter(|m| m.group_id == *group_id)
+                .count()
+            {
+                0 => problems.push(format!(
+                    "{} is missing a {} subsystem",
+                    ship.ship_name, label
+                )),
+                1 => {}
+                _ => problems.push(format!("Only one {} subsystem can be fitted", label)),
+            }
+        }
+        for subsystem in &subsystems {
+            let fits_to = subsystem
+                .attributes
+                .get(attr::FITS_TO_SHIP_TYPE)
+                .map(|id| *id as i64);
+            if fits_to.is_some_and(|id| id != ship.ship_id) {
+                problems.push(format!(
+                    "{} cannot be fitted to {}",
+                    subsystem.module_name, ship.ship_name
+                ));
+            }
+        }
+    } else if !subsystems.is_empty() {
+        problems.push(format!("{} cannot fit subsystems", ship.ship_name));
+    }
+
+    match (&fit.mode, ship.group_id == TACTICAL_DESTROYER_GROUP) {
+        (None, true) => problems.push(format!("{} needs a tactical mode", ship.ship_name)),
+        (Some(mode), true) if mode.ship_id != ship.ship_id => problems.push(format!(
+            "{} is not a mode of {}",
+            mode.mode_name, ship.ship_name
+        )),
+        (Some(_), false) => problems.push(format!("{} has no tactical modes", ship.ship_name)),
+        _ => {}
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::esi::{
+        validate_fit, FitVariant, HullBonus, Module, Ship, ShipMode, Skill, SlotType,
+    };
+    use crate::fitting::{calculate_fit_stats, Modifier, ModifierTarget};
+    use crate::test_support::attrs;
+
+    fn subsystem(name: &str, group_id: i64, pairs: &[(&str, f64)]) -> Module {
+        let mut attributes = attrs(pairs);
+        attributes.insert("fitsToShipType".to_string(), 29984.0);
+        Module {
+            module_name: name.to_string(),
+            group_id,
+            slot: Some(SlotType::Subsystem),
+            attributes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_strategic_cruiser_subsystems() {
+        let tengu = Ship {
+            ship_id: 29984,
+            ship_name: "Tengu".to_string(),
+            group_id: 963,
+            attributes: attrs(&[("maxSubSystems", 4.0), ("shieldCapacity", 1_000.0)]),
+            ..Default::default()
+        };
+        let mut offensive = subsystem(
+            "Tengu Offensive - Accelerated Ejection Bay",
+            956,
+            &[("hiSlotModifier", 5.0), ("launcherHardPointModifier", 5.0)],
+        );
+        offensive.bonuses = vec![HullBonus {
+            skill_id: Some(30651),
+            modifier: Modifier::percent(ModifierTarget::Ship, "shieldCapacity", 4.0),
+        }];
+        let mut fit = FitVariant {
+            ship: tengu,
+            modules: vec![
+                subsystem(
+                    "Tengu Defensive - Supplemental Screening",
+                    954,
+                    &[("medSlotModifier", 3.0)],
+                ),
+                offensive,
+                subsystem(
+                    "Tengu Propulsion - Chassis Optimization",
+                    957,
+                    &[("medSlotModifier", 1.0)],
+                ),
+            ],
+            ..Default::default()
+        };
+        let skills = [Skill {
+            skill_id: 30651,
+            skill_name: Some("Caldari Offensive Systems".to_string()),
+            active_level: 5,
+        }];
+        let stats = calculate_fit_stats(&fit, &skills);
+        assert!((stats.shield_hp - 1_200.0).abs() < 1e-6);
+        let validation = validate_fit(&fit, &skills);
+        assert!(validation
+            .missing_requirements
+            .contains(&"Tengu is missing a Core subsystem".to_string()));
+
+        fit.modules.push(subsystem(
+            "Tengu Core - Augmented Graviton Reactor",
+            958,
+            &[("lowSlotModifier", 2.0)],
+        ));
+        let launchers = |count: usize| {
+            (0..count).map(|_| Module {
+                module_name: "Heavy Missile Launcher II".to_string(),
+                group_id: 510,
+                slot: Some(SlotType::High),
+                ..Default::default()
+            })
+        };
+        fit.modules.extend(launchers(5));
+        let validation = validate_fit(&fit, &skills);
+        assert!(validation.is_valid, "{:?}", validation.missing_requirements);
+        // The subsystems only provide five high slots and launcher hardpoints
+        fit.modules.extend(launchers(1));
+        let validation = validate_fit(&fit, &skills);
+        assert!(validation
+            .missing_requirements
+            .iter()
+            .any(|r| r.starts_with("Too many High slot modules")));
+    }
+
+    #[test]
+    fn test_tactical_destroyer_modes() {
+        let svipul = Ship {
+            ship_id: 34317,
+            ship_name: "Svipul".to_string(),
+            group_id: 1305,
+            attributes: attrs(&[("maxVelocity", 300.0)]),
+            ..Default::default()
+        };
+        let mut fit = FitVariant {
+            ship: svipul,
+            ..Default::default()
+        };
+        let validation = validate_fit(&fit, &[]);
+        assert!(validation
+            .missing_requirements
+            .contains(&"Svipul needs a tactical mode".to_string()));
+        fit.mode = Some(ShipMode {
+            mode_id: 34566,
+            mode_name: "Svipul Propulsion Mode".to_string(),
+            ship_id: 34317,
+            effects: vec![Modifier::percent(ModifierTarget::Ship, "maxVelocity", 66.0)],
+        });
+        assert!(validate_fit(&fit, &[]).is_valid);
+        let stats = calculate_fit_stats(&fit, &[]);
+        assert!((stats.max_velocity - 498.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,254 @@
+//! Shared fixtures for the unit tests of EveOnline Helper
+//
+// SDE-like ships, modules and charges, and helpers to build them, used by the tests of
+// more than one module. Fixtures only one module needs live with its tests.
+
+use crate::activity::{builtin_activities, Activity};
+use crate::eft::EftTypes;
+use crate::esi::{Charge, Drone, FitVariant, Module, Ship, SlotType};
+use crate::fitting::{Modifier, ModifierTarget, Operation};
+use std::collections::HashMap;
+
+pub fn attrs(pairs: &[(&str, f64)]) -> HashMap<String, f64> {
+    pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+}
+
+/// Built-in activity profile by name
+pub fn activity(name: &str) -> Activity {
+    builtin_activities()
+        .into_iter()
+        .find(|a| a.name == name)
+        .unwrap()
+}
+
+/// Large hybrid turret accepting size 3 hybrid charges (group 85)
+pub fn large_blaster() -> Module {
+    Module {
+        module_id: 3186,
+        module_name: "Neutron Blaster Cannon II".to_string(),
+        group_id: 74,
+        slot: Some(SlotType::High),
+        attributes: attrs(&[
+            ("chargeGroup1", 85.0),
+            ("chargeSize", 3.0),
+            ("capacity", 1.0),
+            ("damageMultiplier", 4.0),
+            ("speed", 10_000.0),
+            ("maxRange", 5_000.0),
+            ("falloff", 10_000.0),
+            ("cpu", 50.0),
+            ("power", 1_500.0),
+        ]),
+        required_skills: vec![(3307, "Large Hybrid Turret".to_string(), 5)],
+        ..Default::default()
+    }
+}
+
+pub fn hybrid_charge(name: &str, damage: f64, range_multiplier: f64) -> Charge {
+    Charge {
+        charge_id: 0,
+        charge_name: name.to_string(),
+        group_id: 85,
+        attributes: attrs(&[
+            ("chargeSize", 3.0),
+            ("volume", 0.025),
+            ("kineticDamage", damage),
+            ("thermalDamage", damage),
+            ("weaponRangeMultiplier", range_multiplier),
+        ]),
+        ..Default::default()
+    }
+}
+
+pub fn blaster_fit() -> FitVariant {
+    FitVariant {
+        fit_name: "Blaster Test".to_string(),
+        ship: Ship {
+            ship_id: 641,
+            ship_name: "Megathron".to_string(),
+            attributes: attrs(&[
+                ("hiSlots", 7.0),
+                ("turretSlotsLeft", 7.0),
+                ("cpuOutput", 500.0),
+                ("powerOutput", 20_000.0),
+            ]),
+            ..Default::default()
+        },
+        modules: vec![large_blaster()],
+        ..Default::default()
+    }
+}
+
+/// Active shield hardener: 30% EM resist while running
+pub fn em_hardener() -> Module {
+    Module {
+        module_id: 2281,
+        module_name: "EM Shield Hardener II".to_string(),
+        group_id: 77,
+        slot: Some(SlotType::Mid),
+        attributes: attrs(&[
+            ("cpu", 44.0),
+            ("duration", 10_000.0),
+            ("overloadHardeningBonus", 20.0),
+        ]),
+        effects: vec![Modifier {
+            target: ModifierTarget::Ship,
+            attribute: "shieldEmDamageResonance".to_string(),
+            operation: Operation::PostPercent,
+            value: -30.0,
+            stacking_penalized: true,
+        }],
+        ..Default::default()
+    }
+}
+
+pub fn mining_fit(crystal: bool) -> FitVariant {
+    let strip_miner = Module {
+        module_id: 17912,
+        module_name: "Modulated Strip Miner II".to_string(),
+        group_id: 464,
+        slot: Some(SlotType::High),
+        attributes: attrs(&[("miningAmount", 900.0), ("duration", 180_000.0)]),
+        charge: crystal.then(|| Charge {
+            charge_name: "Veldspar Mining Crystal II".to_string(),
+            group_id: 482,
+            attributes: attrs(&[
+                ("specialisationAsteroidGroup", 462.0),
+                ("specialisationAsteroidYieldMultiplier", 1.75),
+                ("miningWasteProbability", 10.0),
+                ("miningWastedVolumeMultiplier", 1.0),
+            ]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    FitVariant {
+        fit_name: if crystal { "Crystal" } else { "No crystal" }.to_string(),
+        ship: Ship {
+            ship_name: "Procurer".to_string(),
+            attributes: attrs(&[
+                ("specialOreHoldCapacity", 16_000.0),
+                ("droneBandwidth", 50.0),
+            ]),
+            ..Default::default()
+        },
+        modules: vec![strip_miner],
+        drones: vec![Drone {
+            drone_name: "Mining Drone I".to_string(),
+            group_id: 101,
+            attributes: attrs(&[
+                ("miningAmount", 25.0),
+                ("duration", 60_000.0),
+                ("droneBandwidthUsed", 10.0),
+            ]),
+            quantity: 8,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+pub fn core_probe_launcher() -> Module {
+    Module {
+        module_name: "Core Probe Launcher I".to_string(),
+        group_id: 481,
+        slot: Some(SlotType::High),
+        attributes: attrs(&[("chargeGroup1", 479.0), ("capacity", 0.8), ("cpu", 5.0)]),
+        ..Default::default()
+    }
+}
+
+pub fn data_analyzer() -> Module {
+    Module {
+        module_name: "Data Analyzer I".to_string(),
+        group_id: 538,
+        slot: Some(SlotType::Mid),
+        attributes: attrs(&[
+            ("virusCoherence", 40.0),
+            ("virusStrength", 20.0),
+            ("duration", 5_000.0),
+        ]),
+        required_skills: vec![(21718, "Hacking".to_string(), 1)],
+        ..Default::default()
+    }
+}
+
+pub fn remote_shield_booster() -> Module {
+    Module {
+        module_name: "Medium Remote Shield Booster II".to_string(),
+        group_id: 41,
+        slot: Some(SlotType::High),
+        attributes: attrs(&[
+            ("shieldBonus", 400.0),
+            ("duration", 5_000.0),
+            ("capacitorNeed", 60.0),
+            ("maxRange", 40_000.0),
+            ("falloffEffectiveness", 20_000.0),
+            ("cpu", 50.0),
+        ]),
+        ..Default::default()
+    }
+}
+
+/// Low slot damage mod for hybrid turrets: +10% damage, stacking penalized
+pub fn magnetic_field_stabilizer() -> Module {
+    Module {
+        module_id: 10190,
+        module_name: "Magnetic Field Stabilizer II".to_string(),
+        group_id: 302,
+        slot: Some(SlotType::Low),
+        attributes: attrs(&[("cpu", 30.0)]),
+        effects: vec![Modifier {
+            stacking_penalized: true,
+            ..Modifier::percent(ModifierTarget::ModuleGroup(74), "damageMultiplier", 10.0)
+        }],
+        ..Default::default()
+    }
+}
+
+pub fn armor_plate() -> Module {
+    Module {
+        module_id: 20353,
+        module_name: "1600mm Steel Plates II".to_string(),
+        group_id: 329,
+        slot: Some(SlotType::Low),
+        attributes: attrs(&[("cpu", 20.0)]),
+        effects: vec![Modifier::add(ModifierTarget::Ship, "armorHP", 4_000.0)],
+        ..Default::default()
+    }
+}
+
+/// SDE types for EFT tests: a Megathron and a Basilisk with their modules
+pub fn eft_types() -> EftTypes {
+    let mut plate = armor_plate();
+    plate.required_skills = vec![(3394, "Hull Upgrades".to_string(), 1)];
+    let mut remote_booster = remote_shield_booster();
+    remote_booster.required_skills = vec![(3422, "Shield Emission Systems".to_string(), 4)];
+    EftTypes {
+        ships: vec![
+            Ship {
+                ship_id: 641,
+                ship_name: "Megathron".to_string(),
+                required_skills: vec![(3336, "Gallente Battleship".to_string(), 1)],
+                skill_ranks: HashMap::from([(3336, 8.0)]),
+                ..Default::default()
+            },
+            Ship {
+                ship_id: 11985,
+                ship_name: "Basilisk".to_string(),
+                required_skills: vec![(12096, "Logistics Cruisers".to_string(), 1)],
+                ..Default::default()
+            },
+        ],
+        modules: vec![large_blaster(), plate, remote_booster],
+        charges: vec![hybrid_charge("Antimatter Charge L", 10.0, 1.0)],
+        drones: vec![Drone {
+            drone_id: 2185,
+            drone_name: "Hammerhead II".to_string(),
+            required_skills: vec![(3436, "Drones".to_string(), 1)],
+            quantity: 1,
+            ..Default::default()
+        }],
+        modes: vec![],
+    }
+}
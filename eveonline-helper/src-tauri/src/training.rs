@@ -0,0 +1,63 @@
+//! Skill training time estimates for EveOnline Helper
+//
+// Skill points needed for a level follow EVE's formula:
+//   SP(level) = 250 * rank * sqrt(32)^(level - 1)
+// Training speed is a flat skill points per minute. The default assumes a character
+// without attribute implants or remap (primary 20 + secondary 20 / 2). Ranks come from
+// the SDE types that require the skills; skills with an unknown rank count as rank 1.
+
+use crate::esi::FitVariant;
+use std::collections::HashMap;
+
+/// Skill points per minute of a character without implants or remap
+pub const DEFAULT_SP_PER_MINUTE: f64 = 30.0;
+
+/// Total skill points needed to reach a level (0-5) of a skill with the given rank
+pub fn skill_points_for_level(rank: f64, level: i32) -> f64 {
+    if level <= 0 {
+        return 0.0;
+    }
+    250.0 * rank * 32f64.sqrt().powi(level - 1)
+}
+
+/// Training rank of every skill the fit's hull, modules, charges and drones require
+pub fn fit_skill_ranks(fit: &FitVariant) -> HashMap<i64, f64> {
+    let modules = fit.modules.iter().map(|m| &m.skill_ranks);
+    let charges = fit
+        .modules
+        .iter()
+        .filter_map(|m| m.charge.as_ref())
+        .map(|c| &c.skill_ranks);
+    let drones = fit.drones.iter().map(|d| &d.skill_ranks);
+    std::iter::once(&fit.ship.skill_ranks)
+        .chain(modules)
+        .chain(charges)
+        .chain(drones)
+        .flat_map(|ranks| ranks.iter().map(|(skill_id, rank)| (*skill_id, *rank)))
+        .collect()
+}
+
+/// Seconds needed to train every entry of a skill plan
+///
+/// # Arguments
+/// * `plan` - (skill_id, skill_name, required_level, current_level) entries
+/// * `skill_ranks` - Training time multiplier per skill ID (rank 1 if missing)
+/// * `sp_per_minute` - The character's training speed
+pub fn plan_training_seconds(
+    plan: &[(i64, String, i32, i32)],
+    skill_ranks: &HashMap<i64, f64>,
+    sp_per_minute: f64,
+) -> f64 {
+    if sp_per_minute <= 0.0 {
+        return 0.0;
+    }
+    let skill_points: f64 = plan
+        .iter()
+        .map(|(skill_id, _, required, current)| {
+            let rank = skill_ranks.get(skill_id).copied().unwrap_or(1.0);
+            (skill_points_for_level(rank, *required) - skill_points_for_level(rank, *current))
+                .max(0.0)
+        })
+        .sum();
+    skill_points / sp_per_minute * 60.0
+}
@@ -0,0 +1,270 @@
+//! What-if skill simulation for EveOnline Helper
+//
+// Calculates a fit twice: with the character's current skills and with hypothetical
+// ones, and reports the stat delta (e.g. the DPS gained from Large Hybrid Turret V, or
+// what finishing the fit's skill plan actually buys). Everything else in the context
+// (implants, boosters, fleet boosts, environment) is the same for both calculations.
+//
+// Scenarios:
+// - current: the skills as they are (a zero delta, for the stats alone)
+// - after plan: every skill raised to the level a skill plan trains it to (the fit's
+//   own plan from `generate_skill_plan_for_fit` unless one is given)
+// - all at level: every skill the calculation knows about set to one level (the
+//   character's skills, the fit's requirements, hull bonus skills and `BONUS_SKILLS`)
+// - overrides: the current skills with the given levels (raised or lowered)
+
+use crate::esi::{generate_skill_plan_for_fit, FitVariant, Skill};
+use crate::fitting::{calculate_fit_stats_with, CalcContext, FitStats, StatDelta, BONUS_SKILLS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Hypothetical skills to evaluate a fit with
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SkillScenario {
+    Current,
+    /// Skills after training a plan of (skill_id, skill_name, required_level,
+    /// current_level) entries; the fit's own plan if None
+    AfterPlan {
+        #[serde(default)]
+        plan: Option<Vec<(i64, String, i32, i32)>>,
+    },
+    AllAtLevel {
+        level: i32,
+    },
+    /// Level per skill ID
+    Overrides {
+        levels: HashMap<i64, i32>,
+    },
+}
+
+/// A skill whose level differs in the scenario
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SkillChange {
+    pub skill_id: i64,
+    pub skill_name: Option<String>,
+    pub from_level: i32,
+    pub to_level: i32,
+}
+
+/// A fit's stats with current and hypothetical skills
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SkillSimulation {
+    pub changes: Vec<SkillChange>,
+    pub current: FitStats,
+    pub simulated: FitStats,
+    /// Simulated minus current
+    pub delta: StatDelta,
+}
+
+/// Set skill levels, adding skills the character does not have
+fn with_levels(skills: &[Skill], levels: impl IntoIterator<Item = (i64, i32)>) -> Vec<Skill> {
+    let mut skills = skills.to_vec();
+    for (skill_id, level) in levels {
+        match skills.iter_mut().find(|s| s.skill_id == skill_id) {
+            Some(skill) => skill.active_level = level,
+            None => skills.push(Skill {
+                skill_id,
+                skill_name: None,
+                active_level: level,
+            }),
+        }
+    }
+    skills
+}
+
+/// Every skill that can change the fit's stats or that it requires
+fn known_skills(fit: &FitVariant, skills: &[Skill]) -> Vec<i64> {
+    let required = fit
+        .ship
+        .required_skills
+        .iter()
+        .chain(fit.modules.iter().flat_map(|m| &m.required_skills))
+        .chain(
+            fit.modules
+                .iter()
+                .filter_map(|m| m.charge.as_ref())
+                .flat_map(|c| &c.required_skills),
+        )
+        .chain(fit.drones.iter().flat_map(|d| &d.required_skills))
+        .map(|(skill_id, _, _)| *skill_id);
+    let hull = fit.ship.bonuses.iter().filter_map(|b| b.skill_id);
+    let mut ids: Vec<i64> = skills.iter().map(|s| s.skill_id).collect();
+    for skill_id in required.chain(hull).chain(BONUS_SKILLS.iter().copied()) {
+        if !ids.contains(&skill_id) {
+            ids.push(skill_id);
+        }
+    }
+    ids
+}
+
+/// The character's skills in a scenario
+///
+/// # Arguments
+/// * `scenario` - The hypothetical skills
+/// * `fit` - The fit (for its skill plan and the skills it requires)
+/// * `skills` - The character's current skills
+pub fn scenario_skills(scenario: &SkillScenario, fit: &FitVariant, skills: &[Skill]) -> Vec<Skill> {
+    match scenario {
+        SkillScenario::Current => skills.to_vec(),
+        SkillScenario::AfterPlan { plan } => {
+            let plan = plan
+                .clone()
+                .unwrap_or_else(|| generate_skill_plan_for_fit(fit, skills));
+            let current = CalcContext::from_skills(skills);
+            let mut trained = with_levels(
+                skills,
+                plan.iter().map(|(skill_id, _, required, _)| {
+                    (*skill_id, current.skill_level(*skill_id).max(*required))
+                }),
+            );
+            // Keep the plan's names for skills the character did not have
+            for skill in &mut trained {
+                if skill.skill_name.is_none() {
+                    skill.skill_name = plan
+                        .iter()
+                        .find(|(skill_id, ..)| *skill_id == skill.skill_id)
+                        .map(|(_, name, ..)| name.clone());
+                }
+            }
+            trained
+        }
+        SkillScenario::AllAtLevel { level } => {
+            let level = (*level).clamp(0, 5);
+            with_levels(
+                skills,
+                known_skills(fit, skills).into_iter().map(|id| (id, level)),
+            )
+        }
+        SkillScenario::Overrides { levels } => with_levels(
+            skills,
+            levels
+                .iter()
+                .map(|(skill_id, level)| (*skill_id, (*level).clamp(0, 5))),
+        ),
+    }
+}
+
+/// Calculate a fit with current and hypothetical skills
+///
+/// # Arguments
+/// * `fit` - The fit to evaluate
+/// * `ctx` - The character's current skills and the rest of the context
+/// * `scenario` - The hypothetical skills
+///
+/// # Returns
+/// - The changed skill levels, both sets of stats and their delta
+pub fn simulate_skills(
+    fit: &FitVariant,
+    ctx: &CalcContext,
+    scenario: &SkillScenario,
+) -> SkillSimulation {
+    let simulated_ctx = CalcContext {
+        skills: scenario_skills(scenario, fit, &ctx.skills),
+        ..ctx.clone()
+    };
+    let mut changes: Vec<SkillChange> = simulated_ctx
+        .skills
+        .iter()
+        .filter(|s| s.active_level != ctx.skill_level(s.skill_id))
+        .map(|s| SkillChange {
+            skill_id: s.skill_id,
+            skill_name: s.skill_name.clone(),
+            from_level: ctx.skill_level(s.skill_id),
+            to_level: s.active_level,
+        })
+        .collect();
+    changes.sort_by_key(|c| c.skill_id);
+    let current = calculate_fit_stats_with(fit, ctx);
+    let simulated = calculate_fit_stats_with(fit, &simulated_ctx);
+    SkillSimulation {
+        changes,
+        delta: StatDelta::between(&current, &simulated),
+        current,
+        simulated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esi::{generate_skill_plan_for_fit, HullBonus, Skill};
+    use crate::fitting::{
+        calculate_fit_stats_with, skill_modifiers, CalcContext, Modifier, ModifierTarget,
+        BONUS_SKILLS,
+    };
+    use crate::test_support::{blaster_fit, hybrid_charge};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_skill_simulation() {
+        assert!(BONUS_SKILLS
+            .iter()
+            .all(|id| !skill_modifiers(*id).is_empty()));
+
+        let mut fit = blaster_fit();
+        fit.modules[0].charge = Some(hybrid_charge("Antimatter Charge L", 10.0, 1.0));
+        fit.ship.required_skills = vec![(3336, "Gallente Battleship".to_string(), 1)];
+        fit.ship.bonuses = vec![HullBonus {
+            skill_id: Some(3336),
+            modifier: Modifier::percent(ModifierTarget::ModuleSkill(3307), "damageMultiplier", 5.0),
+        }];
+        let ctx = CalcContext {
+            skills: vec![
+                Skill {
+                    skill_id: 3307,
+                    skill_name: Some("Large Hybrid Turret".to_string()),
+                    active_level: 4,
+                },
+                Skill {
+                    skill_id: 3336,
+                    skill_name: Some("Gallente Battleship".to_string()),
+                    active_level: 3,
+                },
+            ],
+            ..Default::default()
+        };
+        let current_dps = calculate_fit_stats_with(&fit, &ctx).dps;
+
+        let current = simulate_skills(&fit, &ctx, &SkillScenario::Current);
+        assert!(current.changes.is_empty());
+        assert_eq!(current.delta.dps, 0.0);
+        assert_eq!(current.current.dps, current_dps);
+
+        // Large Hybrid Turret V: 5% more damage per level, 1.25 / 1.2 in total
+        let turret_v = simulate_skills(
+            &fit,
+            &ctx,
+            &SkillScenario::Overrides {
+                levels: HashMap::from([(3307, 5)]),
+            },
+        );
+        assert_eq!(turret_v.changes.len(), 1);
+        assert_eq!(
+            (turret_v.changes[0].from_level, turret_v.changes[0].to_level),
+            (4, 5)
+        );
+        assert!((turret_v.simulated.dps / current_dps - 1.25 / 1.2).abs() < 1e-9);
+        assert!((turret_v.delta.dps - (turret_v.simulated.dps - current_dps)).abs() < 1e-9);
+
+        // The fit's own plan trains the turret skill to V; the hull skill is already enough
+        let plan = generate_skill_plan_for_fit(&fit, &ctx.skills);
+        let after_plan = simulate_skills(&fit, &ctx, &SkillScenario::AfterPlan { plan: None });
+        assert!(after_plan
+            .changes
+            .iter()
+            .all(|c| plan.iter().any(|p| p.0 == c.skill_id)));
+        assert!((after_plan.delta.dps - turret_v.delta.dps).abs() < 1e-9);
+
+        // All at V also maxes the hull bonus; all at 0 loses both skills' bonuses
+        let all_v = simulate_skills(&fit, &ctx, &SkillScenario::AllAtLevel { level: 5 });
+        assert!((all_v.simulated.dps / current_dps - (1.25 * 1.25) / (1.2 * 1.15)).abs() < 1e-9);
+        assert!(all_v
+            .changes
+            .iter()
+            .any(|c| c.skill_id == 3449 && c.to_level == 5));
+        let all_zero = simulate_skills(&fit, &ctx, &SkillScenario::AllAtLevel { level: 0 });
+        assert!((all_zero.simulated.dps / current_dps - 1.0 / (1.2 * 1.15)).abs() < 1e-9);
+        assert!(all_zero.delta.dps < 0.0);
+    }
+}
@@ -1,82 +0,0 @@
-//! Unit and integration tests for the ship fitting recommendation engine
-//
-// These tests cover:
-// - Skill-to-ship/module matching
-// - Fit variant generation
-// - Fit validation
-// - Alternative fit suggestions
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::esi::{
-        export_skill_plan_evemon, generate_fit_variants, generate_skill_plan_for_fit,
-        match_skills_to_ships_and_modules, suggest_alternative_fits, validate_fit, FitVariant,
-        Module, Ship, Skill,
-    };
-
-    #[test]
-    fn test_match_skills_to_ships_and_modules() {
-        // TODO: Add mock skills, ships, and modules and test matching logic
-    }
-
-    #[test]
-    fn test_generate_fit_variants() {
-        // TODO: Add mock ship, activity, and modules and test fit generation
-    }
-
-    #[test]
-    fn test_validate_fit() {
-        // TODO: Add mock fit and skills and test validation logic
-    }
-
-    #[test]
-    fn test_suggest_alternative_fits() {
-        // TODO: Add mock fit, skills, and modules and test alternative suggestions
-    }
-
-    #[test]
-    fn test_generate_skill_plan_for_fit() {
-        // Mock fit: requires Spaceship Command 4 (ship), Engineering 3 (module)
-        let fit = FitVariant {
-            fit_name: "Test Fit".to_string(),
-            ship: Ship {
-                ship_id: 1,
-                ship_name: "Test Ship".to_string(),
-            },
-            modules: vec![Module {
-                module_id: 10,
-                module_name: "Test Module".to_string(),
-            }],
-            rationale: "Test rationale".to_string(),
-        };
-        // User has Spaceship Command 2, no Engineering
-        let user_skills = vec![Skill {
-            skill_id: 333,
-            skill_name: Some("Spaceship Command".to_string()),
-            active_level: 2,
-        }];
-        let plan = generate_skill_plan_for_fit(&fit, &user_skills);
-        // Should require Spaceship Command 4 (current 2) and Engineering 3 (current 0)
-        assert_eq!(plan.len(), 2);
-        assert!(plan.iter().any(|(id, name, req, cur)| *id == 333
-            && name == "Spaceship Command"
-            && *req == 4
-            && *cur == 2));
-        assert!(plan.iter().any(|(id, name, req, cur)| *id == 444
-            && name == "Engineering"
-            && *req == 3
-            && *cur == 0));
-    }
-
-    #[test]
-    fn test_export_skill_plan_evemon() {
-        let plan = vec![
-            (333, "Spaceship Command".to_string(), 4, 2),
-            (444, "Engineering".to_string(), 3, 0),
-        ];
-        let export = export_skill_plan_evemon(&plan);
-        let expected = "Spaceship Command Level 4\nEngineering Level 3";
-        assert_eq!(export, expected);
-    }
-}